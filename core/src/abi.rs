@@ -0,0 +1,473 @@
+//! Solidity ABI layout of the committed public values.
+//!
+//! The SP1 program commits `abi.encode(PublicValuesStruct)`, so a contract
+//! can decode the proof's public values with
+//! `abi.decode(publicValues, (PublicValuesStruct))`. The versus program
+//! commits `abi.encode(VersusPublicValuesStruct)` the same way, the
+//! leaderboard program `abi.encode(LeaderboardPublicValuesStruct)`, the
+//! threshold program `abi.encode(ThresholdPublicValuesStruct)`, the
+//! achievements program `abi.encode(AchievementPublicValuesStruct)` and the
+//! chunk program `abi.encode(ChunkPublicValuesStruct)` and the aggregate
+//! program `abi.encode(AggregatePublicValuesStruct)`. The chain program
+//! commits `PublicValuesStruct`, like the single-game program.
+//!
+//! `PublicValuesStruct` opens with a `uint16 version`, currently
+//! [`PUBLIC_VALUES_VERSION`], so fields can be added without verifiers
+//! misreading older proofs. Every layout is a fixed number of words and
+//! keeps `version` first; [`PublicValues::decode`] reads the current
+//! layout and version 1, the unversioned `LegacyPublicValuesStruct`
+//! committed before.
+
+use alloc::vec::Vec;
+use alloy_sol_types::{sol, SolValue};
+
+use crate::inputs::{
+    AchievementPublicInputs, AggregatePublicInputs, ChunkPublicInputs, LeaderboardPublicInputs,
+    SnakeGamePublicInputs, ThresholdPublicInputs, VersusPublicInputs,
+};
+
+/// Layout of the game public values [`encode_public_inputs`] writes.
+pub const PUBLIC_VALUES_VERSION: u16 = 2;
+
+/// Encoded size of version 1, which had no version field.
+const LEGACY_SIZE: usize = 15 * 32;
+
+sol! {
+    struct PublicValuesStruct {
+        uint16 version;
+        uint64 score;
+        uint32 snakeLength;
+        uint32 gridWidth;
+        uint32 gridHeight;
+        bool wrapWalls;
+        bytes32 levelHash;
+        uint64 seed;
+        bytes32 configHash;
+        bytes32 gameStateHash;
+        address player;
+        uint32 challengeDay;
+        bytes32 challengeSaltHash;
+        bytes32 nullifier;
+        bytes32 playerKey;
+        bytes32 tickChain;
+    }
+
+    struct LegacyPublicValuesStruct {
+        uint64 score;
+        uint32 snakeLength;
+        uint32 gridWidth;
+        uint32 gridHeight;
+        bool wrapWalls;
+        bytes32 levelHash;
+        uint64 seed;
+        bytes32 configHash;
+        bytes32 gameStateHash;
+        address player;
+        uint32 challengeDay;
+        bytes32 challengeSaltHash;
+        bytes32 nullifier;
+        bytes32 playerKey;
+        bytes32 tickChain;
+    }
+
+    struct VersusPublicValuesStruct {
+        uint64 score0;
+        uint64 score1;
+        uint8 winner;
+        bytes32 levelHash;
+        uint64 seed;
+        bytes32 configHash;
+        bytes32 matchStateHash;
+        address player0;
+        address player1;
+    }
+
+    struct LeaderboardPublicValuesStruct {
+        bytes32 gameVkeyDigest;
+        bytes32 configHash;
+        uint64 seed;
+        bytes32 root;
+        address[] players;
+        uint64[] scores;
+    }
+
+    struct ThresholdPublicValuesStruct {
+        uint64 threshold;
+        bytes32 configHash;
+        address player;
+    }
+
+    struct AchievementPublicValuesStruct {
+        uint32 achievements;
+        bytes32 configHash;
+        address player;
+    }
+
+    struct ChunkPublicValuesStruct {
+        bytes32 inputHash;
+        bytes32 outputHash;
+    }
+
+    struct AggregatePublicValuesStruct {
+        bytes32 gameVkeyDigest;
+        address[] players;
+        uint64[] scores;
+        bytes32[] configHashes;
+        bytes32[] nullifiers;
+        uint64[] seeds;
+        bytes32[] playerKeys;
+    }
+}
+
+impl From<&SnakeGamePublicInputs> for PublicValuesStruct {
+    fn from(inputs: &SnakeGamePublicInputs) -> Self {
+        PublicValuesStruct {
+            version: PUBLIC_VALUES_VERSION,
+            score: inputs.score,
+            snakeLength: inputs.snake_length,
+            gridWidth: inputs.grid_width,
+            gridHeight: inputs.grid_height,
+            wrapWalls: inputs.wrap_walls,
+            levelHash: inputs.level_hash.into(),
+            seed: inputs.seed,
+            configHash: inputs.config_hash.into(),
+            gameStateHash: inputs.game_state_hash.into(),
+            player: inputs.player.into(),
+            challengeDay: inputs.challenge_day,
+            challengeSaltHash: inputs.challenge_salt_hash.into(),
+            nullifier: inputs.nullifier.into(),
+            playerKey: inputs.player_key.into(),
+            tickChain: inputs.tick_chain.into(),
+        }
+    }
+}
+
+impl From<PublicValuesStruct> for SnakeGamePublicInputs {
+    fn from(values: PublicValuesStruct) -> Self {
+        SnakeGamePublicInputs {
+            game_state_hash: values.gameStateHash.into(),
+            score: values.score,
+            snake_length: values.snakeLength,
+            grid_width: values.gridWidth,
+            grid_height: values.gridHeight,
+            wrap_walls: values.wrapWalls,
+            level_hash: values.levelHash.into(),
+            seed: values.seed,
+            config_hash: values.configHash.into(),
+            player: values.player.into(),
+            challenge_day: values.challengeDay,
+            challenge_salt_hash: values.challengeSaltHash.into(),
+            nullifier: values.nullifier.into(),
+            player_key: values.playerKey.into(),
+            tick_chain: values.tickChain.into(),
+        }
+    }
+}
+
+impl From<LegacyPublicValuesStruct> for SnakeGamePublicInputs {
+    fn from(values: LegacyPublicValuesStruct) -> Self {
+        SnakeGamePublicInputs {
+            game_state_hash: values.gameStateHash.into(),
+            score: values.score,
+            snake_length: values.snakeLength,
+            grid_width: values.gridWidth,
+            grid_height: values.gridHeight,
+            wrap_walls: values.wrapWalls,
+            level_hash: values.levelHash.into(),
+            seed: values.seed,
+            config_hash: values.configHash.into(),
+            player: values.player.into(),
+            challenge_day: values.challengeDay,
+            challenge_salt_hash: values.challengeSaltHash.into(),
+            nullifier: values.nullifier.into(),
+            player_key: values.playerKey.into(),
+            tick_chain: values.tickChain.into(),
+        }
+    }
+}
+
+/// Game public values in any layout this build reads.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicValues {
+    /// The layout they were committed in.
+    pub version: u16,
+    pub inputs: SnakeGamePublicInputs,
+}
+
+impl PublicValues {
+    /// Decodes version 1 or [`PUBLIC_VALUES_VERSION`] public values. Fails
+    /// on malformed bytes and on any other version, including newer ones
+    /// this build cannot read. Version 1 is the current layout without its
+    /// `version` word:
+    ///
+    /// ```
+    /// use snake_core::abi::{encode_public_inputs, PublicValues, PUBLIC_VALUES_VERSION};
+    /// use snake_core::inputs::SnakeGamePublicInputs;
+    ///
+    /// let inputs = SnakeGamePublicInputs {
+    ///     score: 120,
+    ///     snake_length: 9,
+    ///     grid_width: 20,
+    ///     grid_height: 16,
+    ///     wrap_walls: true,
+    ///     seed: 7,
+    ///     player: [0xaa; 20],
+    ///     tick_chain: [0x5c; 32],
+    ///     ..Default::default()
+    /// };
+    /// let current = encode_public_inputs(&inputs);
+    /// let decoded = PublicValues::decode(&current).unwrap();
+    /// assert_eq!(decoded.version, PUBLIC_VALUES_VERSION);
+    /// assert_eq!(decoded.inputs, inputs);
+    ///
+    /// let legacy = PublicValues::decode(&current[32..]).unwrap();
+    /// assert_eq!(legacy.version, 1);
+    /// assert_eq!(legacy.inputs, inputs);
+    ///
+    /// let mut newer = current.clone();
+    /// newer[31] += 1;
+    /// assert!(PublicValues::decode(&newer).is_err());
+    /// ```
+    pub fn decode(bytes: &[u8]) -> Result<PublicValues, alloy_sol_types::Error> {
+        if bytes.len() == LEGACY_SIZE {
+            return Ok(PublicValues {
+                version: 1,
+                inputs: LegacyPublicValuesStruct::abi_decode(bytes)?.into(),
+            });
+        }
+        let values = PublicValuesStruct::abi_decode(bytes)?;
+        if values.version != PUBLIC_VALUES_VERSION {
+            return Err(alloy_sol_types::Error::custom(
+                "unsupported public values version",
+            ));
+        }
+        Ok(PublicValues {
+            version: values.version,
+            inputs: values.into(),
+        })
+    }
+}
+
+/// ABI-encodes `inputs` exactly as the guest commits them, in layout
+/// [`PUBLIC_VALUES_VERSION`].
+pub fn encode_public_inputs(inputs: &SnakeGamePublicInputs) -> Vec<u8> {
+    PublicValuesStruct::from(inputs).abi_encode()
+}
+
+/// The inputs of game public values in any layout [`PublicValues::decode`]
+/// reads.
+pub fn decode_public_inputs(bytes: &[u8]) -> Result<SnakeGamePublicInputs, alloy_sol_types::Error> {
+    PublicValues::decode(bytes).map(|values| values.inputs)
+}
+
+impl From<&VersusPublicInputs> for VersusPublicValuesStruct {
+    fn from(inputs: &VersusPublicInputs) -> Self {
+        let [score0, score1] = inputs.scores;
+        let [player0, player1] = inputs.players;
+        VersusPublicValuesStruct {
+            score0,
+            score1,
+            winner: inputs.winner,
+            levelHash: inputs.level_hash.into(),
+            seed: inputs.seed,
+            configHash: inputs.config_hash.into(),
+            matchStateHash: inputs.match_state_hash.into(),
+            player0: player0.into(),
+            player1: player1.into(),
+        }
+    }
+}
+
+impl From<VersusPublicValuesStruct> for VersusPublicInputs {
+    fn from(values: VersusPublicValuesStruct) -> Self {
+        VersusPublicInputs {
+            match_state_hash: values.matchStateHash.into(),
+            scores: [values.score0, values.score1],
+            winner: values.winner,
+            level_hash: values.levelHash.into(),
+            seed: values.seed,
+            config_hash: values.configHash.into(),
+            players: [values.player0.into(), values.player1.into()],
+        }
+    }
+}
+
+/// ABI-encodes `inputs` exactly as the versus guest commits them.
+pub fn encode_versus_inputs(inputs: &VersusPublicInputs) -> Vec<u8> {
+    VersusPublicValuesStruct::from(inputs).abi_encode()
+}
+
+pub fn decode_versus_inputs(bytes: &[u8]) -> Result<VersusPublicInputs, alloy_sol_types::Error> {
+    VersusPublicValuesStruct::abi_decode(bytes).map(Into::into)
+}
+
+impl From<&LeaderboardPublicInputs> for LeaderboardPublicValuesStruct {
+    fn from(inputs: &LeaderboardPublicInputs) -> Self {
+        LeaderboardPublicValuesStruct {
+            gameVkeyDigest: inputs.game_vkey_digest.into(),
+            configHash: inputs.config_hash.into(),
+            seed: inputs.seed,
+            root: inputs.root.into(),
+            players: inputs.players.iter().map(|&player| player.into()).collect(),
+            scores: inputs.scores.clone(),
+        }
+    }
+}
+
+impl From<LeaderboardPublicValuesStruct> for LeaderboardPublicInputs {
+    fn from(values: LeaderboardPublicValuesStruct) -> Self {
+        LeaderboardPublicInputs {
+            game_vkey_digest: values.gameVkeyDigest.into(),
+            config_hash: values.configHash.into(),
+            seed: values.seed,
+            root: values.root.into(),
+            players: values.players.into_iter().map(Into::into).collect(),
+            scores: values.scores,
+        }
+    }
+}
+
+/// ABI-encodes `inputs` exactly as the leaderboard guest commits them.
+pub fn encode_leaderboard_inputs(inputs: &LeaderboardPublicInputs) -> Vec<u8> {
+    LeaderboardPublicValuesStruct::from(inputs).abi_encode()
+}
+
+pub fn decode_leaderboard_inputs(
+    bytes: &[u8],
+) -> Result<LeaderboardPublicInputs, alloy_sol_types::Error> {
+    LeaderboardPublicValuesStruct::abi_decode(bytes).map(Into::into)
+}
+
+impl From<&ThresholdPublicInputs> for ThresholdPublicValuesStruct {
+    fn from(inputs: &ThresholdPublicInputs) -> Self {
+        ThresholdPublicValuesStruct {
+            threshold: inputs.threshold,
+            configHash: inputs.config_hash.into(),
+            player: inputs.player.into(),
+        }
+    }
+}
+
+impl From<ThresholdPublicValuesStruct> for ThresholdPublicInputs {
+    fn from(values: ThresholdPublicValuesStruct) -> Self {
+        ThresholdPublicInputs {
+            threshold: values.threshold,
+            config_hash: values.configHash.into(),
+            player: values.player.into(),
+        }
+    }
+}
+
+/// ABI-encodes `inputs` exactly as the threshold guest commits them.
+pub fn encode_threshold_inputs(inputs: &ThresholdPublicInputs) -> Vec<u8> {
+    ThresholdPublicValuesStruct::from(inputs).abi_encode()
+}
+
+pub fn decode_threshold_inputs(
+    bytes: &[u8],
+) -> Result<ThresholdPublicInputs, alloy_sol_types::Error> {
+    ThresholdPublicValuesStruct::abi_decode(bytes).map(Into::into)
+}
+
+impl From<&AchievementPublicInputs> for AchievementPublicValuesStruct {
+    fn from(inputs: &AchievementPublicInputs) -> Self {
+        AchievementPublicValuesStruct {
+            achievements: inputs.achievements,
+            configHash: inputs.config_hash.into(),
+            player: inputs.player.into(),
+        }
+    }
+}
+
+impl From<AchievementPublicValuesStruct> for AchievementPublicInputs {
+    fn from(values: AchievementPublicValuesStruct) -> Self {
+        AchievementPublicInputs {
+            achievements: values.achievements,
+            config_hash: values.configHash.into(),
+            player: values.player.into(),
+        }
+    }
+}
+
+/// ABI-encodes `inputs` exactly as the achievements guest commits them.
+pub fn encode_achievement_inputs(inputs: &AchievementPublicInputs) -> Vec<u8> {
+    AchievementPublicValuesStruct::from(inputs).abi_encode()
+}
+
+pub fn decode_achievement_inputs(
+    bytes: &[u8],
+) -> Result<AchievementPublicInputs, alloy_sol_types::Error> {
+    AchievementPublicValuesStruct::abi_decode(bytes).map(Into::into)
+}
+
+impl From<&ChunkPublicInputs> for ChunkPublicValuesStruct {
+    fn from(inputs: &ChunkPublicInputs) -> Self {
+        ChunkPublicValuesStruct {
+            inputHash: inputs.input_hash.into(),
+            outputHash: inputs.output_hash.into(),
+        }
+    }
+}
+
+impl From<ChunkPublicValuesStruct> for ChunkPublicInputs {
+    fn from(values: ChunkPublicValuesStruct) -> Self {
+        ChunkPublicInputs {
+            input_hash: values.inputHash.into(),
+            output_hash: values.outputHash.into(),
+        }
+    }
+}
+
+/// ABI-encodes `inputs` exactly as the chunk guest commits them.
+pub fn encode_chunk_inputs(inputs: &ChunkPublicInputs) -> Vec<u8> {
+    ChunkPublicValuesStruct::from(inputs).abi_encode()
+}
+
+pub fn decode_chunk_inputs(bytes: &[u8]) -> Result<ChunkPublicInputs, alloy_sol_types::Error> {
+    ChunkPublicValuesStruct::abi_decode(bytes).map(Into::into)
+}
+
+impl From<&AggregatePublicInputs> for AggregatePublicValuesStruct {
+    fn from(inputs: &AggregatePublicInputs) -> Self {
+        AggregatePublicValuesStruct {
+            gameVkeyDigest: inputs.game_vkey_digest.into(),
+            players: inputs.players.iter().map(|&player| player.into()).collect(),
+            scores: inputs.scores.clone(),
+            configHashes: inputs
+                .config_hashes
+                .iter()
+                .map(|&hash| hash.into())
+                .collect(),
+            nullifiers: (inputs.nullifiers.iter())
+                .map(|&nullifier| nullifier.into())
+                .collect(),
+            seeds: inputs.seeds.clone(),
+            playerKeys: (inputs.player_keys.iter()).map(|&key| key.into()).collect(),
+        }
+    }
+}
+
+impl From<AggregatePublicValuesStruct> for AggregatePublicInputs {
+    fn from(values: AggregatePublicValuesStruct) -> Self {
+        AggregatePublicInputs {
+            game_vkey_digest: values.gameVkeyDigest.into(),
+            players: values.players.into_iter().map(Into::into).collect(),
+            scores: values.scores,
+            config_hashes: values.configHashes.into_iter().map(Into::into).collect(),
+            nullifiers: values.nullifiers.into_iter().map(Into::into).collect(),
+            seeds: values.seeds,
+            player_keys: values.playerKeys.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// ABI-encodes `inputs` exactly as the aggregate guest commits them.
+pub fn encode_aggregate_inputs(inputs: &AggregatePublicInputs) -> Vec<u8> {
+    AggregatePublicValuesStruct::from(inputs).abi_encode()
+}
+
+pub fn decode_aggregate_inputs(
+    bytes: &[u8],
+) -> Result<AggregatePublicInputs, alloy_sol_types::Error> {
+    AggregatePublicValuesStruct::abi_decode(bytes).map(Into::into)
+}
@@ -0,0 +1,67 @@
+//! Badges earned during a game.
+//!
+//! An [`AchievementTracker`] watches every tick of a replay (see
+//! [`crate::verify::replay_moves_with`]) and reports the badges as bit
+//! flags. The achievements program commits only those flags with the config
+//! hash and player, so a player can prove a badge while their moves, score
+//! and strategy stay private. Flags are only ever added; renumbering one
+//! changes what every existing proof claims.
+
+use crate::{GameState, StepOutcome};
+
+/// Ate at least 50 foods of any kind.
+pub const ATE_50_FOODS: u32 = 1 << 0;
+/// Played at least 5000 ticks.
+pub const SURVIVED_5000_TICKS: u32 = 1 << 1;
+/// The head never entered a cell on the edge of the arena. Never earned in
+/// wrap-around mode, which has no walls to stay away from.
+pub const NEVER_NEAR_WALL: u32 = 1 << 2;
+
+const FOODS_FOR_BADGE: u32 = 50;
+const TICKS_FOR_BADGE: u32 = 5000;
+
+/// Progress towards every badge during one game.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AchievementTracker {
+    foods_eaten: u32,
+    near_wall: bool,
+}
+
+impl AchievementTracker {
+    /// Starts tracking a game from its initial state.
+    pub fn new(initial: &GameState) -> AchievementTracker {
+        AchievementTracker {
+            foods_eaten: 0,
+            near_wall: next_to_wall(initial),
+        }
+    }
+
+    /// Records one tick that produced `outcome` and left the game in
+    /// `state`.
+    pub fn observe(&mut self, state: &GameState, outcome: StepOutcome) {
+        if outcome == StepOutcome::AteFood {
+            self.foods_eaten += 1;
+        }
+        self.near_wall |= next_to_wall(state);
+    }
+
+    /// The badges earned by the game that ended in `state`.
+    pub fn finish(&self, state: &GameState) -> u32 {
+        let mut flags = 0;
+        if self.foods_eaten >= FOODS_FOR_BADGE {
+            flags |= ATE_50_FOODS;
+        }
+        if state.ticks() >= TICKS_FOR_BADGE {
+            flags |= SURVIVED_5000_TICKS;
+        }
+        if !self.near_wall && !state.wrap_walls() {
+            flags |= NEVER_NEAR_WALL;
+        }
+        flags
+    }
+}
+
+fn next_to_wall(state: &GameState) -> bool {
+    let (arena, head) = (state.arena(), state.head());
+    head.x == arena.min.x || head.x == arena.max.x || head.y == arena.min.y || head.y == arena.max.y
+}
@@ -0,0 +1,125 @@
+//! Snake bodies stored as packed cells.
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::level::MAX_GRID_SIZE;
+use crate::{simd, Position};
+
+/// A grid cell packed into one word, `y` in the high half and `x` in the
+/// low half. Grids are at most [`MAX_GRID_SIZE`] cells a side, so both
+/// fit in 16 bits.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "Position", into = "Position")
+)]
+pub(crate) struct Cell(u32);
+
+impl Cell {
+    /// Packs `pos`, or `None` if it lies outside any grid.
+    pub(crate) fn new(pos: Position) -> Option<Cell> {
+        let coordinate = |value: i32| (0..MAX_GRID_SIZE).contains(&value).then_some(value as u32);
+        Some(Cell(coordinate(pos.y)? << 16 | coordinate(pos.x)?))
+    }
+
+    #[cfg(feature = "simd")]
+    pub(crate) fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub(crate) fn position(self) -> Position {
+        Position::new((self.0 & 0xffff) as i32, (self.0 >> 16) as i32)
+    }
+}
+
+impl From<Position> for Cell {
+    /// Wraps coordinates outside any grid, which only a corrupt saved game
+    /// has.
+    fn from(pos: Position) -> Cell {
+        Cell((pos.y as u32 & 0xffff) << 16 | (pos.x as u32 & 0xffff))
+    }
+}
+
+impl From<Cell> for Position {
+    fn from(cell: Cell) -> Position {
+        cell.position()
+    }
+}
+
+impl fmt::Debug for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.position().fmt(f)
+    }
+}
+
+/// A snake's segments, head first. Moving pushes the new head at the front
+/// and pops the tail from the back of a ring buffer of packed cells, half
+/// the size of [`Position`]s.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Body {
+    cells: VecDeque<Cell>,
+}
+
+impl Body {
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn head(&self) -> Option<Position> {
+        self.cells.front().map(|cell| cell.position())
+    }
+
+    pub fn tail(&self) -> Option<Position> {
+        self.cells.back().map(|cell| cell.position())
+    }
+
+    /// The segment `index` cells behind the head.
+    pub fn get(&self, index: usize) -> Option<Position> {
+        self.cells.get(index).map(|cell| cell.position())
+    }
+
+    /// Segments, head first.
+    pub fn iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Position> + ExactSizeIterator + Clone + '_ {
+        self.cells.iter().map(|cell| cell.position())
+    }
+
+    pub fn contains(&self, pos: Position) -> bool {
+        self.contains_in(self.len(), pos)
+    }
+
+    /// Whether `pos` is one of the first `len` segments.
+    pub(crate) fn contains_in(&self, len: usize, pos: Position) -> bool {
+        Cell::new(pos).is_some_and(|cell| simd::deque_contains(&self.cells, len, cell))
+    }
+
+    /// Adds a head on a grid cell.
+    pub(crate) fn push_front(&mut self, pos: Position) {
+        self.cells.push_front(Cell::from(pos));
+    }
+
+    pub(crate) fn pop_back(&mut self) -> Option<Position> {
+        self.cells.pop_back().map(|cell| cell.position())
+    }
+}
+
+impl From<Vec<Position>> for Body {
+    fn from(segments: Vec<Position>) -> Body {
+        Body {
+            cells: segments.into_iter().map(Cell::from).collect(),
+        }
+    }
+}
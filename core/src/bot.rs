@@ -0,0 +1,265 @@
+//! Computer players.
+//!
+//! A [`Strategy`] looks at a [`GameState`] and picks the direction for the
+//! next tick. Bots only read the state, so the turn they choose goes
+//! through [`GameState::change_direction`] and the move log like any
+//! player's, and a bot game replays and proves like a human one.
+//! [`generate_game`] uses this to produce known-good replays for tests and
+//! prover benchmarks.
+
+use alloc::collections::BinaryHeap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+use crate::{Direction, FoodKind, GameState, Level, Position, Replay, SnakeError};
+
+/// How a bot chooses its next turn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Strategy {
+    /// Steps towards the nearest food by straight-line distance, avoiding
+    /// only the cells next to the head.
+    Greedy,
+    /// Follows the shortest path around the snake and obstacles to the
+    /// nearest food, falling back to the move with the most room.
+    #[default]
+    AStar,
+    /// Walks a fixed cycle through every cell, which never crashes on an
+    /// open board with an even side. Boards without such a cycle fall back
+    /// to [`Strategy::AStar`].
+    Hamiltonian,
+}
+
+impl Strategy {
+    /// The direction this strategy takes from `state`. When every move
+    /// crashes the snake keeps its heading.
+    pub fn next_direction(self, state: &GameState) -> Direction {
+        let direction = match self {
+            Strategy::Greedy => greedy(state),
+            Strategy::AStar => a_star(state).or_else(|| roomiest(state)),
+            Strategy::Hamiltonian => hamiltonian(state)
+                .or_else(|| a_star(state))
+                .or_else(|| roomiest(state)),
+        };
+        direction.unwrap_or(state.direction())
+    }
+}
+
+/// Board [`generate_game`] plays on.
+pub const GENERATED_GRID_SIZE: i32 = 20;
+
+/// Plays a legal game on the classic [`GENERATED_GRID_SIZE`] square board
+/// with the [`Strategy::Hamiltonian`] bot until it scores at least
+/// `target_score` or fills the board, then runs it straight into a wall.
+/// The returned replay always verifies.
+pub fn generate_game(seed: u64, target_score: u64) -> Replay {
+    let level = Level::classic(GENERATED_GRID_SIZE, GENERATED_GRID_SIZE, false);
+    generate_game_on(level, seed, target_score, Strategy::Hamiltonian)
+        .expect("the classic board is playable")
+}
+
+/// Like [`generate_game`] on any level with any strategy. A bot that
+/// crashes first ends below `target_score`, and on a wrap-around level the
+/// game only ends once the bot crashes or a limit of the level ends it.
+/// Fails with [`SnakeError::BadLayout`] if the level is not playable.
+pub fn generate_game_on(
+    level: Level,
+    seed: u64,
+    target_score: u64,
+    strategy: Strategy,
+) -> Result<Replay, SnakeError> {
+    let mut state = GameState::from_level(level, seed)?;
+    while !state.is_game_over() {
+        let direction = strategy.next_direction(&state);
+        // Repeating the heading would end the game under the EndGame policy
+        if state.score() < target_score && direction != state.direction() {
+            state.change_direction(direction)?;
+        }
+        state.step()?;
+    }
+    Ok(Replay::from_game(&state))
+}
+
+/// Whether the head may enter `pos` next tick. The tail is free unless the
+/// snake is still growing or it is also the neck, and poison is avoided.
+fn is_safe(state: &GameState, pos: Position) -> bool {
+    let snake = state.snake();
+    let tail_moves = state.pending_growth() == 0 && snake.len() > 2 && snake.tail() == Some(pos);
+    let poison = state
+        .foods()
+        .iter()
+        .zip(state.food_kinds())
+        .any(|(&food, &kind)| food == pos && kind == FoodKind::Poison);
+    state.in_bounds(pos)
+        && !state.obstacles().contains(&pos)
+        && (tail_moves || !snake.contains(pos))
+        && !poison
+}
+
+/// Directions the head can take without crashing this tick, in
+/// [`Direction::ALL`] order.
+fn safe_moves(state: &GameState) -> impl Iterator<Item = Direction> + '_ {
+    Direction::ALL
+        .into_iter()
+        .filter(|&direction| is_safe(state, state.neighbour(state.head(), direction)))
+}
+
+/// Food worth eating.
+fn targets(state: &GameState) -> impl Iterator<Item = Position> + '_ {
+    state
+        .foods()
+        .iter()
+        .zip(state.food_kinds())
+        .filter(|(_, &kind)| kind != FoodKind::Poison)
+        .map(|(&food, _)| food)
+}
+
+/// Manhattan distance, measured around the edges in wrap mode.
+fn distance(state: &GameState, from: Position, to: Position) -> u32 {
+    let (dx, dy) = (from.x.abs_diff(to.x), from.y.abs_diff(to.y));
+    if state.wrap_walls() {
+        let (width, height) = (state.grid_width() as u32, state.grid_height() as u32);
+        dx.min(width - dx) + dy.min(height - dy)
+    } else {
+        dx + dy
+    }
+}
+
+fn nearest_target(state: &GameState, from: Position) -> Option<u32> {
+    targets(state).map(|food| distance(state, from, food)).min()
+}
+
+fn greedy(state: &GameState) -> Option<Direction> {
+    safe_moves(state).min_by_key(|&direction| {
+        let next = state.neighbour(state.head(), direction);
+        nearest_target(state, next).unwrap_or(0)
+    })
+}
+
+fn index(state: &GameState, pos: Position) -> usize {
+    pos.y as usize * state.grid_width() as usize + pos.x as usize
+}
+
+/// First step of the shortest path from the head to any food, searched
+/// with A* over the board as it stands.
+fn a_star(state: &GameState) -> Option<Direction> {
+    let head = state.head();
+    let cells = (state.grid_width() * state.grid_height()) as usize;
+    let mut cost = vec![u32::MAX; cells];
+    let mut first_step: Vec<Option<Direction>> = vec![None; cells];
+    let mut open = BinaryHeap::new();
+    cost[index(state, head)] = 0;
+    open.push(Reverse((
+        nearest_target(state, head)?,
+        0u32,
+        head.y,
+        head.x,
+    )));
+
+    while let Some(Reverse((_, steps, y, x))) = open.pop() {
+        let cell = Position::new(x, y);
+        if steps > cost[index(state, cell)] {
+            continue;
+        }
+        if steps > 0 && targets(state).any(|food| food == cell) {
+            return first_step[index(state, cell)];
+        }
+        for direction in Direction::ALL {
+            let next = state.neighbour(cell, direction);
+            if !is_safe(state, next) || cost[index(state, next)] <= steps + 1 {
+                continue;
+            }
+            cost[index(state, next)] = steps + 1;
+            first_step[index(state, next)] = first_step[index(state, cell)].or(Some(direction));
+            let estimate = steps + 1 + nearest_target(state, next).unwrap_or(0);
+            open.push(Reverse((estimate, steps + 1, next.y, next.x)));
+        }
+    }
+    None
+}
+
+/// Number of cells reachable from `start` through safe cells.
+fn room(state: &GameState, start: Position) -> usize {
+    let mut seen = vec![false; (state.grid_width() * state.grid_height()) as usize];
+    seen[index(state, start)] = true;
+    let mut queue = vec![start];
+    let mut count = 0;
+    while let Some(cell) = queue.pop() {
+        count += 1;
+        for direction in Direction::ALL {
+            let next = state.neighbour(cell, direction);
+            if is_safe(state, next) && !seen[index(state, next)] {
+                seen[index(state, next)] = true;
+                queue.push(next);
+            }
+        }
+    }
+    count
+}
+
+/// The safe move leading into the largest open area, so a snake with no
+/// way to food does not trap itself; the first such move on a tie.
+fn roomiest(state: &GameState) -> Option<Direction> {
+    safe_moves(state)
+        .fold(None, |best: Option<(Direction, usize)>, direction| {
+            let space = room(state, state.neighbour(state.head(), direction));
+            match best {
+                Some((_, best_space)) if best_space >= space => best,
+                _ => Some((direction, space)),
+            }
+        })
+        .map(|(direction, _)| direction)
+}
+
+/// Successor of `pos` on a cycle through every cell of a `width` by
+/// `height` grid with an even `height`: snake along the rows over columns
+/// `1..width`, then back up column 0.
+fn cycle_successor(pos: Position, width: i32, height: i32) -> Position {
+    let (x, y) = (pos.x, pos.y);
+    if x == 0 {
+        return if y == 0 {
+            Position::new(1, 0)
+        } else {
+            Position::new(0, y - 1)
+        };
+    }
+    if y % 2 == 0 {
+        if x < width - 1 {
+            Position::new(x + 1, y)
+        } else {
+            Position::new(x, y + 1)
+        }
+    } else if x > 1 {
+        Position::new(x - 1, y)
+    } else if y == height - 1 {
+        Position::new(0, y)
+    } else {
+        Position::new(x, y + 1)
+    }
+}
+
+/// The next step along the board's Hamiltonian cycle, or `None` if the
+/// board has none (obstacles, a shrinking arena or two odd sides) or the
+/// step is unsafe.
+fn hamiltonian(state: &GameState) -> Option<Direction> {
+    let level = state.level();
+    let (width, height) = (level.grid_width, level.grid_height);
+    if !level.obstacles.is_empty() || level.shrink_interval_ticks > 0 || width < 2 || height < 2 {
+        return None;
+    }
+    let head = state.head();
+    let next = if height % 2 == 0 {
+        cycle_successor(head, width, height)
+    } else if width % 2 == 0 {
+        // Walk the transposed cycle
+        let swapped = cycle_successor(Position::new(head.y, head.x), height, width);
+        Position::new(swapped.y, swapped.x)
+    } else {
+        return None;
+    };
+    let direction = Direction::ALL
+        .into_iter()
+        .find(|&direction| state.neighbour(head, direction) == next)?;
+    is_safe(state, next).then_some(direction)
+}
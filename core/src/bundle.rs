@@ -0,0 +1,196 @@
+//! `.skp` proof bundles.
+//!
+//! A bundle is a proof of a game together with everything needed to check
+//! it and tell where it came from, so the CLI, the server and the browser
+//! pass proofs around in one format. Layout, integers little endian:
+//!
+//! | bytes | field                                          |
+//! |-------|------------------------------------------------|
+//! | 4     | magic `SKP\x1a`                                |
+//! | 1     | format version ([`FORMAT_VERSION`])            |
+//! | 1     | proof kind, see [`ProofKind`]                  |
+//! | 32    | program verification key hash                  |
+//! | 2     | engine version ([`rules::ENGINE_VERSION`])     |
+//! | 4 + n | program version, UTF-8                         |
+//! | 8     | Unix time proving started, in seconds          |
+//! | 8     | Unix time proving finished, in seconds         |
+//! | 32    | SHA-256 of the game's `.skr` bytes             |
+//! | 4 + n | public values, ABI-encoded (`abi.rs`)          |
+//! | 4 + n | proof                                          |
+//! | 4     | CRC-32 of all preceding bytes                  |
+//!
+//! Lengths are `u32` prefixes. Groth16 and PLONK proofs are the encoding
+//! EVM verifiers and the `sp1-verifier` crate check, so the browser can
+//! verify them; core and compressed proofs are the SP1 SDK's serialized
+//! proof, which only the SDK checks.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::codec::{crc32, Reader};
+use crate::{rules, SnakeError};
+
+/// Leading bytes of every `.skp` file.
+pub const MAGIC: [u8; 4] = *b"SKP\x1a";
+
+/// Version of the container layout described above.
+pub const FORMAT_VERSION: u8 = 1;
+
+/// Kind of SP1 proof a bundle holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProofKind {
+    Core,
+    Compressed,
+    Groth16,
+    Plonk,
+}
+
+impl ProofKind {
+    fn to_byte(self) -> u8 {
+        match self {
+            ProofKind::Core => 0,
+            ProofKind::Compressed => 1,
+            ProofKind::Groth16 => 2,
+            ProofKind::Plonk => 3,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Result<ProofKind, SnakeError> {
+        match byte {
+            0 => Ok(ProofKind::Core),
+            1 => Ok(ProofKind::Compressed),
+            2 => Ok(ProofKind::Groth16),
+            3 => Ok(ProofKind::Plonk),
+            _ => Err(SnakeError::BadBundle),
+        }
+    }
+
+    /// Whether the proof is the on-chain encoding, which verifies outside
+    /// the SP1 SDK.
+    pub fn is_onchain(self) -> bool {
+        matches!(self, ProofKind::Groth16 | ProofKind::Plonk)
+    }
+}
+
+/// A decoded `.skp` file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProofBundle {
+    pub kind: ProofKind,
+    /// The program's `bytes32` verification key hash.
+    pub vkey_hash: [u8; 32],
+    /// Engine the proven game was played on.
+    pub engine_version: u16,
+    /// Version of the host that made the proof.
+    pub program_version: String,
+    /// Unix time proving started, in seconds; for a proof taken from a
+    /// cache, when it was looked up.
+    pub started_at: u64,
+    /// Unix time proving finished, in seconds.
+    pub finished_at: u64,
+    /// SHA-256 of the game's `.skr` bytes.
+    pub replay_hash: [u8; 32],
+    /// The values the program committed.
+    pub public_values: Vec<u8>,
+    pub proof: Vec<u8>,
+}
+
+impl ProofBundle {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            100 + self.program_version.len() + self.public_values.len() + self.proof.len(),
+        );
+        bytes.extend_from_slice(&MAGIC);
+        bytes.push(FORMAT_VERSION);
+        bytes.push(self.kind.to_byte());
+        bytes.extend_from_slice(&self.vkey_hash);
+        bytes.extend_from_slice(&self.engine_version.to_le_bytes());
+        write_bytes(&mut bytes, self.program_version.as_bytes());
+        bytes.extend_from_slice(&self.started_at.to_le_bytes());
+        bytes.extend_from_slice(&self.finished_at.to_le_bytes());
+        bytes.extend_from_slice(&self.replay_hash);
+        write_bytes(&mut bytes, &self.public_values);
+        write_bytes(&mut bytes, &self.proof);
+        let checksum = crc32(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    /// Parses and validates a `.skp` file. Fails with
+    /// [`SnakeError::BadBundle`] on a wrong magic or format version, an
+    /// unknown proof kind, a checksum mismatch, a truncated buffer or a
+    /// bundle [`ProofBundle::validate`] rejects.
+    pub fn decode(bytes: &[u8]) -> Result<ProofBundle, SnakeError> {
+        let (body, checksum) = bytes.split_last_chunk::<4>().ok_or(SnakeError::BadBundle)?;
+        if crc32(body) != u32::from_le_bytes(*checksum) {
+            return Err(SnakeError::BadBundle);
+        }
+
+        let mut reader = Reader::new(body, SnakeError::BadBundle);
+        if reader.take(4)? != MAGIC || reader.u8()? != FORMAT_VERSION {
+            return Err(SnakeError::BadBundle);
+        }
+        let kind = ProofKind::from_byte(reader.u8()?)?;
+        let vkey_hash = read_hash(&mut reader)?;
+        let engine_version = u16::from_le_bytes([reader.u8()?, reader.u8()?]);
+        let program_version = String::from_utf8(read_bytes(&mut reader)?.to_vec())
+            .map_err(|_| SnakeError::BadBundle)?;
+        let started_at = reader.u64()?;
+        let finished_at = reader.u64()?;
+        let replay_hash = read_hash(&mut reader)?;
+        let public_values = read_bytes(&mut reader)?.to_vec();
+        let proof = read_bytes(&mut reader)?.to_vec();
+        reader.finish()?;
+
+        let bundle = ProofBundle {
+            kind,
+            vkey_hash,
+            engine_version,
+            program_version,
+            started_at,
+            finished_at,
+            replay_hash,
+            public_values,
+            proof,
+        };
+        bundle.validate()?;
+        Ok(bundle)
+    }
+
+    /// Fails with [`SnakeError::BadBundle`] if the proof or public values
+    /// are empty, the vkey hash is zero, proving finished before it started
+    /// or the game was played on a newer engine than this one.
+    pub fn validate(&self) -> Result<(), SnakeError> {
+        let valid = !self.proof.is_empty()
+            && !self.public_values.is_empty()
+            && self.vkey_hash != [0; 32]
+            && self.started_at <= self.finished_at
+            && self.engine_version <= rules::ENGINE_VERSION;
+        match valid {
+            true => Ok(()),
+            false => Err(SnakeError::BadBundle),
+        }
+    }
+
+    /// The committed values of a game proof. Fails with
+    /// [`SnakeError::BadBundle`] if they are not ABI-encoded game inputs.
+    #[cfg(feature = "abi")]
+    pub fn public_inputs(&self) -> Result<crate::inputs::SnakeGamePublicInputs, SnakeError> {
+        crate::abi::decode_public_inputs(&self.public_values).map_err(|_| SnakeError::BadBundle)
+    }
+}
+
+fn write_bytes(bytes: &mut Vec<u8>, field: &[u8]) {
+    bytes.extend_from_slice(&(field.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(field);
+}
+
+fn read_bytes<'a>(reader: &mut Reader<'a>) -> Result<&'a [u8], SnakeError> {
+    let len = reader.u32()? as usize;
+    reader.take(len)
+}
+
+fn read_hash(reader: &mut Reader<'_>) -> Result<[u8; 32], SnakeError> {
+    Ok(reader.take(32)?.try_into().expect("32 bytes"))
+}
@@ -0,0 +1,106 @@
+//! Daily-challenge seeds.
+//!
+//! Everyone playing the challenge of a given day starts from the same seed,
+//! derived from the date and a domain salt naming the deployment, so two
+//! sites running the game do not share challenges:
+//!
+//! `seed = u64_le(sha256("snake-daily-challenge" || day || salt)[..8])`
+//!
+//! where `day` is the date as the `u32` `YYYYMMDD`, little endian. Dates are
+//! UTC days written `YYYY-MM-DD`. The SP1 program checks a game's seed
+//! against its [`Challenge`] and commits the day and [`salt_hash`], so a
+//! leaderboard can reject scores played on any other seed.
+
+use alloc::vec::Vec;
+
+use sha2::{Digest, Sha256};
+
+use crate::SnakeError;
+
+const DOMAIN: &[u8] = b"snake-daily-challenge";
+
+/// The challenge a game claims to be played on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Challenge {
+    /// The date as `YYYYMMDD`, see [`parse_date`].
+    pub day: u32,
+    pub domain_salt: Vec<u8>,
+}
+
+impl Challenge {
+    /// The challenge of `date`, a `YYYY-MM-DD` day. Fails with
+    /// [`SnakeError::BadDate`] if it is not one.
+    pub fn new(date: &str, domain_salt: &[u8]) -> Result<Challenge, SnakeError> {
+        Ok(Challenge {
+            day: parse_date(date)?,
+            domain_salt: domain_salt.to_vec(),
+        })
+    }
+
+    pub fn seed(&self) -> u64 {
+        seed_for_day(self.day, &self.domain_salt)
+    }
+
+    /// Fails with [`SnakeError::NotChallengeSeed`] unless `seed` is this
+    /// challenge's seed.
+    pub fn check(&self, seed: u64) -> Result<(), SnakeError> {
+        match seed == self.seed() {
+            true => Ok(()),
+            false => Err(SnakeError::NotChallengeSeed),
+        }
+    }
+}
+
+/// The seed of the challenge on `date`, a `YYYY-MM-DD` day. Fails with
+/// [`SnakeError::BadDate`] if it is not one.
+pub fn seed_for_date(date: &str, domain_salt: &[u8]) -> Result<u64, SnakeError> {
+    Ok(seed_for_day(parse_date(date)?, domain_salt))
+}
+
+/// The seed of the challenge on `day`, given as `YYYYMMDD`.
+pub fn seed_for_day(day: u32, domain_salt: &[u8]) -> u64 {
+    let digest = Sha256::new()
+        .chain_update(DOMAIN)
+        .chain_update(day.to_le_bytes())
+        .chain_update(domain_salt)
+        .finalize();
+    u64::from_le_bytes(digest[..8].try_into().expect("8 bytes"))
+}
+
+/// SHA-256 of a domain salt, as committed by the SP1 program; a contract
+/// accepts the challenges of the salts it knows.
+pub fn salt_hash(domain_salt: &[u8]) -> [u8; 32] {
+    Sha256::digest(domain_salt).into()
+}
+
+/// Reads a `YYYY-MM-DD` date into `YYYYMMDD`. Fails with
+/// [`SnakeError::BadDate`] unless it is a real day of the Gregorian
+/// calendar between years 1 and 9999.
+pub fn parse_date(date: &str) -> Result<u32, SnakeError> {
+    let bytes = date.as_bytes();
+    if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return Err(SnakeError::BadDate);
+    }
+    let number = |range: core::ops::Range<usize>| {
+        bytes[range]
+            .iter()
+            .try_fold(0, |value, &digit| match digit {
+                b'0'..=b'9' => Ok(value * 10 + u32::from(digit - b'0')),
+                _ => Err(SnakeError::BadDate),
+            })
+    };
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return Err(SnakeError::BadDate),
+    };
+    if year == 0 || day == 0 || day > days_in_month {
+        return Err(SnakeError::BadDate);
+    }
+    Ok(year * 10_000 + month * 100 + day)
+}
@@ -0,0 +1,191 @@
+//! Helpers shared by the binary encodings.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use sha2::{Digest, Sha256};
+
+use crate::SnakeError;
+
+/// Cursor over an encoded buffer. Every read fails with `error` once the
+/// buffer runs out, so decoders can propagate truncation with `?`.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    error: SnakeError,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8], error: SnakeError) -> Reader<'a> {
+        Reader { bytes, error }
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], SnakeError> {
+        if self.bytes.len() < len {
+            return Err(self.error);
+        }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    /// The error reported for malformed input.
+    pub(crate) fn error(&self) -> SnakeError {
+        self.error
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, SnakeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// A `0` or `1` byte.
+    pub(crate) fn bool(&mut self) -> Result<bool, SnakeError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(self.error),
+        }
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, SnakeError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub(crate) fn i32(&mut self) -> Result<i32, SnakeError> {
+        Ok(self.u32()? as i32)
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, SnakeError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    /// Reads a LEB128 varint written by [`write_varint`].
+    pub(crate) fn varint(&mut self) -> Result<u64, SnakeError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(self.error)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Fails unless the whole buffer has been consumed.
+    pub(crate) fn finish(self) -> Result<(), SnakeError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error)
+        }
+    }
+}
+
+/// Where an encoder writes: a buffer, or a hasher that digests the bytes
+/// as they come instead of holding them.
+pub(crate) trait Sink {
+    fn put(&mut self, bytes: &[u8]);
+}
+
+impl Sink for Vec<u8> {
+    fn put(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+}
+
+/// Appends `value` as an unsigned LEB128 varint: seven bits per byte, low
+/// bits first, high bit set on every byte but the last.
+pub(crate) fn write_varint(bytes: &mut impl Sink, mut value: u64) {
+    while value >= 0x80 {
+        bytes.put(&[value as u8 | 0x80]);
+        value >>= 7;
+    }
+    bytes.put(&[value as u8]);
+}
+
+/// Number of bytes [`write_varint`] writes for `value`.
+pub(crate) fn varint_len(value: u64) -> usize {
+    (64 - (value | 1).leading_zeros() as usize).div_ceil(7)
+}
+
+/// CRC-32 (IEEE 802.3, as used by zip and PNG).
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+/// HMAC-SHA256 (RFC 2104) of `message` under `key`.
+pub(crate) fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK: usize = 64;
+    let mut block = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let inner = Sha256::new()
+        .chain_update(block.map(|byte| byte ^ 0x36))
+        .chain_update(message)
+        .finalize();
+    Sha256::new()
+        .chain_update(block.map(|byte| byte ^ 0x5c))
+        .chain_update(inner)
+        .finalize()
+        .into()
+}
+
+/// Compares two byte strings in time that depends only on their length,
+/// for checking signatures.
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// The URL- and filename-safe base64 alphabet (RFC 4648 section 5).
+const BASE64URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Encodes `bytes` as unpadded base64url.
+pub(crate) fn base64url_encode(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | u32::from(byte) << (16 - 8 * i)
+        });
+        for i in 0..=chunk.len() {
+            text.push(char::from(
+                BASE64URL[(group >> (18 - 6 * i) & 0x3f) as usize],
+            ));
+        }
+    }
+    text
+}
+
+/// Decodes unpadded base64url, or `None` on any other character or a
+/// length no encoding produces.
+pub(crate) fn base64url_decode(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() / 4 * 3 + 2);
+    for chunk in text.as_bytes().chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let mut group = 0u32;
+        for (i, &symbol) in chunk.iter().enumerate() {
+            let value = BASE64URL.iter().position(|&c| c == symbol)?;
+            group |= (value as u32) << (18 - 6 * i);
+        }
+        bytes.extend_from_slice(&group.to_be_bytes()[1..chunk.len()]);
+    }
+    Some(bytes)
+}
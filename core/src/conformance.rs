@@ -0,0 +1,63 @@
+//! Cross-build conformance checks.
+//!
+//! The browser runs this crate compiled to wasm32 and the prover runs it on
+//! the zkVM's RISC-V target. Both builds must agree on every tick, so a
+//! replay's per-tick [`crate::GameState::state_hash`] trace from one build can be
+//! checked against the other's with [`first_divergence`] to find the first
+//! tick where the engines drifted apart.
+
+use alloc::vec::Vec;
+
+use crate::{Replay, ReplayPlayer, SnakeError};
+
+/// Length in bytes of one entry of an encoded trace.
+pub const HASH_SIZE: usize = 32;
+
+/// The state hash before the first tick and after every tick of `replay`,
+/// so entry `n` is the state at tick `n`. Fails like [`Replay::play`] if
+/// the replay is not playable; the recorded score is not checked.
+pub fn hash_trace(replay: &Replay) -> Result<Vec<[u8; 32]>, SnakeError> {
+    let initial = replay.initial_state()?;
+    let mut trace = Vec::new();
+    trace.push(initial.state_hash());
+    let mut player = ReplayPlayer::new(initial, replay.moves.clone());
+    while !player.is_finished() {
+        player.step()?;
+        trace.push(player.state().state_hash());
+    }
+    Ok(trace)
+}
+
+/// First tick whose hash differs between two traces, or where one trace
+/// ends before the other. `None` when they match exactly.
+pub fn first_divergence(expected: &[[u8; 32]], actual: &[[u8; 32]]) -> Option<u32> {
+    let tick = expected
+        .iter()
+        .zip(actual)
+        .position(|(expected, actual)| expected != actual)
+        .unwrap_or(expected.len().min(actual.len()));
+    (expected.len() != actual.len() || tick < expected.len()).then_some(tick as u32)
+}
+
+/// Flattens a trace for transport, [`HASH_SIZE`] bytes per tick.
+pub fn encode_trace(trace: &[[u8; 32]]) -> Vec<u8> {
+    trace.concat()
+}
+
+/// Reads a trace written by [`encode_trace`]. Fails with
+/// [`SnakeError::BadReplay`] if the length is not a whole number of hashes.
+pub fn decode_trace(bytes: &[u8]) -> Result<Vec<[u8; 32]>, SnakeError> {
+    if !bytes.len().is_multiple_of(HASH_SIZE) {
+        return Err(SnakeError::BadReplay);
+    }
+    Ok(bytes
+        .chunks_exact(HASH_SIZE)
+        .map(|hash| hash.try_into().expect("chunks are HASH_SIZE long"))
+        .collect())
+}
+
+/// Plays `replay` on this build and compares it with `expected`, a trace
+/// from another build. Returns the first divergent tick, if any.
+pub fn check_trace(replay: &Replay, expected: &[[u8; 32]]) -> Result<Option<u32>, SnakeError> {
+    Ok(first_divergence(expected, &hash_trace(replay)?))
+}
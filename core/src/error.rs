@@ -0,0 +1,99 @@
+use core::fmt;
+
+/// Everything that can go wrong while playing or verifying a game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SnakeError {
+    /// A direction change that would reverse the snake into its neck.
+    InvalidMove,
+    /// A position outside the grid, including the head hitting a wall.
+    OutOfBounds,
+    /// The head ran into the snake's own body.
+    SelfCollision,
+    /// The head ran into an obstacle tile.
+    ObstacleCollision,
+    /// The game has already ended.
+    GameOver,
+    /// More turns were queued than `rules::INPUT_QUEUE_CAPACITY` allows.
+    InputQueueFull,
+    /// Score and snake length do not agree with the scoring rules.
+    ScoreMismatch,
+    /// A replay or move log that is malformed or not a legal game.
+    BadReplay,
+    /// A state hash that differs from the expected commitment.
+    HashMismatch,
+    /// A level that is not playable: see `Level::validate`.
+    BadLayout,
+    /// A saved game snapshot that is truncated or describes an illegal board.
+    BadSnapshot,
+    /// A player index that is not in the game.
+    UnknownPlayer,
+    /// A game state that breaks the engine's invariants, which only a
+    /// hand-built or deserialized value can: an empty snake or food slots
+    /// out of step.
+    CorruptState,
+    /// A spectator delta that is malformed or does not follow from the
+    /// spectator's state.
+    BadDelta,
+    /// A peer's input for a tick that is already known or too far ahead.
+    BadInput,
+    /// A peer protocol message that is malformed or from another protocol
+    /// version.
+    BadMessage,
+    /// A leaderboard inclusion proof that is malformed.
+    BadProof,
+    /// Games ranked together that were not all played under the same
+    /// config and seed.
+    MismatchedGames,
+    /// A date that is not a `YYYY-MM-DD` day.
+    BadDate,
+    /// A game claimed for a daily challenge that was not played on its
+    /// seed.
+    NotChallengeSeed,
+    /// A player signature that is malformed or does not sign the game.
+    BadSignature,
+    /// A game that does not reach the score it is meant to prove.
+    BelowThreshold,
+    /// A proof bundle that is malformed or fails its own checks.
+    BadBundle,
+    /// A vkey registry manifest that is malformed or registers a version
+    /// or key twice.
+    BadManifest,
+    /// A proof from a program the vkey registry does not accept.
+    UnregisteredProgram,
+}
+
+impl fmt::Display for SnakeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SnakeError::InvalidMove => "snake cannot reverse into itself",
+            SnakeError::OutOfBounds => "position is outside the grid",
+            SnakeError::SelfCollision => "snake collided with itself",
+            SnakeError::ObstacleCollision => "snake hit an obstacle",
+            SnakeError::GameOver => "game is already over",
+            SnakeError::InputQueueFull => "too many turns queued",
+            SnakeError::ScoreMismatch => "score does not match the snake length",
+            SnakeError::BadReplay => "replay is not a legal game",
+            SnakeError::HashMismatch => "game state hash mismatch",
+            SnakeError::BadLayout => "level is not a playable board",
+            SnakeError::BadSnapshot => "game snapshot is corrupt",
+            SnakeError::UnknownPlayer => "no such player",
+            SnakeError::CorruptState => "game state breaks the engine invariants",
+            SnakeError::BadDelta => "spectator delta does not follow from the game",
+            SnakeError::BadInput => "peer input is out of order",
+            SnakeError::BadMessage => "peer message is malformed",
+            SnakeError::BadProof => "inclusion proof is malformed",
+            SnakeError::MismatchedGames => "games were played under different rules or seeds",
+            SnakeError::BadDate => "date is not a valid YYYY-MM-DD day",
+            SnakeError::NotChallengeSeed => "seed is not the daily challenge seed",
+            SnakeError::BadSignature => "player signature does not match the game",
+            SnakeError::BelowThreshold => "score is below the threshold",
+            SnakeError::BadBundle => "proof bundle is malformed",
+            SnakeError::BadManifest => "vkey registry manifest is malformed",
+            SnakeError::UnregisteredProgram => "proof is from an unregistered program",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SnakeError {}
@@ -0,0 +1,262 @@
+//! Fuzzing and property-test entry points.
+//!
+//! [`FuzzInput`] turns raw bytes into engine values the way
+//! `arbitrary::Unstructured` does, without pulling a dependency into the
+//! zkVM guest: [`arbitrary_direction`], [`arbitrary_level`] (the game
+//! config) and [`arbitrary_replay`] each read what they need and fall back
+//! to zeros once the bytes run out, so every input builds something. A
+//! `cargo fuzz` target or a property test feeds its bytes to
+//! [`step_from_bytes`], which panics on any rule divergence.
+
+use alloc::vec::Vec;
+
+use crate::level::FoodRules;
+use crate::rules::{self, Difficulty, IllegalMovePolicy, ScoringMode, ScoringRules};
+use crate::{
+    conformance, verify, Direction, GameState, Level, Move, Position, Replay, SnakeError,
+    StepOutcome,
+};
+
+/// Largest side of an arbitrary level, so a game stays cheap to play.
+pub const MAX_FUZZ_GRID: i32 = 24;
+
+/// Tick budget ceiling of an arbitrary level. Every arbitrary level has a
+/// budget, so every driven game ends.
+pub const MAX_FUZZ_TICKS: u32 = 2_000;
+
+/// Cursor over fuzzer bytes. Reads past the end return zero.
+pub struct FuzzInput<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> FuzzInput<'a> {
+    pub fn new(bytes: &'a [u8]) -> FuzzInput<'a> {
+        FuzzInput { bytes }
+    }
+
+    /// Whether every byte has been read.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn u8(&mut self) -> u8 {
+        match self.bytes.split_first() {
+            Some((&byte, rest)) => {
+                self.bytes = rest;
+                byte
+            }
+            None => 0,
+        }
+    }
+
+    pub fn bool(&mut self) -> bool {
+        self.u8() & 1 == 1
+    }
+
+    pub fn u32(&mut self) -> u32 {
+        u32::from_le_bytes([self.u8(), self.u8(), self.u8(), self.u8()])
+    }
+
+    pub fn u64(&mut self) -> u64 {
+        u64::from(self.u32()) | u64::from(self.u32()) << 32
+    }
+
+    /// A value in `min..=max`.
+    pub fn in_range(&mut self, min: u32, max: u32) -> u32 {
+        min + self.u32() % (max - min + 1)
+    }
+}
+
+pub fn arbitrary_direction(input: &mut FuzzInput) -> Direction {
+    Direction::ALL[input.u8() as usize % Direction::ALL.len()]
+}
+
+fn arbitrary_position(input: &mut FuzzInput, width: i32, height: i32) -> Position {
+    Position::new(
+        input.in_range(0, width as u32 - 1) as i32,
+        input.in_range(0, height as u32 - 1) as i32,
+    )
+}
+
+/// A level of up to [`MAX_FUZZ_GRID`] cells a side with every rule drawn
+/// from `input`. It is often not playable; [`Level::validate`] tells.
+pub fn arbitrary_level(input: &mut FuzzInput) -> Level {
+    let width = input.in_range(1, MAX_FUZZ_GRID as u32) as i32;
+    let height = input.in_range(1, MAX_FUZZ_GRID as u32) as i32;
+    let obstacles = (0..input.in_range(0, 8))
+        .map(|_| arbitrary_position(input, width, height))
+        .collect();
+    let start = arbitrary_position(input, width, height);
+    let start_direction = arbitrary_direction(input);
+    let first = input
+        .bool()
+        .then(|| arbitrary_position(input, width, height));
+    let food = FoodRules {
+        first,
+        count: input.in_range(1, 4),
+        golden_percent: input.in_range(0, 60) as u8,
+        poison_percent: input.in_range(0, 40) as u8,
+        lifetime_ticks: input.in_range(0, 3) * 20,
+    };
+    let scoring = ScoringRules {
+        mode: ScoringMode::from_byte(input.u8() & 1).unwrap_or_default(),
+        points_per_food: input.in_range(0, 20),
+        points_per_golden_food: input.in_range(0, 100),
+        growth_per_food: input.in_range(0, 3),
+        survival_points_per_tick: input.in_range(0, 2),
+        combo_window_ticks: input.in_range(0, 20),
+        max_combo_multiplier: input.in_range(1, 5),
+    };
+    Level {
+        grid_width: width,
+        grid_height: height,
+        wrap_walls: input.bool(),
+        obstacles,
+        start,
+        start_direction,
+        food,
+        scoring,
+        illegal_moves: IllegalMovePolicy::from_byte(input.u8() & 1).unwrap_or_default(),
+        difficulty: Difficulty::default(),
+        lives: input.in_range(1, 3) as u8,
+        max_ticks: input.in_range(1, MAX_FUZZ_TICKS),
+        shrink_interval_ticks: input.in_range(0, 1) * input.in_range(10, 100),
+    }
+}
+
+/// A replay of an arbitrary level with an arbitrary move log and claimed
+/// score; almost never a legal game, which is what the verifier has to
+/// reject without panicking.
+pub fn arbitrary_replay(input: &mut FuzzInput) -> Replay {
+    let level = arbitrary_level(input);
+    let seed = input.u64();
+    let score = u64::from(input.u32() % 1_000);
+    let mut tick = 0;
+    let moves = (0..input.in_range(0, 64))
+        .map(|_| {
+            tick += input.in_range(0, 16);
+            Move {
+                tick,
+                direction: arbitrary_direction(input),
+            }
+        })
+        .collect();
+    Replay {
+        engine_version: rules::ENGINE_VERSION,
+        level,
+        seed,
+        score,
+        moves,
+    }
+}
+
+/// Hammers the engine and verifier with `bytes`:
+///
+/// * the raw bytes are fed to every decoder and, when they decode, to the
+///   verifier, none of which may panic;
+/// * an [`arbitrary_replay`] must be accepted by [`verify::verify_replay`]
+///   exactly when [`Replay::play`] accepts it and the game does not stall;
+/// * an [`arbitrary_level`] is played with turns read from the rest of the
+///   bytes, checking each tick that a snapshot restores the same state,
+///   and the finished game must survive a `.skr` round trip, verify to the
+///   same state hash and reproduce the same hash trace.
+///
+/// Panics on any disagreement.
+pub fn step_from_bytes(bytes: &[u8]) {
+    if let Ok(replay) = Replay::decode(bytes) {
+        let _ = verify::verify_replay(&replay);
+    }
+    let _ = GameState::from_bytes(bytes);
+    let _ = crate::moves::decode_moves(bytes);
+    let _ = conformance::decode_trace(bytes);
+
+    let mut input = FuzzInput::new(bytes);
+    check_replay(&arbitrary_replay(&mut input));
+    if let Ok(state) = GameState::from_level(arbitrary_level(&mut input), input.u64()) {
+        check_driven_game(state, &mut input);
+    }
+}
+
+/// The verifier and the plain replay player agree on `replay`.
+fn check_replay(replay: &Replay) {
+    let verified = verify::verify_replay(replay);
+    let played = replay.play();
+    match (&verified, &played) {
+        (Ok(outcome), Ok(state)) => {
+            assert_eq!(
+                outcome.game_state_hash,
+                state.state_hash(),
+                "final states differ"
+            );
+        }
+        // The verifier alone rejects games that would never end
+        (Err(SnakeError::BadReplay), Ok(_)) => {}
+        (Ok(_), Err(error)) => panic!("verified a replay the player rejects: {error:?}"),
+        (Err(_), Err(_)) => {}
+        (Err(error), Ok(_)) => panic!("verifier rejected a played replay: {error:?}"),
+    }
+}
+
+/// Plays `state` to the end with turns drawn from `input`, then checks the
+/// recorded game end to end.
+fn check_driven_game(mut state: GameState, input: &mut FuzzInput) {
+    // Whether the verifier will see the game stall after its last move
+    let mut stalled = false;
+    let mut idle_ticks = 0u32;
+    while !state.is_game_over() {
+        let recorded = state.moves().len();
+        if !input.is_empty() && input.bool() {
+            let _ = state.change_direction(arbitrary_direction(input));
+        }
+        if state.moves().len() > recorded {
+            (stalled, idle_ticks) = (false, 0);
+        }
+        if state.is_game_over() {
+            break;
+        }
+        let outcome = state.step().expect("a running game steps");
+        idle_ticks = match outcome {
+            StepOutcome::AteFood | StepOutcome::LostLife => 0,
+            _ => idle_ticks.saturating_add(1),
+        };
+        stalled |= !state.is_game_over() && state.is_stalled(idle_ticks);
+
+        let report = state.validate_invariants();
+        assert!(report.is_ok(), "invariants broken: {report}");
+        let restored = GameState::from_bytes(&state.to_bytes()).expect("snapshots restore");
+        assert_eq!(
+            restored.state_hash(),
+            state.state_hash(),
+            "snapshot changed the state"
+        );
+    }
+
+    let replay = Replay::from_game(&state);
+    assert_eq!(
+        Replay::decode(&replay.encode()),
+        Ok(replay.clone()),
+        ".skr round trip"
+    );
+    let played = replay.play().expect("a recorded game replays");
+    assert_eq!(played.state_hash(), state.state_hash(), "replay diverged");
+    match verify::verify_replay(&replay) {
+        Ok(outcome) => {
+            assert!(!stalled, "verified a stalled game");
+            assert_eq!(
+                outcome.game_state_hash,
+                state.state_hash(),
+                "verifier diverged"
+            );
+        }
+        Err(error) => assert!(
+            stalled && error == SnakeError::BadReplay,
+            "verifier rejected a recorded game: {error:?}"
+        ),
+    }
+    let trace: Vec<[u8; 32]> = conformance::hash_trace(&replay).expect("a recorded game traces");
+    assert_eq!(
+        trace.last(),
+        Some(&state.state_hash()),
+        "hash trace diverged"
+    );
+}
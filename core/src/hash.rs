@@ -0,0 +1,198 @@
+//! Game state commitment.
+//!
+//! `state_hash` is the value committed as `game_state_hash` by the SP1
+//! program. Fields are serialized in a fixed order, integers little endian,
+//! and hashed with SHA-256, or Keccak-256 with the `keccak` feature (state
+//! and config hashes only), which lets EVM contracts recompute them with
+//! `keccak256`, or SP1 5's Poseidon2 over BabyBear with the `poseidon`
+//! feature (the same two hashes), for verifiers that already compute it.
+//! `poseidon` takes precedence when both are enabled. Changing anything here
+//! changes every proof.
+//!
+//! Hashing goes through the `sha2` crate everywhere. The SP1 workspace
+//! patches it to use the zkVM's SHA-256 precompile, which gives the same
+//! digests as the software implementation the wasm crate uses.
+
+use alloc::vec::Vec;
+use sha2::{Digest, Sha256};
+
+use crate::codec::Sink;
+use crate::spectate::StateDelta;
+use crate::versus::{self, VersusState};
+use crate::{rules, GameState, Level, Position};
+
+/// Canonical byte encoding of the observable game state. Food and snake
+/// cells are written as `u16` coordinate pairs, the way [`crate::Body`]
+/// packs them.
+pub fn encode_state(state: &GameState) -> Vec<u8> {
+    let snake = state.snake();
+    let foods = state.foods();
+    let mut bytes = Vec::with_capacity(27 + snake.len() * 4 + foods.len() * 5);
+    bytes.extend_from_slice(&state.grid_width().to_le_bytes());
+    bytes.extend_from_slice(&state.grid_height().to_le_bytes());
+    bytes.extend_from_slice(&state.ticks().to_le_bytes());
+    bytes.extend_from_slice(&state.score().to_le_bytes());
+    bytes.push(state.direction().to_byte());
+    bytes.push(state.is_game_over() as u8);
+    bytes.push(state.lives());
+    for (food, kind) in foods.iter().zip(state.food_kinds()) {
+        write_cell(&mut bytes, *food);
+        bytes.push(kind.to_byte());
+    }
+    bytes.extend_from_slice(&(snake.len() as u32).to_le_bytes());
+    for segment in snake.iter() {
+        write_cell(&mut bytes, segment);
+    }
+    bytes
+}
+
+/// A grid cell as two little-endian `u16`s, `x` first.
+fn write_cell(bytes: &mut Vec<u8>, cell: Position) {
+    bytes.extend_from_slice(&(cell.x as u16).to_le_bytes());
+    bytes.extend_from_slice(&(cell.y as u16).to_le_bytes());
+}
+
+pub fn state_hash(state: &GameState) -> [u8; 32] {
+    commitment(&encode_state(state))
+}
+
+/// The hash behind state and config hashes: SHA-256, Keccak-256 with the
+/// `keccak` feature or Poseidon2 with the `poseidon` feature. With both,
+/// `poseidon` wins, so features stay additive.
+fn commitment(bytes: &[u8]) -> [u8; 32] {
+    #[cfg(feature = "poseidon")]
+    return crate::poseidon::poseidon2(bytes);
+    #[cfg(all(feature = "keccak", not(feature = "poseidon")))]
+    return crate::keccak::keccak256(bytes);
+    #[cfg(not(any(feature = "keccak", feature = "poseidon")))]
+    Sha256::digest(bytes).into()
+}
+
+/// [`commitment`] fed piece by piece, so a message is hashed as it is
+/// encoded rather than collected in a buffer first.
+pub(crate) struct Commitment {
+    #[cfg(feature = "poseidon")]
+    hasher: crate::poseidon::Sponge,
+    #[cfg(all(feature = "keccak", not(feature = "poseidon")))]
+    hasher: crate::keccak::Keccak256,
+    #[cfg(not(any(feature = "keccak", feature = "poseidon")))]
+    hasher: Sha256,
+}
+
+impl Commitment {
+    /// A hasher for a message of `len` bytes, which Poseidon2 absorbs
+    /// before them.
+    pub(crate) fn new(len: usize) -> Commitment {
+        #[cfg(not(feature = "poseidon"))]
+        let _ = len;
+        #[cfg(feature = "poseidon")]
+        let hasher = crate::poseidon::Sponge::new(len);
+        #[cfg(all(feature = "keccak", not(feature = "poseidon")))]
+        let hasher = crate::keccak::Keccak256::new();
+        #[cfg(not(any(feature = "keccak", feature = "poseidon")))]
+        let hasher = Sha256::new();
+        Commitment { hasher }
+    }
+
+    pub(crate) fn finalize(self) -> [u8; 32] {
+        #[cfg(any(feature = "keccak", feature = "poseidon"))]
+        return self.hasher.finalize();
+        #[cfg(not(any(feature = "keccak", feature = "poseidon")))]
+        self.hasher.finalize().into()
+    }
+}
+
+impl Sink for Commitment {
+    fn put(&mut self, bytes: &[u8]) {
+        self.hasher.update(bytes);
+    }
+}
+
+/// Canonical byte encoding of a versus match: grid size, ticks, game-over
+/// byte, food cells and kind bytes, then for each player its score, alive
+/// byte, direction byte, snake length and segments. Cells are `u16` pairs
+/// as in [`encode_state`].
+pub fn encode_versus_state(state: &VersusState) -> Vec<u8> {
+    let foods = state.foods();
+    let segments: usize = (0..versus::PLAYERS)
+        .map(|player| state.snake(player).len())
+        .sum();
+    let mut bytes = Vec::with_capacity(13 + foods.len() * 5 + versus::PLAYERS * 14 + segments * 4);
+    bytes.extend_from_slice(&state.level().grid_width.to_le_bytes());
+    bytes.extend_from_slice(&state.level().grid_height.to_le_bytes());
+    bytes.extend_from_slice(&state.ticks().to_le_bytes());
+    bytes.push(state.is_game_over() as u8);
+    for (food, kind) in foods.iter().zip(state.food_kinds()) {
+        write_cell(&mut bytes, *food);
+        bytes.push(kind.to_byte());
+    }
+    for player in 0..versus::PLAYERS {
+        let snake = state.snake(player);
+        bytes.extend_from_slice(&state.score(player).to_le_bytes());
+        bytes.push(state.is_alive(player) as u8);
+        bytes.push(state.direction(player).to_byte());
+        bytes.extend_from_slice(&(snake.len() as u32).to_le_bytes());
+        for segment in snake.iter() {
+            write_cell(&mut bytes, segment);
+        }
+    }
+    bytes
+}
+
+pub fn versus_state_hash(state: &VersusState) -> [u8; 32] {
+    commitment(&encode_versus_state(state))
+}
+
+/// The tick chain head after `head` and one more tick, `delta`:
+/// `H(head || delta)` over [`StateDelta::encode`], with the same hash as
+/// [`state_hash`]. See [`GameState::tick_chain`].
+pub fn chain_tick(head: &[u8; 32], delta: &StateDelta) -> [u8; 32] {
+    let mut link = Commitment::new(head.len() + delta.encoded_len());
+    link.put(head);
+    delta.write(&mut link);
+    link.finalize()
+}
+
+/// Hash of [`rules::ENGINE_VERSION`] (little endian) followed by
+/// [`Level::hash`], which stays SHA-256 whatever the backend. It pins the
+/// grid, wall mode, food and scoring rules and the engine a game was played
+/// under, so verifiers can whitelist whole rule sets.
+pub fn config_hash(level: &Level) -> [u8; 32] {
+    let mut bytes = [0; 34];
+    bytes[..2].copy_from_slice(&rules::ENGINE_VERSION.to_le_bytes());
+    bytes[2..].copy_from_slice(&level.hash());
+    commitment(&bytes)
+}
+
+/// SHA-256 of [`GameState::to_bytes`], which covers everything a game
+/// resumes from, RNG states and move log included. Chunked proofs chain
+/// these from one chunk to the next.
+pub fn snapshot_hash(state: &GameState) -> [u8; 32] {
+    Sha256::digest(state.to_bytes()).into()
+}
+
+/// SHA-256 of a move log in the [`crate::moves::compress_moves`] encoding
+/// followed by the seed (little endian): the message a player signs with
+/// their Ed25519 key so a proof can only be made from their own game.
+pub fn move_digest(game_moves: &[u8], seed: u64) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(game_moves)
+        .chain_update(seed.to_le_bytes())
+        .finalize()
+        .into()
+}
+
+/// SHA-256 of `"snake-nullifier"`, `player`, then `seed` and `nonce` (both
+/// little endian). A leaderboard contract records the nullifiers it has
+/// accepted and rejects a proof whose nullifier it has seen, so the same
+/// game cannot be submitted twice, and a proof for one player cannot be
+/// replayed by another since the player is part of it.
+pub fn nullifier(player: &[u8; 20], seed: u64, nonce: u64) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(b"snake-nullifier")
+        .chain_update(player)
+        .chain_update(seed.to_le_bytes())
+        .chain_update(nonce.to_le_bytes())
+        .finalize()
+        .into()
+}
@@ -0,0 +1,360 @@
+//! Inputs and outputs of the SP1 program.
+//!
+//! The host writes [`SnakeGamePrivateInputs`] to the prover's stdin; the
+//! guest replays them and commits [`SnakeGamePublicInputs`] as its public
+//! values (ABI-encoded, see the `abi` module). The versus program does the
+//! same with [`VersusPrivateInputs`] and [`VersusPublicInputs`], the
+//! leaderboard program with [`LeaderboardPrivateInputs`] and
+//! [`LeaderboardPublicInputs`], and the threshold program with
+//! [`ThresholdPrivateInputs`] and [`ThresholdPublicInputs`]. The
+//! achievements program reads a [`SnakeGamePrivateInputs`] and commits
+//! [`AchievementPublicInputs`].
+//!
+//! Games too long to prove in one go are split into chunks: the chunk
+//! program plays [`ChunkPrivateInputs`] and commits [`ChunkPublicInputs`],
+//! and the chain program checks the chunk proofs with [`ChainPrivateInputs`]
+//! and commits the same [`SnakeGamePublicInputs`] as a single proof would.
+//!
+//! The aggregate program checks independent game proofs with
+//! [`AggregatePrivateInputs`] and commits [`AggregatePublicInputs`].
+
+use alloc::vec::Vec;
+
+use crate::challenge::Challenge;
+use crate::versus::PLAYERS;
+use crate::{moves, GameState, Level, Move, Position, ReplayPlayer, SnakeError, VersusState};
+
+/// Values committed by the SP1 program.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnakeGamePublicInputs {
+    pub game_state_hash: [u8; 32],
+    pub score: u64,
+    pub snake_length: u32,
+    pub grid_width: u32,
+    pub grid_height: u32,
+    /// Whether the game was played in wrap-around mode, so its score is
+    /// never mistaken for a classic one.
+    pub wrap_walls: bool,
+    /// [`Level::hash`] of the board, identifying the level the score was
+    /// set on.
+    pub level_hash: [u8; 32],
+    /// Seed the food was spawned from, e.g. a daily-challenge seed.
+    pub seed: u64,
+    /// [`crate::hash::config_hash`] of the level and engine version, for
+    /// whitelisting rule sets.
+    pub config_hash: [u8; 32],
+    /// Address the score is credited to.
+    pub player: [u8; 20],
+    /// Day of the daily challenge the game was played on, as `YYYYMMDD`,
+    /// or 0 for a free game; see the `challenge` module.
+    pub challenge_day: u32,
+    /// [`crate::challenge::salt_hash`] of the challenge's domain salt, or zero
+    /// for a free game.
+    pub challenge_salt_hash: [u8; 32],
+    /// [`crate::hash::nullifier`] of the player, seed and nonce, spent by
+    /// the contract that accepts the score.
+    pub nullifier: [u8; 32],
+    /// Ed25519 key that signed the move log, or zero for an unsigned game.
+    pub player_key: [u8; 32],
+    /// [`GameState::tick_chain`] of the finished game.
+    pub tick_chain: [u8; 32],
+}
+
+/// The recorded game, known only to the prover.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnakeGamePrivateInputs {
+    pub level: Level,
+    /// Game seed; the verifier spawns food from it and commits it.
+    pub seed: u64,
+    /// Move log in the [`moves::compress_moves`] encoding.
+    pub game_moves: Vec<u8>,
+    pub food_positions: Vec<(u32, u32)>,
+    pub player: [u8; 20],
+    /// The daily challenge the game claims; the verifier checks `seed` is
+    /// its seed and commits it.
+    pub challenge: Option<Challenge>,
+    /// Chosen by the player to tell their games on the same seed apart;
+    /// it only goes into the committed nullifier.
+    pub nonce: u64,
+    /// The player's signature over the moves and seed; the verifier checks
+    /// it and commits the key.
+    pub signature: Option<PlayerSignature>,
+}
+
+/// An Ed25519 signature over [`crate::hash::move_digest`] of a game's move
+/// log and seed, made in the player's browser when the game ends. A replay
+/// copied from someone else's session cannot be proven under the copier's
+/// key without their private key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerSignature {
+    pub public_key: [u8; 32],
+    /// The 64-byte signature, `R` then `s`.
+    pub signature: Vec<u8>,
+}
+
+impl SnakeGamePrivateInputs {
+    /// Builds the prover input for a game started with
+    /// `GameState::from_level(level, seed)` that recorded `moves`, by
+    /// playing it back to the end and noting where each food appeared, in
+    /// spawn order.
+    pub fn from_seeded_game(
+        level: &Level,
+        seed: u64,
+        moves: &[Move],
+    ) -> Result<SnakeGamePrivateInputs, SnakeError> {
+        let initial = GameState::from_level(level.clone(), seed)?;
+        let mut food_positions = initial.foods().to_vec();
+
+        let mut player = ReplayPlayer::new(initial, moves.to_vec());
+        while !player.is_finished() {
+            player.step_with_food(|state| {
+                let food = state.random_free_cell();
+                food_positions.extend(food);
+                food
+            })?;
+        }
+
+        Ok(SnakeGamePrivateInputs {
+            level: level.clone(),
+            seed,
+            game_moves: moves::compress_moves(moves),
+            food_positions: cells(&food_positions),
+            player: [0; 20],
+            challenge: None,
+            nonce: 0,
+            signature: None,
+        })
+    }
+}
+
+/// Values committed by the versus program.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VersusPublicInputs {
+    /// [`VersusState::state_hash`] of the final board.
+    pub match_state_hash: [u8; 32],
+    /// Final score of each player, by index.
+    pub scores: [u64; PLAYERS],
+    /// Index of the winning player, or [`PLAYERS`] for a draw.
+    pub winner: u8,
+    pub level_hash: [u8; 32],
+    pub seed: u64,
+    pub config_hash: [u8; 32],
+    /// Addresses of the players, by index.
+    pub players: [[u8; 20]; PLAYERS],
+}
+
+/// A recorded versus match, known only to the prover.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VersusPrivateInputs {
+    pub level: Level,
+    pub seed: u64,
+    /// Each player's move log in the [`moves::compress_moves`] encoding.
+    pub game_moves: [Vec<u8>; PLAYERS],
+    /// Every food in spawn order, across both players.
+    pub food_positions: Vec<(u32, u32)>,
+    pub players: [[u8; 20]; PLAYERS],
+}
+
+impl VersusPrivateInputs {
+    /// Builds the prover input for a match started with
+    /// `VersusState::from_level(level, seed)` in which each player recorded
+    /// the given moves, by playing it to the end and noting where each food
+    /// appeared.
+    pub fn from_seeded_match(
+        level: &Level,
+        seed: u64,
+        moves: [&[Move]; PLAYERS],
+    ) -> Result<VersusPrivateInputs, SnakeError> {
+        let mut state = VersusState::from_level(level.clone(), seed)?;
+        let mut food_positions = state.foods().to_vec();
+        state.play_out(moves, |state| {
+            let food = state.random_free_cell();
+            food_positions.extend(food);
+            food
+        })?;
+
+        Ok(VersusPrivateInputs {
+            level: level.clone(),
+            seed,
+            game_moves: moves.map(moves::compress_moves),
+            food_positions: cells(&food_positions),
+            players: [[0; 20]; PLAYERS],
+        })
+    }
+}
+
+/// Proven games to rank, known only to the leaderboard prover. The proof
+/// of each game is passed alongside through SP1 recursion and checked
+/// against `vkey_digest` and the game's public values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LeaderboardPrivateInputs {
+    /// Verification key digest of the single-game program, as
+    /// `SP1VerifyingKey::hash_u32` gives it.
+    pub vkey_digest: [u32; 8],
+    /// The public values each game proof commits, in proof order.
+    pub public_values: Vec<Vec<u8>>,
+    /// Number of players the board keeps.
+    pub top_n: u32,
+}
+
+/// Values committed by the leaderboard program.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LeaderboardPublicInputs {
+    /// `vkey_digest` as bytes, each word big endian, so a contract can
+    /// check which program proved the games.
+    pub game_vkey_digest: [u8; 32],
+    /// Config hash every ranked game was played under.
+    pub config_hash: [u8; 32],
+    /// Seed every ranked game was played on, e.g. the day's challenge.
+    pub seed: u64,
+    /// [`crate::leaderboard::Leaderboard::root`] of the board, leaves in
+    /// rank order.
+    pub root: [u8; 32],
+    /// The ranked players, best first, and their best scores.
+    pub players: Vec<[u8; 20]>,
+    pub scores: Vec<u64>,
+}
+
+/// A game proven to reach a score without revealing it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ThresholdPrivateInputs {
+    pub game: SnakeGamePrivateInputs,
+    /// Score the game must reach; the verifier refuses to prove less.
+    pub threshold: u64,
+}
+
+/// Values committed by the threshold program: that `player` scored at
+/// least `threshold` under the rule set `config_hash`, and nothing else
+/// about the game.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ThresholdPublicInputs {
+    pub threshold: u64,
+    pub config_hash: [u8; 32],
+    pub player: [u8; 20],
+}
+
+/// Values committed by the achievements program: the badges `player`
+/// earned in a game under the rule set `config_hash`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AchievementPublicInputs {
+    /// Flags from the [`crate::achievements`] module.
+    pub achievements: u32,
+    pub config_hash: [u8; 32],
+    pub player: [u8; 20],
+}
+
+/// One chunk of a long game, see [`crate::verify::replay_chunk`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChunkPrivateInputs {
+    /// [`GameState::to_bytes`] of the state the chunk starts from.
+    pub snapshot: Vec<u8>,
+    /// Moves made during the chunk, in the [`moves::compress_moves`]
+    /// encoding.
+    pub game_moves: Vec<u8>,
+    /// Most ticks the chunk plays.
+    pub ticks: u32,
+}
+
+/// Values committed by the chunk program: the
+/// [`crate::hash::snapshot_hash`] of the state before and after the chunk.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChunkPublicInputs {
+    pub input_hash: [u8; 32],
+    pub output_hash: [u8; 32],
+}
+
+/// Proven chunks of one game, in order, for the chain program. Their
+/// proofs are passed alongside through SP1 recursion and checked against
+/// `vkey_digest` and each chunk's public values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChainPrivateInputs {
+    /// Verification key digest of the chunk program, as
+    /// `SP1VerifyingKey::hash_u32` gives it.
+    pub vkey_digest: [u32; 8],
+    /// The public values each chunk proof commits.
+    pub chunks: Vec<Vec<u8>>,
+    /// [`GameState::to_bytes`] of the finished game the last chunk ends on.
+    pub final_snapshot: Vec<u8>,
+    pub player: [u8; 20],
+    pub nonce: u64,
+}
+
+/// Proven games to settle together, e.g. a tournament bracket. Their proofs
+/// are passed alongside through SP1 recursion and checked against
+/// `vkey_digest` and each game's public values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AggregatePrivateInputs {
+    /// Verification key digest of the single-game program, as
+    /// `SP1VerifyingKey::hash_u32` gives it.
+    pub vkey_digest: [u32; 8],
+    /// The public values each game proof commits, in proof order.
+    pub public_values: Vec<Vec<u8>>,
+}
+
+/// Values committed by the aggregate program, one entry per game in proof
+/// order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AggregatePublicInputs {
+    /// See [`LeaderboardPublicInputs::game_vkey_digest`].
+    pub game_vkey_digest: [u8; 32],
+    pub players: Vec<[u8; 20]>,
+    pub scores: Vec<u64>,
+    /// Each game's config hash, so a contract can check every game was
+    /// played under the tournament's rules.
+    pub config_hashes: Vec<[u8; 32]>,
+    /// Each game's nullifier, all distinct, so a contract can refuse games
+    /// it has already settled.
+    pub nullifiers: Vec<[u8; 32]>,
+    pub seeds: Vec<u64>,
+    /// Each game's signing key, zero for an unsigned game, which is what
+    /// ties the game to its player.
+    pub player_keys: Vec<[u8; 32]>,
+}
+
+impl AggregatePublicInputs {
+    /// The values committed for `games`, proven under the program with
+    /// `vkey_digest`.
+    pub fn from_games(vkey_digest: [u32; 8], games: &[SnakeGamePublicInputs]) -> Self {
+        AggregatePublicInputs {
+            game_vkey_digest: digest_bytes(vkey_digest),
+            players: games.iter().map(|game| game.player).collect(),
+            scores: games.iter().map(|game| game.score).collect(),
+            config_hashes: games.iter().map(|game| game.config_hash).collect(),
+            nullifiers: games.iter().map(|game| game.nullifier).collect(),
+            seeds: games.iter().map(|game| game.seed).collect(),
+            player_keys: games.iter().map(|game| game.player_key).collect(),
+        }
+    }
+}
+
+/// A verification key digest as committed: each word big endian, the way
+/// `SP1VerifyingKey::hash_bytes` and on-chain verifiers write it.
+pub fn digest_bytes(words: [u32; 8]) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    for (chunk, word) in bytes.chunks_exact_mut(4).zip(words) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    bytes
+}
+
+fn cells(positions: &[Position]) -> Vec<(u32, u32)> {
+    positions
+        .iter()
+        .map(|pos| (pos.x as u32, pos.y as u32))
+        .collect()
+}
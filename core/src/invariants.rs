@@ -0,0 +1,160 @@
+//! Structural self-checks on a [`GameState`], see
+//! [`GameState::validate_invariants`].
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{Direction, GameState, Position};
+
+/// One broken invariant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Violation {
+    /// The snake has no segments.
+    EmptySnake,
+    /// A segment lies off the grid.
+    SegmentOutOfBounds(Position),
+    /// A cell is covered by two segments.
+    DuplicateSegment(Position),
+    /// The segment at this index is not next to the one before it, taking
+    /// wrap-around edges into account.
+    Disconnected(usize),
+    /// A segment lies on an obstacle.
+    SegmentOnObstacle(Position),
+    /// The food kinds or spawn ticks do not cover every food slot.
+    FoodSlotsMismatch,
+    /// A food lies outside the current arena.
+    FoodOutOfBounds(Position),
+    /// Two foods share a cell.
+    DuplicateFood(Position),
+    /// A food lies under the snake.
+    FoodOnSnake(Position),
+    /// A food lies on an obstacle.
+    FoodOnObstacle(Position),
+    /// The game is running with no food left.
+    MissingFood,
+    /// More lives are left than the level grants.
+    TooManyLives,
+    /// An occupancy board disagrees with the snake, or with the obstacles
+    /// and food.
+    StaleOccupancy,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::EmptySnake => f.write_str("snake has no segments"),
+            Violation::SegmentOutOfBounds(pos) => {
+                write!(f, "segment at ({}, {}) is off the grid", pos.x, pos.y)
+            }
+            Violation::DuplicateSegment(pos) => {
+                write!(f, "two segments at ({}, {})", pos.x, pos.y)
+            }
+            Violation::Disconnected(index) => {
+                write!(f, "segment {index} is not next to segment {}", index - 1)
+            }
+            Violation::SegmentOnObstacle(pos) => {
+                write!(f, "segment at ({}, {}) is on an obstacle", pos.x, pos.y)
+            }
+            Violation::FoodSlotsMismatch => f.write_str("food slots are out of step"),
+            Violation::FoodOutOfBounds(pos) => {
+                write!(f, "food at ({}, {}) is outside the arena", pos.x, pos.y)
+            }
+            Violation::DuplicateFood(pos) => write!(f, "two foods at ({}, {})", pos.x, pos.y),
+            Violation::FoodOnSnake(pos) => {
+                write!(f, "food at ({}, {}) is under the snake", pos.x, pos.y)
+            }
+            Violation::FoodOnObstacle(pos) => {
+                write!(f, "food at ({}, {}) is on an obstacle", pos.x, pos.y)
+            }
+            Violation::MissingFood => f.write_str("game is running without food"),
+            Violation::TooManyLives => f.write_str("more lives than the level grants"),
+            Violation::StaleOccupancy => f.write_str("occupancy board disagrees with the game"),
+        }
+    }
+}
+
+/// Every invariant a [`GameState`] breaks, empty for a sound state.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InvariantReport {
+    pub violations: Vec<Violation>,
+}
+
+impl InvariantReport {
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl fmt::Display for InvariantReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return f.write_str("no invariant violations");
+        }
+        for (i, violation) in self.violations.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{violation}")?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn check(state: &GameState) -> InvariantReport {
+    let level = state.level();
+    let (snake, foods) = (state.snake(), state.foods());
+    let mut violations = Vec::new();
+
+    if snake.is_empty() {
+        violations.push(Violation::EmptySnake);
+    }
+    for (i, segment) in snake.iter().enumerate() {
+        if !level.in_bounds(segment) {
+            violations.push(Violation::SegmentOutOfBounds(segment));
+        }
+        if snake.contains_in(i, segment) {
+            violations.push(Violation::DuplicateSegment(segment));
+        }
+        let adjacent = |prev: Position| {
+            Direction::ALL
+                .into_iter()
+                .any(|direction| level.neighbour(prev, direction) == segment)
+        };
+        if i > 0 && !snake.get(i - 1).is_some_and(adjacent) {
+            violations.push(Violation::Disconnected(i));
+        }
+        if level.obstacles.contains(&segment) {
+            violations.push(Violation::SegmentOnObstacle(segment));
+        }
+    }
+
+    if state.food_kinds().len() != foods.len() || state.food_ticks().len() != foods.len() {
+        violations.push(Violation::FoodSlotsMismatch);
+    }
+    for (i, &food) in foods.iter().enumerate() {
+        if !state.in_bounds(food) {
+            violations.push(Violation::FoodOutOfBounds(food));
+        }
+        if foods[..i].contains(&food) {
+            violations.push(Violation::DuplicateFood(food));
+        }
+        if snake.contains(food) {
+            violations.push(Violation::FoodOnSnake(food));
+        }
+        if level.obstacles.contains(&food) {
+            violations.push(Violation::FoodOnObstacle(food));
+        }
+    }
+    if foods.is_empty() && !state.is_game_over() {
+        violations.push(Violation::MissingFood);
+    }
+    if state.lives() > level.lives {
+        violations.push(Violation::TooManyLives);
+    }
+    if !state.occupancy_matches() {
+        violations.push(Violation::StaleOccupancy);
+    }
+    InvariantReport { violations }
+}
@@ -0,0 +1,157 @@
+//! Keccak-256, as the EVM's `keccak256` computes it.
+//!
+//! Inside the SP1 zkVM the permutation is the keccak precompile, which
+//! `sp1-zkvm` links into every guest; everywhere else it is computed here.
+//! Both give identical digests.
+
+const RATE: usize = 136;
+
+/// The Keccak-256 digest of `bytes`. Known answers, as `keccak256` gives
+/// them:
+///
+/// ```
+/// use snake_core::keccak::keccak256;
+///
+/// let hex = |bytes: &[u8]| -> String { bytes.iter().map(|b| format!("{b:02x}")).collect() };
+/// assert_eq!(
+///     hex(&keccak256(b"")),
+///     "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+/// );
+/// assert_eq!(
+///     hex(&keccak256(b"abc")),
+///     "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+/// );
+/// ```
+pub fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(bytes);
+    hasher.finalize()
+}
+
+/// [`keccak256`] fed piece by piece, holding at most one block.
+pub(crate) struct Keccak256 {
+    state: [u64; 25],
+    block: [u8; RATE],
+    filled: usize,
+}
+
+impl Keccak256 {
+    pub(crate) fn new() -> Keccak256 {
+        Keccak256 {
+            state: [0; 25],
+            block: [0; RATE],
+            filled: 0,
+        }
+    }
+
+    pub(crate) fn update(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            let take = bytes.len().min(RATE - self.filled);
+            self.block[self.filled..self.filled + take].copy_from_slice(&bytes[..take]);
+            self.filled += take;
+            bytes = &bytes[take..];
+            if self.filled == RATE {
+                absorb(&mut self.state, &self.block);
+                self.filled = 0;
+            }
+        }
+    }
+
+    pub(crate) fn finalize(mut self) -> [u8; 32] {
+        // Keccak padding, not SHA-3's: 0x01, zeros, then 0x80
+        self.block[self.filled..].fill(0);
+        self.block[self.filled] ^= 0x01;
+        self.block[RATE - 1] ^= 0x80;
+        absorb(&mut self.state, &self.block);
+
+        let mut digest = [0u8; 32];
+        for (chunk, lane) in digest.chunks_exact_mut(8).zip(self.state) {
+            chunk.copy_from_slice(&lane.to_le_bytes());
+        }
+        digest
+    }
+}
+
+fn absorb(state: &mut [u64; 25], block: &[u8]) {
+    for (lane, chunk) in state.iter_mut().zip(block.chunks_exact(8)) {
+        *lane ^= u64::from_le_bytes(chunk.try_into().expect("8 bytes"));
+    }
+    permute(state);
+}
+
+#[cfg(target_os = "zkvm")]
+fn permute(state: &mut [u64; 25]) {
+    extern "C" {
+        fn syscall_keccak_permute(state: *mut [u64; 25]);
+    }
+    // SAFETY: the syscall reads and writes exactly the 25 lanes
+    unsafe { syscall_keccak_permute(state) }
+}
+
+#[cfg(not(target_os = "zkvm"))]
+fn permute(state: &mut [u64; 25]) {
+    const ROUND_CONSTANTS: [u64; 24] = [
+        0x0000000000000001,
+        0x0000000000008082,
+        0x800000000000808a,
+        0x8000000080008000,
+        0x000000000000808b,
+        0x0000000080000001,
+        0x8000000080008081,
+        0x8000000000008009,
+        0x000000000000008a,
+        0x0000000000000088,
+        0x0000000080008009,
+        0x000000008000000a,
+        0x000000008000808b,
+        0x800000000000008b,
+        0x8000000000008089,
+        0x8000000000008003,
+        0x8000000000008002,
+        0x8000000000000080,
+        0x000000000000800a,
+        0x800000008000000a,
+        0x8000000080008081,
+        0x8000000000008080,
+        0x0000000080000001,
+        0x8000000080008008,
+    ];
+    // Rotation and destination of each lane in the rho and pi steps,
+    // following lane 1 around the cycle
+    const ROTATIONS: [u32; 24] = [
+        1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+    ];
+    const PI: [usize; 24] = [
+        10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+    ];
+
+    for round_constant in ROUND_CONSTANTS {
+        // Theta
+        let mut columns = [0u64; 5];
+        for (x, column) in columns.iter_mut().enumerate() {
+            *column = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+        }
+        for x in 0..5 {
+            let d = columns[(x + 4) % 5] ^ columns[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                state[x + 5 * y] ^= d;
+            }
+        }
+        // Rho and pi
+        let mut lane = state[1];
+        for (&rotation, &to) in ROTATIONS.iter().zip(&PI) {
+            let next = state[to];
+            state[to] = lane.rotate_left(rotation);
+            lane = next;
+        }
+        // Chi
+        for y in 0..5 {
+            let row: [u64; 5] = state[5 * y..5 * y + 5].try_into().expect("5 lanes");
+            for x in 0..5 {
+                state[x + 5 * y] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+        // Iota
+        state[0] ^= round_constant;
+    }
+}
@@ -0,0 +1,282 @@
+//! A leaderboard committed to by a single Merkle root.
+//!
+//! Each player holds one leaf, in the order players were first added, so a
+//! contract can store just the 32-byte root and check any `(player, score)`
+//! against it with a [`MerkleProof`]. Leaves and nodes are SHA-256, which
+//! the EVM has as a precompile, with a domain byte so a node can never pass
+//! as a leaf:
+//!
+//! - leaf: `sha256(0x00 || player || score)`, the 20-byte address then the
+//!   score as a big-endian `u64`, i.e. `abi.encodePacked(address, uint64)`
+//! - node: `sha256(0x01 || left || right)`; a node without a right sibling
+//!   is paired with 32 zero bytes
+//!
+//! A single leaf is its own root, and an empty leaderboard's root is 32
+//! zero bytes. Changing any of this changes every root already anchored.
+//!
+//! [`Leaderboard::prove_inclusion`] gives a player a [`MerkleProof`] they
+//! can hand to anyone holding the published root, who checks it with
+//! [`verify_inclusion`] without trusting whoever served the board. Encoded
+//! proof layout, integers little endian:
+//!
+//! | bytes  | field                                   |
+//! |--------|-----------------------------------------|
+//! | 4      | leaf index                              |
+//! | 1      | sibling count, at most [`MAX_DEPTH`]    |
+//! | 32 × n | sibling hashes, from the leaf level up  |
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use sha2::{Digest, Sha256};
+
+use crate::codec::Reader;
+use crate::inputs::SnakeGamePublicInputs;
+use crate::SnakeError;
+
+/// Most siblings an encoded [`MerkleProof`] carries, enough for 2^32
+/// players.
+pub const MAX_DEPTH: usize = 32;
+
+const LEAF: u8 = 0;
+const NODE: u8 = 1;
+const EMPTY: [u8; 32] = [0; 32];
+
+/// Hash of the leaf for `player` holding `score`.
+pub fn leaf_hash(player: &[u8; 20], score: u64) -> [u8; 32] {
+    Sha256::new()
+        .chain_update([LEAF])
+        .chain_update(player)
+        .chain_update(score.to_be_bytes())
+        .finalize()
+        .into()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update([NODE])
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+/// Evidence that a player's leaf is in the tree with a given root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MerkleProof {
+    /// Position of the leaf, which decides the side of each sibling.
+    pub index: usize,
+    /// Sibling hashes from the leaf level up.
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl MerkleProof {
+    /// The root this proof yields for `player` holding `score`; compare it
+    /// with the anchored root.
+    pub fn root(&self, player: &[u8; 20], score: u64) -> [u8; 32] {
+        let mut hash = leaf_hash(player, score);
+        let mut index = self.index;
+        for sibling in &self.siblings {
+            hash = match index % 2 {
+                0 => node_hash(&hash, sibling),
+                _ => node_hash(sibling, &hash),
+            };
+            index /= 2;
+        }
+        hash
+    }
+
+    pub fn verify(&self, root: &[u8; 32], player: &[u8; 20], score: u64) -> bool {
+        self.root(player, score) == *root
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(5 + self.siblings.len() * 32);
+        bytes.extend_from_slice(&(self.index as u32).to_le_bytes());
+        bytes.push(self.siblings.len() as u8);
+        for sibling in &self.siblings {
+            bytes.extend_from_slice(sibling);
+        }
+        bytes
+    }
+
+    /// Decodes [`MerkleProof::encode`] output. Fails with
+    /// [`SnakeError::BadProof`] if the buffer is truncated, has trailing
+    /// bytes, more than [`MAX_DEPTH`] siblings or an index the siblings
+    /// cannot reach.
+    pub fn decode(bytes: &[u8]) -> Result<MerkleProof, SnakeError> {
+        let mut reader = Reader::new(bytes, SnakeError::BadProof);
+        let index = reader.u32()?;
+        let depth = usize::from(reader.u8()?);
+        if depth > MAX_DEPTH || (depth < MAX_DEPTH && index >> depth != 0) {
+            return Err(SnakeError::BadProof);
+        }
+        let siblings = (0..depth)
+            .map(|_| Ok(reader.take(32)?.try_into().expect("32 bytes")))
+            .collect::<Result<Vec<[u8; 32]>, SnakeError>>()?;
+        reader.finish()?;
+        Ok(MerkleProof {
+            index: index as usize,
+            siblings,
+        })
+    }
+}
+
+/// Checks an encoded [`MerkleProof`] that `player` holds `score` on the
+/// leaderboard with `root`. Fails with [`SnakeError::BadProof`] if the
+/// proof does not decode, and returns `false` if it does not match.
+pub fn verify_inclusion(
+    root: &[u8; 32],
+    player: &[u8; 20],
+    score: u64,
+    proof: &[u8],
+) -> Result<bool, SnakeError> {
+    Ok(MerkleProof::decode(proof)?.verify(root, player, score))
+}
+
+/// Players' scores with their Merkle tree, kept up to date on every change
+/// at a cost logarithmic in the number of players.
+#[derive(Clone, Debug, Default)]
+pub struct Leaderboard {
+    players: Vec<([u8; 20], u64)>,
+    index: BTreeMap<[u8; 20], usize>,
+    /// Every level of the tree, leaves first; the last holds the root.
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl Leaderboard {
+    pub fn new() -> Leaderboard {
+        Leaderboard::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.players.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.players.is_empty()
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        match self.levels.last() {
+            Some(top) => top[0],
+            None => EMPTY,
+        }
+    }
+
+    pub fn score(&self, player: &[u8; 20]) -> Option<u64> {
+        self.index.get(player).map(|&index| self.players[index].1)
+    }
+
+    /// Players and scores in leaf order.
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8; 20], u64)> {
+        self.players.iter().map(|(player, score)| (player, *score))
+    }
+
+    /// Adds `player` with `score` as the next leaf and returns its index.
+    /// Fails with [`SnakeError::BadInput`] if the player already has one;
+    /// use [`Leaderboard::update`] or [`Leaderboard::record`] for that.
+    pub fn insert(&mut self, player: [u8; 20], score: u64) -> Result<usize, SnakeError> {
+        if self.index.contains_key(&player) {
+            return Err(SnakeError::BadInput);
+        }
+        let index = self.players.len();
+        self.players.push((player, score));
+        self.index.insert(player, index);
+        self.set_leaf(index, leaf_hash(&player, score));
+        Ok(index)
+    }
+
+    /// Sets the score of a player already on the board. Fails with
+    /// [`SnakeError::UnknownPlayer`] otherwise.
+    pub fn update(&mut self, player: &[u8; 20], score: u64) -> Result<(), SnakeError> {
+        let index = *self.index.get(player).ok_or(SnakeError::UnknownPlayer)?;
+        self.players[index].1 = score;
+        self.set_leaf(index, leaf_hash(player, score));
+        Ok(())
+    }
+
+    /// Records a verified `score`, adding the player if new and keeping
+    /// their best otherwise. Returns whether the board changed.
+    pub fn record(&mut self, player: [u8; 20], score: u64) -> bool {
+        match self.score(&player) {
+            None => self.insert(player, score).is_ok(),
+            Some(best) if score > best => self.update(&player, score).is_ok(),
+            Some(_) => false,
+        }
+    }
+
+    /// Proof that `player` holds their current score under the current
+    /// root, or `None` if they are not on the board. It goes stale as soon
+    /// as any score changes.
+    pub fn prove_inclusion(&self, player: &[u8; 20]) -> Option<MerkleProof> {
+        let leaf = *self.index.get(player)?;
+        let mut index = leaf;
+        let mut siblings = Vec::with_capacity(self.levels.len());
+        for level in &self.levels[..self.levels.len() - 1] {
+            siblings.push(level.get(index ^ 1).copied().unwrap_or(EMPTY));
+            index /= 2;
+        }
+        Some(MerkleProof {
+            index: leaf,
+            siblings,
+        })
+    }
+
+    /// Writes leaf `index`, which is either existing or the next one, and
+    /// rehashes its path to the root.
+    fn set_leaf(&mut self, mut index: usize, mut hash: [u8; 32]) {
+        if self.levels.is_empty() {
+            self.levels.push(Vec::new());
+        }
+        let mut depth = 0;
+        loop {
+            let level = &mut self.levels[depth];
+            match level.get_mut(index) {
+                Some(slot) => *slot = hash,
+                None => level.push(hash),
+            }
+            if level.len() == 1 {
+                return;
+            }
+            let sibling = level.get(index ^ 1).copied().unwrap_or(EMPTY);
+            hash = match index % 2 {
+                0 => node_hash(&hash, &sibling),
+                _ => node_hash(&sibling, &hash),
+            };
+            index /= 2;
+            depth += 1;
+            if self.levels.len() == depth {
+                self.levels.push(Vec::new());
+            }
+        }
+    }
+}
+
+/// The board of the best `top_n` players among `games`, each counted once
+/// with their best score, leaves in rank order: higher score first, lower
+/// address first among equal scores. Fails with
+/// [`SnakeError::MismatchedGames`] unless every game shares one config
+/// hash and seed, since scores from different rules or seeds do not rank.
+pub fn rank_games(
+    games: &[SnakeGamePublicInputs],
+    top_n: usize,
+) -> Result<Leaderboard, SnakeError> {
+    let mut best = BTreeMap::new();
+    for game in games {
+        if game.config_hash != games[0].config_hash || game.seed != games[0].seed {
+            return Err(SnakeError::MismatchedGames);
+        }
+        let score = best.entry(game.player).or_insert(game.score);
+        *score = (*score).max(game.score);
+    }
+    let mut ranked: Vec<([u8; 20], u64)> = best.into_iter().collect();
+    // Stable, so equal scores keep the address order of the map
+    ranked.sort_by_key(|&(_, score)| core::cmp::Reverse(score));
+    let mut board = Leaderboard::new();
+    for (player, score) in ranked.into_iter().take(top_n) {
+        board.insert(player, score)?;
+    }
+    Ok(board)
+}
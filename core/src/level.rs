@@ -0,0 +1,495 @@
+//! Level definitions: board size, walls, obstacles, start, food and game
+//! rules.
+//!
+//! A level fully describes the board a game is played on and the rules it
+//! is played by, everything except the seed. Its [`Level::hash`] is
+//! committed with every proof, so scores set on different levels cannot be
+//! confused.
+
+#[cfg(feature = "json")]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "json")]
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use sha2::{Digest, Sha256};
+
+use crate::codec::Reader;
+use crate::rules::{self, Difficulty, IllegalMovePolicy, ScoringMode, ScoringRules, SpeedStep};
+use crate::{Direction, Position, SnakeError};
+
+/// Largest grid side. Cells are stored as two 16-bit coordinates, see
+/// [`crate::Body`].
+pub const MAX_GRID_SIZE: i32 = u16::MAX as i32;
+
+/// Largest grid area. Validation, food spawns and the verifier all walk
+/// the grid, so this bounds what a replay from anyone can make them do.
+pub const MAX_CELLS: usize = 4096;
+
+/// Effect of a food item when eaten.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FoodKind {
+    /// Grows the snake and scores [`ScoringRules::points_per_food`].
+    #[default]
+    Normal,
+    /// Grows the snake and scores [`ScoringRules::points_per_golden_food`].
+    Golden,
+    /// Shrinks the snake by one and scores nothing; fatal for a snake of
+    /// length one.
+    Poison,
+}
+
+impl FoodKind {
+    /// Stable one-byte encoding used in snapshots and the state hash.
+    pub const fn to_byte(self) -> u8 {
+        match self {
+            FoodKind::Normal => 0,
+            FoodKind::Golden => 1,
+            FoodKind::Poison => 2,
+        }
+    }
+
+    pub const fn from_byte(byte: u8) -> Option<FoodKind> {
+        match byte {
+            0 => Some(FoodKind::Normal),
+            1 => Some(FoodKind::Golden),
+            2 => Some(FoodKind::Poison),
+            _ => None,
+        }
+    }
+}
+
+/// How food appears on a level.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct FoodRules {
+    /// Cell of the first food; seeded at random when `None`.
+    pub first: Option<Position>,
+    /// Number of food items on the board at once. Each one eaten is replaced
+    /// in place by the next seeded spawn.
+    pub count: u32,
+    /// Percentage of spawns that are [`FoodKind::Golden`].
+    pub golden_percent: u8,
+    /// Percentage of spawns that are [`FoodKind::Poison`].
+    pub poison_percent: u8,
+    /// Ticks a food stays before it despawns and a new one is seeded
+    /// elsewhere; zero keeps food until eaten.
+    pub lifetime_ticks: u32,
+}
+
+impl FoodRules {
+    /// Maps a roll in `0..100` to the kind of food it spawns.
+    pub fn kind_for_roll(&self, roll: u32) -> FoodKind {
+        let golden = u32::from(self.golden_percent);
+        if roll < golden {
+            FoodKind::Golden
+        } else if roll < golden + u32::from(self.poison_percent) {
+            FoodKind::Poison
+        } else {
+            FoodKind::Normal
+        }
+    }
+}
+
+/// The playable rectangle of a level at some tick, corners inclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Arena {
+    pub min: Position,
+    pub max: Position,
+}
+
+impl Arena {
+    pub fn contains(&self, pos: Position) -> bool {
+        pos.x >= self.min.x && pos.x <= self.max.x && pos.y >= self.min.y && pos.y <= self.max.y
+    }
+
+    /// Number of cells inside.
+    pub fn cells(&self) -> u32 {
+        let side = |min: i32, max: i32| max.saturating_sub(min).saturating_add(1).max(0) as u32;
+        let (width, height) = (side(self.min.x, self.max.x), side(self.min.y, self.max.y));
+        width.saturating_mul(height)
+    }
+}
+
+impl Default for FoodRules {
+    fn default() -> Self {
+        FoodRules {
+            first: None,
+            count: 1,
+            golden_percent: 0,
+            poison_percent: 0,
+            lifetime_ticks: 0,
+        }
+    }
+}
+
+/// A playable board. With the `serde` feature a level can be written as
+/// JSON, e.g. `{"grid_width": 20, "grid_height": 15, "start": {"x": 5, "y": 7},
+/// "start_direction": "Right", "obstacles": [{"x": 10, "y": 7}]}`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Level {
+    pub grid_width: i32,
+    pub grid_height: i32,
+    /// Toroidal mode: leaving one edge re-enters on the opposite side.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub wrap_walls: bool,
+    /// Static wall tiles inside the grid.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub obstacles: Vec<Position>,
+    /// Cell of the snake's head at the start.
+    pub start: Position,
+    /// Initial heading; the body trails behind it.
+    pub start_direction: Direction,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub food: FoodRules,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub scoring: ScoringRules,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub illegal_moves: IllegalMovePolicy,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub difficulty: Difficulty,
+    /// Lives per game, at least one. Every death but the last respawns the
+    /// snake at `start`.
+    #[cfg_attr(feature = "serde", serde(default = "one_life"))]
+    pub lives: u8,
+    /// Tick budget for timed challenges: the game ends once this many ticks
+    /// have run. Zero means no limit.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_ticks: u32,
+    /// Battle-royale mode: every this many ticks the arena loses its outer
+    /// ring, down to a one- or two-cell-wide strip, and cells outside it
+    /// kill the snake like walls. Zero keeps the whole grid.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub shrink_interval_ticks: u32,
+}
+
+#[cfg(feature = "serde")]
+fn one_life() -> u8 {
+    1
+}
+
+impl Level {
+    /// The open board used by `GameState::new`: no obstacles, snake in the
+    /// centre heading right.
+    pub fn classic(grid_width: i32, grid_height: i32, wrap_walls: bool) -> Level {
+        Level {
+            grid_width,
+            grid_height,
+            wrap_walls,
+            obstacles: Vec::new(),
+            start: Position::new(grid_width / 2, grid_height / 2),
+            start_direction: Direction::Right,
+            food: FoodRules::default(),
+            scoring: ScoringRules::default(),
+            illegal_moves: IllegalMovePolicy::default(),
+            difficulty: Difficulty::default(),
+            lives: 1,
+            max_ticks: 0,
+            shrink_interval_ticks: 0,
+        }
+    }
+
+    /// The open board for versus play: like [`Level::classic`] with the
+    /// snake a quarter of the way in from the top-left corner, so its
+    /// [`Level::rival_start`] is clear of it.
+    pub fn versus(grid_width: i32, grid_height: i32, wrap_walls: bool) -> Level {
+        Level {
+            start: Position::new(grid_width / 4, grid_height / 4),
+            ..Level::classic(grid_width, grid_height, wrap_walls)
+        }
+    }
+
+    pub fn in_bounds(&self, pos: Position) -> bool {
+        pos.x >= 0 && pos.x < self.grid_width && pos.y >= 0 && pos.y < self.grid_height
+    }
+
+    /// The playable area after `ticks` ticks: the grid less one outer ring
+    /// per [`Level::shrink_interval_ticks`] elapsed.
+    pub fn arena(&self, ticks: u32) -> Arena {
+        let max_rings = self
+            .grid_width
+            .min(self.grid_height)
+            .saturating_sub(1)
+            .max(0)
+            / 2;
+        let rings = match self.shrink_interval_ticks {
+            0 => 0,
+            interval => (ticks / interval).min(max_rings as u32) as i32,
+        };
+        Arena {
+            min: Position::new(rings, rings),
+            max: Position::new(
+                self.grid_width.saturating_sub(1 + rings),
+                self.grid_height.saturating_sub(1 + rings),
+            ),
+        }
+    }
+
+    /// The cell next to `pos` in `direction`, wrapping around the edges in
+    /// wrap mode. Outside wrap mode this may be off the board.
+    pub fn neighbour(&self, pos: Position, direction: Direction) -> Position {
+        let next = pos.moved(direction);
+        if self.wrap_walls {
+            // An empty grid has nothing to wrap around
+            Position::new(
+                next.x.checked_rem_euclid(self.grid_width).unwrap_or(next.x),
+                next.y
+                    .checked_rem_euclid(self.grid_height)
+                    .unwrap_or(next.y),
+            )
+        } else {
+            next
+        }
+    }
+
+    /// The starting snake, head first, trailing away from
+    /// `start_direction`.
+    pub fn start_snake(&self) -> Vec<Position> {
+        self.snake_at(self.start, self.start_direction)
+    }
+
+    /// A snake of the initial length with its head on `head`, trailing away
+    /// from `direction`.
+    pub fn snake_at(&self, head: Position, direction: Direction) -> Vec<Position> {
+        let mut snake = vec![head];
+        while snake.len() < rules::INITIAL_LENGTH as usize {
+            let tail = snake[snake.len() - 1];
+            snake.push(self.neighbour(tail, direction.opposite()));
+        }
+        snake
+    }
+
+    /// Start of the second snake in versus mode: the level's start mirrored
+    /// through the centre of the grid, heading the opposite way.
+    pub fn rival_start(&self) -> (Position, Direction) {
+        let head = Position::new(
+            self.grid_width - 1 - self.start.x,
+            self.grid_height - 1 - self.start.y,
+        );
+        (head, self.start_direction.opposite())
+    }
+
+    /// Checks that the level is playable. Fails with
+    /// [`SnakeError::BadLayout`] if the grid is empty, wider or taller than
+    /// [`MAX_GRID_SIZE`] or larger than [`MAX_CELLS`], an obstacle is off the
+    /// grid or repeated, the starting snake leaves the grid, overlaps itself
+    /// or sits on an obstacle, the first food is not on a free cell, the food
+    /// count is zero or exceeds the free cells, the special food percentages
+    /// add up to more than 100, the speed schedule is not valid (see
+    /// [`Difficulty::is_valid`]), there are no lives, or some free cell
+    /// cannot be reached from the start.
+    pub fn validate(&self) -> Result<(), SnakeError> {
+        if self.grid_width <= 0
+            || self.grid_height <= 0
+            || self.grid_width > MAX_GRID_SIZE
+            || self.grid_height > MAX_GRID_SIZE
+            || self.grid_width as usize * self.grid_height as usize > MAX_CELLS
+        {
+            return Err(SnakeError::BadLayout);
+        }
+        let cells = self.grid_width as usize * self.grid_height as usize;
+        let index = |pos: Position| pos.y as usize * self.grid_width as usize + pos.x as usize;
+
+        let mut walls = vec![false; cells];
+        let obstacles_valid = self.obstacles.iter().all(|&obstacle| {
+            self.in_bounds(obstacle) && !core::mem::replace(&mut walls[index(obstacle)], true)
+        });
+        if !obstacles_valid {
+            return Err(SnakeError::BadLayout);
+        }
+        let snake = self.start_snake();
+        let snake_valid = snake.iter().enumerate().all(|(i, &segment)| {
+            self.in_bounds(segment) && !snake[..i].contains(&segment) && !walls[index(segment)]
+        });
+        let food_valid = self.food.first.is_none_or(|food| {
+            self.in_bounds(food) && !snake.contains(&food) && !walls[index(food)]
+        });
+        let free_cells = cells.saturating_sub(self.obstacles.len() + snake.len());
+        let count_valid = self.food.count >= 1 && self.food.count as usize <= free_cells;
+        let kinds_valid = self.food.golden_percent as u32 + self.food.poison_percent as u32 <= 100;
+        if !(snake_valid
+            && food_valid
+            && count_valid
+            && kinds_valid
+            && self.difficulty.is_valid()
+            && self.lives >= 1
+            && self.is_fully_reachable(walls))
+        {
+            return Err(SnakeError::BadLayout);
+        }
+        Ok(())
+    }
+
+    /// Whether every cell that is not an obstacle can be reached from the
+    /// start, so food can never spawn somewhere the snake cannot go. `seen`
+    /// marks the obstacles, row by row.
+    fn is_fully_reachable(&self, mut seen: Vec<bool>) -> bool {
+        let width = self.grid_width as usize;
+        let index = |pos: Position| pos.y as usize * width + pos.x as usize;
+
+        let mut open = seen.len() - self.obstacles.len();
+        let mut queue = vec![self.start];
+        seen[index(self.start)] = true;
+        while let Some(cell) = queue.pop() {
+            open -= 1;
+            for direction in Direction::ALL {
+                let next = self.neighbour(cell, direction);
+                if self.in_bounds(next) && !seen[index(next)] {
+                    seen[index(next)] = true;
+                    queue.push(next);
+                }
+            }
+        }
+        open == 0
+    }
+
+    /// Canonical byte encoding, integers little endian: width, height, wrap
+    /// byte, start x/y, start direction byte, first-food flag and x/y, food
+    /// count, golden and poison percentage bytes, food lifetime, scoring mode
+    /// byte and the six other scoring rules in declaration order,
+    /// illegal-move policy byte, base tick interval, speed step count and
+    /// min-score/interval pairs, lives byte, tick budget, shrink interval,
+    /// obstacle count and cells.
+    pub fn encode(&self) -> Vec<u8> {
+        let steps = self.difficulty.steps.len();
+        let mut bytes = Vec::with_capacity(84 + (steps + self.obstacles.len()) * 8);
+        self.write(&mut bytes);
+        bytes
+    }
+
+    pub(crate) fn write(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.grid_width.to_le_bytes());
+        bytes.extend_from_slice(&self.grid_height.to_le_bytes());
+        bytes.push(self.wrap_walls as u8);
+        bytes.extend_from_slice(&self.start.x.to_le_bytes());
+        bytes.extend_from_slice(&self.start.y.to_le_bytes());
+        bytes.push(self.start_direction.to_byte());
+        let first = self.food.first.unwrap_or(Position::new(0, 0));
+        bytes.push(self.food.first.is_some() as u8);
+        bytes.extend_from_slice(&first.x.to_le_bytes());
+        bytes.extend_from_slice(&first.y.to_le_bytes());
+        bytes.extend_from_slice(&self.food.count.to_le_bytes());
+        bytes.push(self.food.golden_percent);
+        bytes.push(self.food.poison_percent);
+        bytes.extend_from_slice(&self.food.lifetime_ticks.to_le_bytes());
+        let scoring = &self.scoring;
+        bytes.push(scoring.mode.to_byte());
+        bytes.extend_from_slice(&scoring.points_per_food.to_le_bytes());
+        bytes.extend_from_slice(&scoring.points_per_golden_food.to_le_bytes());
+        bytes.extend_from_slice(&scoring.growth_per_food.to_le_bytes());
+        bytes.extend_from_slice(&scoring.survival_points_per_tick.to_le_bytes());
+        bytes.extend_from_slice(&scoring.combo_window_ticks.to_le_bytes());
+        bytes.extend_from_slice(&scoring.max_combo_multiplier.to_le_bytes());
+        bytes.push(self.illegal_moves.to_byte());
+        bytes.extend_from_slice(&self.difficulty.base_interval_ms.to_le_bytes());
+        bytes.extend_from_slice(&(self.difficulty.steps.len() as u32).to_le_bytes());
+        for step in &self.difficulty.steps {
+            bytes.extend_from_slice(&step.min_score.to_le_bytes());
+            bytes.extend_from_slice(&step.interval_ms.to_le_bytes());
+        }
+        bytes.push(self.lives);
+        bytes.extend_from_slice(&self.max_ticks.to_le_bytes());
+        bytes.extend_from_slice(&self.shrink_interval_ticks.to_le_bytes());
+        bytes.extend_from_slice(&(self.obstacles.len() as u32).to_le_bytes());
+        for cell in &self.obstacles {
+            bytes.extend_from_slice(&cell.x.to_le_bytes());
+            bytes.extend_from_slice(&cell.y.to_le_bytes());
+        }
+    }
+
+    /// Reads a level written by [`Level::write`], failing with the reader's
+    /// error on malformed input. The level is not validated.
+    pub(crate) fn read(reader: &mut Reader) -> Result<Level, SnakeError> {
+        let grid_width = reader.i32()?;
+        let grid_height = reader.i32()?;
+        let wrap_walls = reader.bool()?;
+        let start = Position::new(reader.i32()?, reader.i32()?);
+        let start_direction = Direction::from_byte(reader.u8()?).ok_or(reader.error())?;
+        let has_first = reader.bool()?;
+        let first = Position::new(reader.i32()?, reader.i32()?);
+        let food_count = reader.u32()?;
+        let golden_percent = reader.u8()?;
+        let poison_percent = reader.u8()?;
+        let lifetime_ticks = reader.u32()?;
+        let scoring = ScoringRules {
+            mode: ScoringMode::from_byte(reader.u8()?).ok_or(reader.error())?,
+            points_per_food: reader.u32()?,
+            points_per_golden_food: reader.u32()?,
+            growth_per_food: reader.u32()?,
+            survival_points_per_tick: reader.u32()?,
+            combo_window_ticks: reader.u32()?,
+            max_combo_multiplier: reader.u32()?,
+        };
+        let illegal_moves = IllegalMovePolicy::from_byte(reader.u8()?).ok_or(reader.error())?;
+        let base_interval_ms = reader.u32()?;
+        let step_count = reader.u32()? as usize;
+        let steps = (0..step_count)
+            .map(|_| {
+                Ok(SpeedStep {
+                    min_score: reader.u32()?,
+                    interval_ms: reader.u32()?,
+                })
+            })
+            .collect::<Result<Vec<_>, SnakeError>>()?;
+        let lives = reader.u8()?;
+        let max_ticks = reader.u32()?;
+        let shrink_interval_ticks = reader.u32()?;
+        let obstacle_count = reader.u32()? as usize;
+        let obstacles = (0..obstacle_count)
+            .map(|_| Ok(Position::new(reader.i32()?, reader.i32()?)))
+            .collect::<Result<Vec<_>, SnakeError>>()?;
+        Ok(Level {
+            grid_width,
+            grid_height,
+            wrap_walls,
+            obstacles,
+            start,
+            start_direction,
+            food: FoodRules {
+                first: has_first.then_some(first),
+                count: food_count,
+                golden_percent,
+                poison_percent,
+                lifetime_ticks,
+            },
+            scoring,
+            illegal_moves,
+            difficulty: Difficulty {
+                base_interval_ms,
+                steps,
+            },
+            lives,
+            max_ticks,
+            shrink_interval_ticks,
+        })
+    }
+
+    /// SHA-256 of [`Level::encode`], the level's identity in proofs.
+    pub fn hash(&self) -> [u8; 32] {
+        Sha256::digest(self.encode()).into()
+    }
+
+    /// Parses and validates a single level from JSON.
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> Result<Level, SnakeError> {
+        let level: Level = serde_json::from_str(json).map_err(|_| SnakeError::BadLayout)?;
+        level.validate()?;
+        Ok(level)
+    }
+}
+
+/// Parses a level pack, a JSON object mapping level names to levels, and
+/// validates every level in it.
+#[cfg(feature = "json")]
+pub fn parse_pack(json: &str) -> Result<BTreeMap<String, Level>, SnakeError> {
+    let pack: BTreeMap<String, Level> =
+        serde_json::from_str(json).map_err(|_| SnakeError::BadLayout)?;
+    for level in pack.values() {
+        level.validate()?;
+    }
+    Ok(pack)
+}
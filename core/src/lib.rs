@@ -0,0 +1,63 @@
+//! Snake game rules shared by the WASM frontend and the SP1 verifier.
+//!
+//! The grid, snake, food, collision and scoring logic live here so that the
+//! browser and the zkVM guest run exactly the same rules.
+
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "abi")]
+pub mod abi;
+pub mod achievements;
+mod body;
+pub mod bot;
+pub mod bundle;
+pub mod challenge;
+mod codec;
+pub mod conformance;
+mod direction;
+mod error;
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+pub mod hash;
+pub mod inputs;
+pub mod invariants;
+#[cfg(feature = "keccak")]
+pub mod keccak;
+pub mod leaderboard;
+pub mod level;
+pub mod lockstep;
+pub mod moves;
+mod occupancy;
+mod player;
+#[cfg(feature = "poseidon")]
+pub mod poseidon;
+mod position;
+pub mod registry;
+pub mod replay;
+mod rng;
+pub mod rollback;
+pub mod rules;
+pub mod scheduler;
+mod simd;
+pub mod spectate;
+mod state;
+#[cfg(feature = "json")]
+pub mod vectors;
+pub mod verify;
+pub mod versus;
+
+pub use body::Body;
+pub use direction::Direction;
+pub use error::SnakeError;
+pub use level::{Arena, FoodKind, Level};
+pub use moves::Move;
+pub use player::ReplayPlayer;
+pub use position::Position;
+pub use replay::Replay;
+pub use rng::Rng;
+pub use state::{GameState, StepOutcome};
+pub use versus::VersusState;
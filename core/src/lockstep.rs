@@ -0,0 +1,308 @@
+//! Wire protocol for two-player versus matches played peer to peer.
+//!
+//! Each peer wraps its [`Rollback`] in a [`Session`], feeds it local turns
+//! and the [`Message`]s that arrive from the other peer, and sends whatever
+//! [`Session::take_outbox`] returns. Moving the bytes is up to the embedder;
+//! an unreliable, unordered channel is enough, since every input message
+//! repeats the inputs the peer has not acknowledged and a gap is filled by
+//! asking for a resend. Encoded layout, integers little endian:
+//!
+//! | bytes | field                                                  |
+//! |-------|--------------------------------------------------------|
+//! | 1     | protocol version ([`PROTOCOL_VERSION`])                |
+//! | 1     | kind: 0 inputs, 1 ack, 2 hash check, 3 resync request  |
+//! | 4     | tick                                                   |
+//! | n     | inputs only: turn count, LEB128 varint                 |
+//! | n     | inputs only: one [`Direction::to_byte`] per tick, or   |
+//! |       | `0xff` for no turn                                     |
+//! | 32    | hash check only: confirmed state hash at the tick      |
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::codec::{write_varint, Reader};
+use crate::rollback::Rollback;
+use crate::{Direction, SnakeError};
+
+/// Version byte leading every encoded [`Message`]; bumped whenever the
+/// layout or the session rules change.
+pub const PROTOCOL_VERSION: u8 = 1;
+
+/// Confirmed ticks between two hash checks in [`Session::new`] callers
+/// that have no better figure: about a second at usual tick rates.
+pub const DEFAULT_HASH_INTERVAL: u32 = 10;
+
+const INPUTS: u8 = 0;
+const ACK: u8 = 1;
+const HASH_CHECK: u8 = 2;
+const RESYNC_REQUEST: u8 = 3;
+const NO_TURN: u8 = 0xff;
+
+/// One message between peers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Message {
+    /// The sender's turns for consecutive ticks from `first_tick` on,
+    /// `None` for no turn.
+    Inputs {
+        first_tick: u32,
+        turns: Vec<Option<Direction>>,
+    },
+    /// The sender holds every input of the receiver's before `tick`.
+    Ack { tick: u32 },
+    /// Hash of the sender's confirmed state at `tick`.
+    HashCheck { tick: u32, hash: [u8; 32] },
+    /// The sender is missing the receiver's inputs from `tick` on.
+    ResyncRequest { tick: u32 },
+}
+
+impl Message {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(38);
+        bytes.push(PROTOCOL_VERSION);
+        match self {
+            Message::Inputs { first_tick, turns } => {
+                bytes.push(INPUTS);
+                bytes.extend_from_slice(&first_tick.to_le_bytes());
+                write_varint(&mut bytes, turns.len() as u64);
+                bytes.extend(
+                    turns
+                        .iter()
+                        .map(|turn| turn.map_or(NO_TURN, Direction::to_byte)),
+                );
+            }
+            Message::Ack { tick } => {
+                bytes.push(ACK);
+                bytes.extend_from_slice(&tick.to_le_bytes());
+            }
+            Message::HashCheck { tick, hash } => {
+                bytes.push(HASH_CHECK);
+                bytes.extend_from_slice(&tick.to_le_bytes());
+                bytes.extend_from_slice(hash);
+            }
+            Message::ResyncRequest { tick } => {
+                bytes.push(RESYNC_REQUEST);
+                bytes.extend_from_slice(&tick.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Decodes [`Message::encode`] output. Fails with
+    /// [`SnakeError::BadMessage`] for another protocol version, an unknown
+    /// kind or direction, a truncated buffer or trailing bytes.
+    pub fn decode(bytes: &[u8]) -> Result<Message, SnakeError> {
+        let mut reader = Reader::new(bytes, SnakeError::BadMessage);
+        if reader.u8()? != PROTOCOL_VERSION {
+            return Err(SnakeError::BadMessage);
+        }
+        let kind = reader.u8()?;
+        let tick = reader.u32()?;
+        let message = match kind {
+            INPUTS => {
+                let count = reader.varint()?;
+                let turns = reader
+                    .take(usize::try_from(count).map_err(|_| SnakeError::BadMessage)?)?
+                    .iter()
+                    .map(|&byte| match byte {
+                        NO_TURN => Ok(None),
+                        _ => Direction::from_byte(byte)
+                            .map(Some)
+                            .ok_or(SnakeError::BadMessage),
+                    })
+                    .collect::<Result<Vec<_>, SnakeError>>()?;
+                Message::Inputs {
+                    first_tick: tick,
+                    turns,
+                }
+            }
+            ACK => Message::Ack { tick },
+            HASH_CHECK => {
+                let mut hash = [0; 32];
+                hash.copy_from_slice(reader.take(32)?);
+                Message::HashCheck { tick, hash }
+            }
+            RESYNC_REQUEST => Message::ResyncRequest { tick },
+            _ => return Err(SnakeError::BadMessage),
+        };
+        reader.finish()?;
+        Ok(message)
+    }
+}
+
+/// Where a [`Session`] stands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    /// Inputs are flowing and every hash compared so far agreed.
+    Running,
+    /// The peers' confirmed states differed at `tick`. The match cannot
+    /// continue peer to peer; settle it some other way, e.g. by having a
+    /// server verify both replays.
+    Desynced { tick: u32 },
+}
+
+/// One peer's side of a lockstep match.
+#[derive(Clone, Debug)]
+pub struct Session {
+    rollback: Rollback,
+    status: Status,
+    /// Tick of the first entry in `sent`.
+    first_tick: u32,
+    /// Every local turn played, kept for resend.
+    sent: Vec<Option<Direction>>,
+    /// The peer holds every local input before this tick.
+    acked: u32,
+    /// Every remote input before this tick has arrived.
+    received: u32,
+    /// Confirmed state hashes awaiting the other side's, by tick.
+    ours: BTreeMap<u32, [u8; 32]>,
+    theirs: BTreeMap<u32, [u8; 32]>,
+    outbox: Vec<Message>,
+}
+
+impl Session {
+    /// Starts a session over a fresh `rollback`, comparing confirmed state hashes
+    /// with the peer every `hash_interval` ticks, or never for 0. Both
+    /// peers must use the same interval.
+    pub fn new(rollback: Rollback, hash_interval: u32) -> Session {
+        let tick = rollback.tick();
+        Session {
+            rollback: rollback.with_hash_interval(hash_interval),
+            status: Status::Running,
+            first_tick: tick,
+            sent: Vec::new(),
+            acked: tick,
+            received: tick,
+            ours: BTreeMap::new(),
+            theirs: BTreeMap::new(),
+            outbox: Vec::new(),
+        }
+    }
+
+    pub fn rollback(&self) -> &Rollback {
+        &self.rollback
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Messages to send to the peer, oldest first.
+    pub fn take_outbox(&mut self) -> Vec<Message> {
+        core::mem::take(&mut self.outbox)
+    }
+
+    /// Queues the inputs the peer has not acknowledged again. Inputs ride
+    /// along with every local tick, but a peer that stops playing, say
+    /// once its window is full, must call this on a timer so a lost
+    /// message is not waited on forever.
+    pub fn resend(&mut self) {
+        self.queue_inputs(self.acked);
+    }
+
+    /// Plays the local `turn` as [`Rollback::advance`] does and queues the
+    /// inputs the peer has not acknowledged. Fails with
+    /// [`SnakeError::HashMismatch`] once the session has desynced.
+    pub fn advance(&mut self, turn: Option<Direction>) -> Result<bool, SnakeError> {
+        self.check_running()?;
+        if !self.rollback.advance(turn)? {
+            return Ok(false);
+        }
+        self.sent.push(turn);
+        self.queue_inputs(self.acked);
+        self.collect_hashes();
+        Ok(true)
+    }
+
+    /// Handles a message from the peer, queuing any reply. Inputs already
+    /// held are ignored, so duplicated and reordered messages are harmless.
+    /// Fails with [`SnakeError::HashMismatch`] once the session has
+    /// desynced, and with [`SnakeError::BadInput`] for inputs too far ahead
+    /// or a resend request from before the match.
+    pub fn receive(&mut self, message: &Message) -> Result<(), SnakeError> {
+        self.check_running()?;
+        match *message {
+            Message::Inputs {
+                first_tick,
+                ref turns,
+            } => {
+                if first_tick > self.received {
+                    // Something before these was lost
+                    self.outbox.push(Message::ResyncRequest {
+                        tick: self.received,
+                    });
+                    return Ok(());
+                }
+                let known = (self.received - first_tick) as usize;
+                for &turn in turns.iter().skip(known) {
+                    self.rollback.add_remote_input(self.received, turn)?;
+                    self.received += 1;
+                }
+                self.outbox.push(Message::Ack {
+                    tick: self.received,
+                });
+                self.collect_hashes();
+            }
+            Message::Ack { tick } => {
+                let end = self.first_tick + self.sent.len() as u32;
+                self.acked = self.acked.max(tick.min(end));
+            }
+            Message::HashCheck { tick, hash } => {
+                // Ours for a tick already confirmed was compared and dropped
+                if tick > self.rollback.confirmed_tick() || self.ours.contains_key(&tick) {
+                    self.theirs.insert(tick, hash);
+                }
+                self.compare_hashes();
+            }
+            Message::ResyncRequest { tick } => {
+                if tick < self.first_tick {
+                    return Err(SnakeError::BadInput);
+                }
+                self.acked = self.acked.min(tick);
+                self.queue_inputs(tick);
+            }
+        }
+        Ok(())
+    }
+
+    fn check_running(&self) -> Result<(), SnakeError> {
+        match self.status {
+            Status::Running => Ok(()),
+            Status::Desynced { .. } => Err(SnakeError::HashMismatch),
+        }
+    }
+
+    /// Queues the local inputs from `tick` on, if there are any.
+    fn queue_inputs(&mut self, tick: u32) {
+        let start = (tick - self.first_tick) as usize;
+        if let Some(turns) = self.sent.get(start..).filter(|turns| !turns.is_empty()) {
+            self.outbox.push(Message::Inputs {
+                first_tick: tick,
+                turns: turns.to_vec(),
+            });
+        }
+    }
+
+    /// Sends the hashes of newly confirmed ticks and checks them against
+    /// the peer's.
+    fn collect_hashes(&mut self) {
+        for (tick, hash) in self.rollback.take_confirmed_hashes() {
+            self.outbox.push(Message::HashCheck { tick, hash });
+            self.ours.insert(tick, hash);
+        }
+        self.compare_hashes();
+    }
+
+    fn compare_hashes(&mut self) {
+        let both: Vec<u32> = (self.ours.keys())
+            .filter(|tick| self.theirs.contains_key(tick))
+            .copied()
+            .collect();
+        for tick in both {
+            if self.ours.remove(&tick) != self.theirs.remove(&tick) {
+                self.status = Status::Desynced { tick };
+                return;
+            }
+        }
+    }
+}
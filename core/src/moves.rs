@@ -0,0 +1,86 @@
+//! Move log recorded while playing and consumed by the verifier.
+//!
+//! Each entry is encoded as five bytes: the tick index (`u32`, little
+//! endian) followed by the direction byte.
+//!
+//! [`compress_moves`] is a denser encoding for storage and the prover: each
+//! move is a single varint holding the number of ticks since the previous
+//! move, shifted left by two, with the direction in the low two bits. The
+//! straight runs between turns cost nothing, so a typical turn takes one or
+//! two bytes.
+
+use alloc::vec::Vec;
+
+use crate::codec::{write_varint, Reader};
+use crate::{Direction, SnakeError};
+
+/// Size of one encoded [`Move`].
+pub const MOVE_SIZE: usize = 5;
+
+/// A direction change that takes effect on the step taken at `tick`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Move {
+    pub tick: u32,
+    pub direction: Direction,
+}
+
+pub fn encode_moves(moves: &[Move]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(moves.len() * MOVE_SIZE);
+    for mv in moves {
+        bytes.extend_from_slice(&mv.tick.to_le_bytes());
+        bytes.push(mv.direction.to_byte());
+    }
+    bytes
+}
+
+/// Decodes a buffer produced by [`encode_moves`]. Fails with
+/// [`SnakeError::BadReplay`] if the buffer is truncated or contains an
+/// unknown direction.
+pub fn decode_moves(bytes: &[u8]) -> Result<Vec<Move>, SnakeError> {
+    if !bytes.len().is_multiple_of(MOVE_SIZE) {
+        return Err(SnakeError::BadReplay);
+    }
+    bytes
+        .chunks_exact(MOVE_SIZE)
+        .map(|chunk| {
+            let tick = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+            let direction = Direction::from_byte(chunk[4]).ok_or(SnakeError::BadReplay)?;
+            Ok(Move { tick, direction })
+        })
+        .collect()
+}
+
+pub fn compress_moves(moves: &[Move]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(moves.len() * 2);
+    let mut last_tick = 0u32;
+    for mv in moves {
+        let delta = mv.tick.wrapping_sub(last_tick);
+        write_varint(
+            &mut bytes,
+            u64::from(delta) << 2 | u64::from(mv.direction.to_byte()),
+        );
+        last_tick = mv.tick;
+    }
+    bytes
+}
+
+/// Decodes a buffer produced by [`compress_moves`]. Fails with
+/// [`SnakeError::BadReplay`] if the buffer is truncated or a delta does not
+/// fit in a tick.
+pub fn decompress_moves(bytes: &[u8]) -> Result<Vec<Move>, SnakeError> {
+    let mut reader = Reader::new(bytes, SnakeError::BadReplay);
+    let mut moves = Vec::new();
+    let mut last_tick = 0u32;
+    while !reader.is_empty() {
+        let value = reader.varint()?;
+        let delta = u32::try_from(value >> 2).map_err(|_| SnakeError::BadReplay)?;
+        let direction = Direction::from_byte((value & 0b11) as u8).ok_or(SnakeError::BadReplay)?;
+        last_tick = last_tick.wrapping_add(delta);
+        moves.push(Move {
+            tick: last_tick,
+            direction,
+        });
+    }
+    Ok(moves)
+}
@@ -0,0 +1,119 @@
+//! Bit-per-cell record of the cells a snake, or the food and obstacles,
+//! cover.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::Position;
+
+/// One bit per grid cell, set where a segment lies. Kept in step with the
+/// snake as it moves, so a collision check is one lookup instead of a scan
+/// of the body. A second board kept the same way marks obstacles and food,
+/// so spawning food counts and finds free cells a word at a time.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Occupancy {
+    width: i32,
+    height: i32,
+    words: Vec<u64>,
+}
+
+impl Occupancy {
+    /// A `width` by `height` board with `cells` set. An empty or oversized
+    /// grid yields a board that holds nothing.
+    pub(crate) fn new(
+        width: i32,
+        height: i32,
+        cells: impl IntoIterator<Item = Position>,
+    ) -> Occupancy {
+        let words = match width.checked_mul(height) {
+            Some(cells) if width > 0 && height > 0 => (cells as usize).div_ceil(64),
+            _ => 0,
+        };
+        let mut board = Occupancy {
+            width,
+            height,
+            words: vec![0; words],
+        };
+        for cell in cells {
+            board.insert(cell);
+        }
+        board
+    }
+
+    /// Whether this board was built for a `width` by `height` grid. A game
+    /// restored through serde has an empty board until it is rebuilt.
+    pub(crate) fn fits(&self, width: i32, height: i32) -> bool {
+        self.width == width && self.height == height && !self.words.is_empty()
+    }
+
+    fn bit(&self, pos: Position) -> Option<(usize, u64)> {
+        if pos.x < 0 || pos.x >= self.width || pos.y < 0 || pos.y >= self.height {
+            return None;
+        }
+        let index = pos.y as usize * self.width as usize + pos.x as usize;
+        Some((index / 64, 1 << (index % 64)))
+    }
+
+    pub(crate) fn contains(&self, pos: Position) -> bool {
+        self.bit(pos)
+            .and_then(|(word, mask)| self.words.get(word).map(|bits| bits & mask != 0))
+            .unwrap_or(false)
+    }
+
+    pub(crate) fn insert(&mut self, pos: Position) {
+        if let Some((word, mask)) = self.bit(pos) {
+            if let Some(bits) = self.words.get_mut(word) {
+                *bits |= mask;
+            }
+        }
+    }
+
+    pub(crate) fn remove(&mut self, pos: Position) {
+        if let Some((word, mask)) = self.bit(pos) {
+            if let Some(bits) = self.words.get_mut(word) {
+                *bits &= !mask;
+            }
+        }
+    }
+
+    /// Clears every cell, then sets `cells`, keeping the board's storage.
+    pub(crate) fn reset(&mut self, cells: impl IntoIterator<Item = Position>) {
+        self.words.fill(0);
+        for cell in cells {
+            self.insert(cell);
+        }
+    }
+
+    /// Number of cells in row `y` from `min_x` to `max_x` set on this board
+    /// or on one of `others`, boards for the same grid.
+    pub(crate) fn count_row(&self, others: &[&Occupancy], y: i32, min_x: i32, max_x: i32) -> usize {
+        let (min_x, max_x) = (min_x.max(0), max_x.min(self.width - 1));
+        if y < 0 || y >= self.height || min_x > max_x {
+            return 0;
+        }
+        let row = y as usize * self.width as usize;
+        let (mut index, end) = (row + min_x as usize, row + max_x as usize + 1);
+        let mut count = 0;
+        while index < end {
+            let (word, offset) = (index / 64, index % 64);
+            let span = (64 - offset).min(end - index);
+            let mask = (u64::MAX >> (64 - span)) << offset;
+            let bits = (others.iter()).fold(self.word(word), |bits, other| bits | other.word(word));
+            count += (bits & mask).count_ones() as usize;
+            index += span;
+        }
+        count
+    }
+
+    fn word(&self, index: usize) -> u64 {
+        self.words.get(index).copied().unwrap_or(0)
+    }
+
+    /// Number of cells set.
+    pub(crate) fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|bits| bits.count_ones() as usize)
+            .sum()
+    }
+}
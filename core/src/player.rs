@@ -0,0 +1,97 @@
+use alloc::vec::Vec;
+
+use crate::{GameState, Move, Position, SnakeError, StepOutcome};
+
+/// Plays a recorded move log back on a fresh game.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplayPlayer {
+    initial: GameState,
+    state: GameState,
+    moves: Vec<Move>,
+    next_move: usize,
+    /// Ticks since the last turn or food, see [`crate::rules::is_stalled`].
+    idle_ticks: u32,
+}
+
+impl ReplayPlayer {
+    /// `initial` must be the state the recording started from (same grid and
+    /// seed); `moves` is the log it produced.
+    pub fn new(initial: GameState, moves: Vec<Move>) -> ReplayPlayer {
+        ReplayPlayer {
+            state: initial.clone(),
+            initial,
+            moves,
+            next_move: 0,
+            idle_ticks: 0,
+        }
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    pub fn tick(&self) -> u32 {
+        self.state.ticks()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state.is_game_over()
+    }
+
+    /// Applies the moves recorded for the current tick and advances one step.
+    /// A recorded move the engine refuses means the log does not belong to
+    /// this game, and a game that would run forever once the log is exhausted
+    /// cannot have been recorded; both fail with [`SnakeError::BadReplay`].
+    pub fn step(&mut self) -> Result<StepOutcome, SnakeError> {
+        self.step_with_food(GameState::random_free_cell)
+    }
+
+    /// [`ReplayPlayer::step`] with the food spawns chosen by `next_food`, see
+    /// [`GameState::step_with_food`].
+    pub(crate) fn step_with_food(
+        &mut self,
+        next_food: impl FnMut(&mut GameState) -> Option<Position>,
+    ) -> Result<StepOutcome, SnakeError> {
+        let tick = self.state.ticks();
+        while let Some(mv) = self.moves.get(self.next_move) {
+            if mv.tick != tick {
+                break;
+            }
+            self.state.replay_move(mv.direction)?;
+            self.next_move += 1;
+            self.idle_ticks = 0;
+            if self.state.is_game_over() {
+                return Ok(StepOutcome::GameOver);
+            }
+        }
+
+        let outcome = self.state.step_with_food(next_food)?;
+        self.idle_ticks = match outcome {
+            StepOutcome::AteFood | StepOutcome::LostLife => 0,
+            StepOutcome::Paused | StepOutcome::CountingDown => self.idle_ticks,
+            _ => self.idle_ticks.saturating_add(1),
+        };
+        if !self.state.is_game_over()
+            && self.next_move == self.moves.len()
+            && self.state.is_stalled(self.idle_ticks)
+        {
+            return Err(SnakeError::BadReplay);
+        }
+        Ok(outcome)
+    }
+
+    /// Moves playback to `tick`, rewinding to the start if needed. Stops
+    /// early if the game ends first.
+    pub fn seek(&mut self, tick: u32) -> Result<(), SnakeError> {
+        if tick < self.state.ticks() {
+            self.state = self.initial.clone();
+            self.next_move = 0;
+            self.idle_ticks = 0;
+        }
+        while self.state.ticks() < tick && !self.state.is_game_over() {
+            self.step()?;
+        }
+        Ok(())
+    }
+}
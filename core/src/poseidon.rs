@@ -0,0 +1,267 @@
+//! Poseidon2 over the BabyBear field, exactly as SP1 5 instantiates it.
+//!
+//! With the `poseidon` feature it backs the game state and config hashes,
+//! computed by this module in the browser and in the guest alike, so the
+//! two always agree. The permutation is `sp1_primitives::poseidon2_init`
+//! of `sp1-primitives` 5.2, Plonky3's width-16 BabyBear instance (crates
+//! `p3-baby-bear` and `p3-poseidon2` 0.2.3): S-box `x^7`, 8 full and 13
+//! partial rounds, the external matrix `circ(2 M4, M4, M4, M4)` with
+//! Plonky3's `MDSMat4`, the internal matrix of `DiffusionMatrixBabyBear`
+//! and the round constants of `sp1_primitives::RC_16_30`, rows 0 to 3 and
+//! 17 to 20 for the full rounds and the first word of rows 4 to 16 for the
+//! partial ones. [`permute`] and [`poseidon2`] carry known answers from
+//! Plonky3 and `sp1-primitives`.
+//!
+//! Bytes become field elements, the byte length first, then three bytes
+//! (little endian) per element with the last zero-padded, and those are
+//! hashed as `sp1_primitives::poseidon2_hash` does, with Plonky3's
+//! overwrite-mode `PaddingFreeSponge` of rate 8. The digest is the 8 output
+//! elements, each as a little-endian `u32`.
+//!
+//! SP1 6 proves over KoalaBear, so this is not a hash its recursion circuit
+//! computes, and in the guest it is plain field arithmetic, many times the
+//! cycles of the SHA-256 precompile. It suits verifiers that already carry
+//! SP1 5's Poseidon2.
+
+/// The BabyBear prime, `2^31 - 2^27 + 1`.
+const P: u32 = 0x7800_0001;
+const WIDTH: usize = 16;
+const RATE: usize = 8;
+const BYTES_PER_ELEMENT: usize = 3;
+
+const EXTERNAL_CONSTANTS: [[u32; WIDTH]; 8] = [
+    [
+        0x05c44304, 0x74548efc, 0x0a92dc75, 0x2ae63782, 0x263157ef, 0x52e360ff, 0x664898b9,
+        0x5fc45609, 0x681b1d07, 0x57619f03, 0x594e6152, 0x3189b835, 0x3d585060, 0x756a60ff,
+        0x04f8c794, 0x71f37c2d,
+    ],
+    [
+        0x64f22fef, 0x2d3f4a46, 0x44cebd25, 0x43761839, 0x406b02dc, 0x03319b3b, 0x3542fef1,
+        0x0061d4bc, 0x427ab78b, 0x229bded7, 0x4b5c8423, 0x323e1dc8, 0x20b73dec, 0x5f162cd9,
+        0x5d200eab, 0x04e090d2,
+    ],
+    [
+        0x0fa64335, 0x5d37bc66, 0x55d50d1d, 0x0afe6861, 0x22e445df, 0x533f25f2, 0x5dedfd22,
+        0x4ceccd58, 0x082c5e5c, 0x2d9920e4, 0x25c5205b, 0x01766962, 0x2162d003, 0x70fe8a27,
+        0x2046e05f, 0x6a4c434f,
+    ],
+    [
+        0x257787e3, 0x3cf1c46d, 0x62ea6ca5, 0x0ea49b8f, 0x00ba12cf, 0x0c0451e9, 0x110f53bf,
+        0x03fd7ae9, 0x1e2d9ed5, 0x3f9fbfdd, 0x2ca0cda7, 0x4a29c2b8, 0x241f68b7, 0x45b4c55e,
+        0x045a0296, 0x2150b878,
+    ],
+    [
+        0x07c26dfd, 0x2c4468fc, 0x20576d41, 0x2fda526d, 0x7324b864, 0x01dc2637, 0x65eb202d,
+        0x3723f412, 0x60fc174c, 0x51ecc05f, 0x0a9a4a6b, 0x2534fd81, 0x0c823e36, 0x1074d7fd,
+        0x6c7c8fd1, 0x3a16665c,
+    ],
+    [
+        0x4d179d28, 0x0d348f34, 0x46db60e1, 0x0d56a7bc, 0x0e0cb856, 0x11ac8a1c, 0x009aa89a,
+        0x4b6fb2e4, 0x00876124, 0x2252cbcf, 0x601db98c, 0x720c07a7, 0x4dcd09f0, 0x227cb37d,
+        0x0b36522b, 0x0b655c03,
+    ],
+    [
+        0x5d3bf2f1, 0x08667386, 0x1a9bd874, 0x331a67f8, 0x51f50851, 0x5b29a0e9, 0x123f5612,
+        0x3234aaba, 0x4a53ab2f, 0x3c43230a, 0x7228b4db, 0x489cf324, 0x1bc75463, 0x3a3526aa,
+        0x308a6e3c, 0x00ca16af,
+    ],
+    [
+        0x4574c574, 0x47c355b9, 0x6b605040, 0x726b2f24, 0x23c6f057, 0x2e17264f, 0x1d023a02,
+        0x0d8fa228, 0x54578bcd, 0x1df8bb26, 0x0bde46e4, 0x1e694b67, 0x09ebbf12, 0x55477325,
+        0x520bf85e, 0x69d9f379,
+    ],
+];
+
+const INTERNAL_CONSTANTS: [u32; 13] = [
+    0x47556cf2, 0x0226080a, 0x58eebefe, 0x6f28f3eb, 0x34abd7e2, 0x6940a1de, 0x5492eac7, 0x53b52e36,
+    0x333d6416, 0x5c4796ba, 0x06411997, 0x0aa5e397, 0x608d4abe,
+];
+
+/// `-2, 1, 2, 4, ..., 2^13, 2^15`, each over `2^32`, in the field:
+/// `DiffusionMatrixBabyBear` works on Montgomery forms and folds the
+/// Montgomery factor into its matrix.
+const INTERNAL_DIAGONAL: [u32; WIDTH] = [
+    0x07800001, 0x38400000, 0x70800000, 0x68ffffff, 0x59fffffd, 0x3bfffff9, 0x77fffff2, 0x77ffffe3,
+    0x77ffffc5, 0x77ffff89, 0x77ffff11, 0x77fffe21, 0x77fffc41, 0x77fff881, 0x77fff101, 0x77ffc401,
+];
+
+/// `2^-32` in the field, the factor on the all-ones part of the internal
+/// matrix.
+const MONTGOMERY_INVERSE: u32 = 0x38400000;
+
+/// The digest of `bytes`, `sp1_primitives::poseidon2_hash` of their field
+/// elements as described above. Known answer from `sp1-primitives` 5.2.1:
+///
+/// ```
+/// let digest = snake_core::poseidon::poseidon2(b"snake");
+/// let words: Vec<u32> = (digest.chunks_exact(4))
+///     .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+///     .collect();
+/// assert_eq!(
+///     words,
+///     [
+///         0x4fa736f8, 0x297349e3, 0x70c05e3d, 0x36390229, 0x5fea32fb, 0x4fa95256, 0x3275a5aa,
+///         0x77e42b55,
+///     ]
+/// );
+/// ```
+pub fn poseidon2(bytes: &[u8]) -> [u8; 32] {
+    let mut sponge = Sponge::new(bytes.len());
+    sponge.update(bytes);
+    sponge.finalize()
+}
+
+/// [`poseidon2`] fed piece by piece. The byte length goes in first, so it
+/// has to be known up front.
+pub(crate) struct Sponge {
+    state: [u32; WIDTH],
+    /// Rate lanes written since the last permutation.
+    lanes: usize,
+    /// Bytes of the next element, and how many there are.
+    element: [u8; 4],
+    filled: usize,
+}
+
+impl Sponge {
+    pub(crate) fn new(len: usize) -> Sponge {
+        let mut sponge = Sponge {
+            state: [0; WIDTH],
+            lanes: 0,
+            element: [0; 4],
+            filled: 0,
+        };
+        sponge.absorb((len as u64 % u64::from(P)) as u32);
+        sponge
+    }
+
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.element[self.filled] = byte;
+            self.filled += 1;
+            if self.filled == BYTES_PER_ELEMENT {
+                self.absorb(u32::from_le_bytes(self.element));
+                self.filled = 0;
+            }
+        }
+    }
+
+    pub(crate) fn finalize(mut self) -> [u8; 32] {
+        if self.filled > 0 {
+            self.element[self.filled..].fill(0);
+            self.absorb(u32::from_le_bytes(self.element));
+        }
+        // Overwrite mode: a short last block leaves the lanes after it as
+        // they are
+        if self.lanes > 0 {
+            permute(&mut self.state);
+        }
+
+        let mut digest = [0u8; 32];
+        for (chunk, lane) in digest.chunks_exact_mut(4).zip(self.state) {
+            chunk.copy_from_slice(&lane.to_le_bytes());
+        }
+        digest
+    }
+
+    fn absorb(&mut self, element: u32) {
+        self.state[self.lanes] = element;
+        self.lanes += 1;
+        if self.lanes == RATE {
+            permute(&mut self.state);
+            self.lanes = 0;
+        }
+    }
+}
+
+/// The Poseidon2 permutation on canonical field elements. Known answer
+/// from Plonky3 (`sp1_primitives::poseidon2_init().permute`):
+///
+/// ```
+/// let mut state = core::array::from_fn(|i| i as u32);
+/// snake_core::poseidon::permute(&mut state);
+/// assert_eq!(
+///     state,
+///     [
+///         0x18788a18, 0x44643975, 0x1fe904fa, 0x4f8637ce, 0x755aefe2, 0x33602b31, 0x37d9992f,
+///         0x0f795642, 0x3f16b533, 0x1fdfd792, 0x5bd11e23, 0x07cc93fe, 0x4a89667a, 0x60192caa,
+///         0x5236656a, 0x6251adcb,
+///     ]
+/// );
+/// ```
+pub fn permute(state: &mut [u32; WIDTH]) {
+    external_layer(state);
+    for constants in &EXTERNAL_CONSTANTS[..4] {
+        full_round(state, constants);
+    }
+    for &constant in &INTERNAL_CONSTANTS {
+        state[0] = sbox(add(state[0], constant));
+        internal_layer(state);
+    }
+    for constants in &EXTERNAL_CONSTANTS[4..] {
+        full_round(state, constants);
+    }
+}
+
+fn full_round(state: &mut [u32; WIDTH], constants: &[u32; WIDTH]) {
+    for (lane, &constant) in state.iter_mut().zip(constants) {
+        *lane = sbox(add(*lane, constant));
+    }
+    external_layer(state);
+}
+
+/// `M4` on each group of four lanes, then each lane plus the sum of the
+/// lanes in its position across groups: `circ(2 M4, M4, M4, M4)`.
+fn external_layer(state: &mut [u32; WIDTH]) {
+    for group in state.chunks_exact_mut(4) {
+        let [a, b, c, d] = [group[0], group[1], group[2], group[3]];
+        let m = |row: [u32; 4]| {
+            [a, b, c, d]
+                .iter()
+                .zip(row)
+                .fold(0, |sum, (&x, coefficient)| add(sum, mul(x, coefficient)))
+        };
+        group.copy_from_slice(&[
+            m([2, 3, 1, 1]),
+            m([1, 2, 3, 1]),
+            m([1, 1, 2, 3]),
+            m([3, 1, 1, 2]),
+        ]);
+    }
+    let mut sums = [0u32; 4];
+    for (i, &lane) in state.iter().enumerate() {
+        sums[i % 4] = add(sums[i % 4], lane);
+    }
+    for (i, lane) in state.iter_mut().enumerate() {
+        *lane = add(*lane, sums[i % 4]);
+    }
+}
+
+/// Each lane times its diagonal entry, plus the sum of all lanes over
+/// `2^32`.
+fn internal_layer(state: &mut [u32; WIDTH]) {
+    let sum = state.iter().fold(0, |sum, &lane| add(sum, lane));
+    let sum = mul(sum, MONTGOMERY_INVERSE);
+    for (lane, &entry) in state.iter_mut().zip(&INTERNAL_DIAGONAL) {
+        *lane = add(mul(*lane, entry), sum);
+    }
+}
+
+fn sbox(x: u32) -> u32 {
+    let x2 = mul(x, x);
+    let x4 = mul(x2, x2);
+    mul(mul(x4, x2), x)
+}
+
+fn add(a: u32, b: u32) -> u32 {
+    // Both are below 2^31, so the sum fits
+    let sum = a + b;
+    if sum >= P {
+        sum - P
+    } else {
+        sum
+    }
+}
+
+fn mul(a: u32, b: u32) -> u32 {
+    (u64::from(a) * u64::from(b) % u64::from(P)) as u32
+}
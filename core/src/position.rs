@@ -0,0 +1,27 @@
+use crate::Direction;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Position {
+    pub const fn new(x: i32, y: i32) -> Position {
+        Position { x, y }
+    }
+
+    /// The neighbouring cell in `direction`.
+    pub const fn moved(self, direction: Direction) -> Position {
+        let (dx, dy) = direction.delta();
+        Position::new(self.x.wrapping_add(dx), self.y.wrapping_add(dy))
+    }
+}
+
+/// The `(x, y)` cells of the verifier's inputs.
+impl From<(u32, u32)> for Position {
+    fn from((x, y): (u32, u32)) -> Position {
+        Position::new(x as i32, y as i32)
+    }
+}
@@ -0,0 +1,149 @@
+//! The game programs a verifier accepts.
+//!
+//! Any change to the guest or to the rules it enforces gives the program a
+//! new verification key, and a proof made by a modified program verifies
+//! against that program's key just as well. A leaderboard therefore pins
+//! the releases it accepts in a [`VkeyRegistry`], mapping each semantic
+//! program version to the `bytes32` hash of its key, and rejects every
+//! [`ProofBundle`] whose key is not registered. The manifest is a JSON
+//! object like a level pack:
+//!
+//! ```json
+//! { "1.0.0": "0x00a1...", "1.1.0": "0x00b2..." }
+//! ```
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::bundle::ProofBundle;
+use crate::SnakeError;
+
+/// A `MAJOR.MINOR.PATCH` program release.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProgramVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl ProgramVersion {
+    /// Reads `MAJOR.MINOR.PATCH`. Fails with [`SnakeError::BadManifest`] on
+    /// anything else, including pre-release and build suffixes.
+    pub fn parse(text: &str) -> Result<ProgramVersion, SnakeError> {
+        let mut parts = text.split('.').map(|part| match part.as_bytes() {
+            [] => Err(SnakeError::BadManifest),
+            [b'0', _, ..] => Err(SnakeError::BadManifest),
+            digits if digits.iter().all(u8::is_ascii_digit) => {
+                part.parse().map_err(|_| SnakeError::BadManifest)
+            }
+            _ => Err(SnakeError::BadManifest),
+        });
+        let mut next = || parts.next().unwrap_or(Err(SnakeError::BadManifest));
+        let version = ProgramVersion {
+            major: next()?,
+            minor: next()?,
+            patch: next()?,
+        };
+        match parts.next() {
+            None => Ok(version),
+            Some(_) => Err(SnakeError::BadManifest),
+        }
+    }
+}
+
+impl fmt::Display for ProgramVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Registered program versions and their vkey hashes, one hash per version
+/// and one version per hash.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VkeyRegistry {
+    /// Sorted by version.
+    programs: Vec<(ProgramVersion, [u8; 32])>,
+}
+
+impl VkeyRegistry {
+    pub fn new() -> VkeyRegistry {
+        VkeyRegistry::default()
+    }
+
+    /// Parses a manifest mapping `MAJOR.MINOR.PATCH` versions to `bytes32`
+    /// hex vkey hashes. Fails with [`SnakeError::BadManifest`] if it is
+    /// malformed or [`VkeyRegistry::register`] rejects an entry.
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> Result<VkeyRegistry, SnakeError> {
+        let manifest: alloc::collections::BTreeMap<alloc::string::String, alloc::string::String> =
+            serde_json::from_str(json).map_err(|_| SnakeError::BadManifest)?;
+        let mut registry = VkeyRegistry::new();
+        for (version, hash) in &manifest {
+            let hash = parse_hash(hash).ok_or(SnakeError::BadManifest)?;
+            registry.register(ProgramVersion::parse(version)?, hash)?;
+        }
+        Ok(registry)
+    }
+
+    /// Accepts proofs of the program with `vkey_hash` as release `version`.
+    /// Fails with [`SnakeError::BadManifest`] if either is already
+    /// registered.
+    pub fn register(
+        &mut self,
+        version: ProgramVersion,
+        vkey_hash: [u8; 32],
+    ) -> Result<(), SnakeError> {
+        if self.version_of(&vkey_hash).is_some() {
+            return Err(SnakeError::BadManifest);
+        }
+        match (self.programs).binary_search_by_key(&version, |&(version, _)| version) {
+            Ok(_) => Err(SnakeError::BadManifest),
+            Err(index) => {
+                self.programs.insert(index, (version, vkey_hash));
+                Ok(())
+            }
+        }
+    }
+
+    /// The release registered with `vkey_hash`, if any.
+    pub fn version_of(&self, vkey_hash: &[u8; 32]) -> Option<ProgramVersion> {
+        (self.programs.iter())
+            .find(|(_, hash)| hash == vkey_hash)
+            .map(|&(version, _)| version)
+    }
+
+    /// The vkey hash of release `version`, if registered.
+    pub fn vkey_hash(&self, version: ProgramVersion) -> Option<[u8; 32]> {
+        (self.programs.iter())
+            .find(|&&(registered, _)| registered == version)
+            .map(|&(_, hash)| hash)
+    }
+
+    /// The newest registered release and its vkey hash.
+    pub fn latest(&self) -> Option<(ProgramVersion, [u8; 32])> {
+        self.programs.last().copied()
+    }
+
+    /// The release that made `bundle`. Fails with
+    /// [`SnakeError::UnregisteredProgram`] if its vkey is not registered.
+    /// Only the key is checked, not the proof.
+    pub fn check(&self, bundle: &ProofBundle) -> Result<ProgramVersion, SnakeError> {
+        self.version_of(&bundle.vkey_hash)
+            .ok_or(SnakeError::UnregisteredProgram)
+    }
+}
+
+/// Reads `0x`-prefixed or bare hex of 32 bytes.
+#[cfg(feature = "json")]
+fn parse_hash(hex: &str) -> Option<[u8; 32]> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if hex.len() != 64 {
+        return None;
+    }
+    let mut bytes = [0; 32];
+    let mut digits = hex.chars().map(|digit| digit.to_digit(16));
+    for byte in &mut bytes {
+        *byte = (digits.next()?? * 16 + digits.next()??) as u8;
+    }
+    Some(bytes)
+}
@@ -0,0 +1,157 @@
+//! `.skr` replay files.
+//!
+//! A replay is everything needed to play a game back: the level, the seed
+//! and the move log, plus the final score so a corrupted or mismatched log is
+//! caught before it reaches the prover. Layout, integers little endian:
+//!
+//! | bytes | field                                      |
+//! |-------|--------------------------------------------|
+//! | 4     | magic `SKR\x1a`                            |
+//! | 1     | format version ([`FORMAT_VERSION`])        |
+//! | 2     | engine version ([`rules::ENGINE_VERSION`]) |
+//! | n     | level, see [`Level::encode`]               |
+//! | 8     | seed                                       |
+//! | 8     | final score                                |
+//! | 4     | length of the move stream in bytes         |
+//! | n     | moves, see [`moves::compress_moves`]       |
+//! | 4     | CRC-32 of all preceding bytes              |
+//!
+//! [`Replay::to_share_string`] wraps the same bytes in base64url text for
+//! pasting into a chat message or URL fragment.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::codec::{base64url_decode, base64url_encode, crc32, Reader};
+use crate::{moves, rules, GameState, Level, Move, ReplayPlayer, SnakeError};
+
+/// Leading bytes of every `.skr` file.
+pub const MAGIC: [u8; 4] = *b"SKR\x1a";
+
+/// Version of the container layout described above.
+pub const FORMAT_VERSION: u8 = 16;
+
+/// Leading text of every [`Replay::to_share_string`].
+pub const SHARE_PREFIX: &str = "skr.";
+
+/// A decoded `.skr` file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Replay {
+    /// Engine that recorded the game. Replays from another engine version
+    /// are not guaranteed to play out the same.
+    pub engine_version: u16,
+    pub level: Level,
+    pub seed: u64,
+    pub score: u64,
+    pub moves: Vec<Move>,
+}
+
+impl Replay {
+    /// Captures a game from its start, normally once it is over.
+    pub fn from_game(state: &GameState) -> Replay {
+        Replay {
+            engine_version: rules::ENGINE_VERSION,
+            level: state.level().clone(),
+            seed: state.seed(),
+            score: state.score(),
+            moves: state.moves().to_vec(),
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let stream = moves::compress_moves(&self.moves);
+        let mut bytes = Vec::with_capacity(62 + self.level.obstacles.len() * 8 + stream.len());
+        bytes.extend_from_slice(&MAGIC);
+        bytes.push(FORMAT_VERSION);
+        bytes.extend_from_slice(&self.engine_version.to_le_bytes());
+        self.level.write(&mut bytes);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&self.score.to_le_bytes());
+        bytes.extend_from_slice(&(stream.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&stream);
+        let checksum = crc32(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    /// Parses a `.skr` file. Fails with [`SnakeError::BadReplay`] on a wrong
+    /// magic or format version, a malformed level, a checksum mismatch or a
+    /// truncated buffer.
+    pub fn decode(bytes: &[u8]) -> Result<Replay, SnakeError> {
+        let (body, checksum) = bytes.split_last_chunk::<4>().ok_or(SnakeError::BadReplay)?;
+        if crc32(body) != u32::from_le_bytes(*checksum) {
+            return Err(SnakeError::BadReplay);
+        }
+
+        let mut reader = Reader::new(body, SnakeError::BadReplay);
+        if reader.take(4)? != MAGIC || reader.u8()? != FORMAT_VERSION {
+            return Err(SnakeError::BadReplay);
+        }
+        let engine_version = u16::from_le_bytes([reader.u8()?, reader.u8()?]);
+        let level = Level::read(&mut reader)?;
+        let seed = reader.u64()?;
+        let score = reader.u64()?;
+        let stream_len = reader.u32()? as usize;
+        let moves = moves::decompress_moves(reader.take(stream_len)?)?;
+        reader.finish()?;
+
+        Ok(Replay {
+            engine_version,
+            level,
+            seed,
+            score,
+            moves,
+        })
+    }
+
+    /// The `.skr` bytes as [`SHARE_PREFIX`] followed by unpadded base64url,
+    /// text that survives chat apps and URLs. The moves are compressed and
+    /// the CRC-32 is kept, so a mangled paste is rejected on decoding.
+    pub fn to_share_string(&self) -> String {
+        let mut text = String::from(SHARE_PREFIX);
+        text.push_str(&base64url_encode(&self.encode()));
+        text
+    }
+
+    /// Decodes a [`Replay::to_share_string`], ignoring surrounding
+    /// whitespace. Fails with [`SnakeError::BadReplay`] like
+    /// [`Replay::decode`], or if the text is not a share string.
+    pub fn from_share_string(text: &str) -> Result<Replay, SnakeError> {
+        let encoded = (text.trim())
+            .strip_prefix(SHARE_PREFIX)
+            .ok_or(SnakeError::BadReplay)?;
+        Replay::decode(&base64url_decode(encoded).ok_or(SnakeError::BadReplay)?)
+    }
+
+    /// The replay as a JSON object with the struct's fields, the level in
+    /// [`Level::from_json`] form and moves as `{ "tick", "direction" }`.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> alloc::string::String {
+        serde_json::to_string(self).expect("replays serialize")
+    }
+
+    /// The state the recording started from. Fails with
+    /// [`SnakeError::BadLayout`] if the level is not playable.
+    pub fn initial_state(&self) -> Result<GameState, SnakeError> {
+        GameState::from_level(self.level.clone(), self.seed)
+    }
+
+    /// Plays the replay to the end on this engine and returns the final
+    /// state. Fails with [`SnakeError::BadReplay`] if it was recorded by
+    /// another engine version or the moves are not legal, and with
+    /// [`SnakeError::ScoreMismatch`] if it does not reach the recorded score.
+    pub fn play(&self) -> Result<GameState, SnakeError> {
+        if self.engine_version != rules::ENGINE_VERSION {
+            return Err(SnakeError::BadReplay);
+        }
+        let mut player = ReplayPlayer::new(self.initial_state()?, self.moves.clone());
+        while !player.is_finished() {
+            player.step()?;
+        }
+        if player.state().score() != self.score {
+            return Err(SnakeError::ScoreMismatch);
+        }
+        Ok(player.state().clone())
+    }
+}
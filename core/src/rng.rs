@@ -0,0 +1,109 @@
+//! Deterministic pseudo-random number generator.
+//!
+//! Food placement must be reproducible from the game seed so that a replay
+//! (and the SP1 prover) sees exactly the same board the player did. The
+//! generator is specified exactly, so the guest regenerates every spawn
+//! from the committed seed and ports of the engine can match it:
+//!
+//! - each stream is ChaCha8 (ChaCha with 8 rounds, the original layout: 4
+//!   constant words, 8 key words, a 64-bit block counter, a 64-bit nonce
+//!   of zero) under the key `sha256(domain || seed)`, the seed little
+//!   endian, read as 8 little-endian words
+//! - output `n` of a stream is words `2(n mod 8)` (low half) and
+//!   `2(n mod 8) + 1` (high half) of block `n / 8`
+//! - [`Rng::next_below`] reduces an output modulo its bound
+//!
+//! This is the stream of `ChaCha8Rng::from_seed(key)` in the `rand_chacha`
+//! and `chacha20` crates.
+//!
+//! Placement and food kinds are two streams with their own domains, so a
+//! kind never depends on where food fell.
+
+use sha2::{Digest, Sha256};
+
+const ROUNDS: usize = 8;
+/// `"expand 32-byte k"`.
+const CONSTANTS: [u32; 4] = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574];
+
+/// A ChaCha8 stream.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rng {
+    key: [u32; 8],
+    /// Outputs drawn so far.
+    position: u64,
+}
+
+impl Rng {
+    /// The stream of `seed` in `domain`.
+    pub fn new(seed: u64, domain: &[u8]) -> Rng {
+        Rng::resume(seed, domain, 0)
+    }
+
+    /// The stream of `seed` in `domain` after `position` outputs.
+    pub fn resume(seed: u64, domain: &[u8], position: u64) -> Rng {
+        let digest = Sha256::new()
+            .chain_update(domain)
+            .chain_update(seed.to_le_bytes())
+            .finalize();
+        let mut key = [0; 8];
+        for (word, chunk) in key.iter_mut().zip(digest.chunks_exact(4)) {
+            *word = u32::from_le_bytes(chunk.try_into().expect("4 bytes"));
+        }
+        Rng { key, position }
+    }
+
+    /// Outputs drawn so far. [`Rng::resume`] with it continues the same
+    /// sequence.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let block = self.block(self.position / 8);
+        let word = (self.position % 8) as usize * 2;
+        self.position = self.position.wrapping_add(1);
+        u64::from(block[word]) | u64::from(block[word + 1]) << 32
+    }
+
+    /// Returns a value in `0..bound`, or zero for a zero `bound`.
+    pub fn next_below(&mut self, bound: u32) -> u32 {
+        self.next_u64().checked_rem(u64::from(bound)).unwrap_or(0) as u32
+    }
+
+    /// Keystream block `counter`. Food is drawn a handful of times a game,
+    /// so blocks are recomputed rather than cached.
+    fn block(&self, counter: u64) -> [u32; 16] {
+        let mut input = [0; 16];
+        input[..4].copy_from_slice(&CONSTANTS);
+        input[4..12].copy_from_slice(&self.key);
+        input[12] = counter as u32;
+        input[13] = (counter >> 32) as u32;
+        let mut state = input;
+        for _ in 0..ROUNDS / 2 {
+            quarter_round(&mut state, 0, 4, 8, 12);
+            quarter_round(&mut state, 1, 5, 9, 13);
+            quarter_round(&mut state, 2, 6, 10, 14);
+            quarter_round(&mut state, 3, 7, 11, 15);
+            quarter_round(&mut state, 0, 5, 10, 15);
+            quarter_round(&mut state, 1, 6, 11, 12);
+            quarter_round(&mut state, 2, 7, 8, 13);
+            quarter_round(&mut state, 3, 4, 9, 14);
+        }
+        for (word, input) in state.iter_mut().zip(input) {
+            *word = word.wrapping_add(input);
+        }
+        state
+    }
+}
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
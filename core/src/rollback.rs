@@ -0,0 +1,202 @@
+//! Rollback netcode for peer-to-peer versus matches.
+//!
+//! Each peer runs the match locally and exchanges only per-tick inputs. A
+//! tick whose remote input has not arrived yet is played with a prediction,
+//! no turn, so the local snake responds at once. When the real input
+//! arrives and differs, [`Rollback`] restores the last state every input
+//! is known for and re-simulates up to the present. The versus engine is
+//! deterministic, so both peers end on the same state once every input has
+//! been exchanged.
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use crate::versus::{VersusState, PLAYERS};
+use crate::{Direction, SnakeError};
+
+/// A prediction window for [`Rollback::new`] that hides about a hundred
+/// milliseconds of latency at usual tick rates.
+pub const DEFAULT_MAX_PREDICTION: u32 = 8;
+
+/// One player's input on one tick: `Some(turn)` once known, where `turn`
+/// is `None` for no turn.
+type Input = Option<Option<Direction>>;
+
+/// A versus match played with predicted remote inputs.
+#[derive(Clone, Debug)]
+pub struct Rollback {
+    local: usize,
+    max_prediction: u32,
+    /// The match up to the last tick every input is known for.
+    confirmed: VersusState,
+    /// Tick `confirmed` is at. It keeps counting after the match ends,
+    /// unlike [`VersusState::ticks`].
+    confirmed_tick: u32,
+    /// `confirmed` played on through `pending` with predictions filled in.
+    predicted: VersusState,
+    /// Inputs for the ticks from `confirmed_tick` on, by player.
+    pending: VecDeque<[Input; PLAYERS]>,
+    rollbacks: u32,
+    /// Confirmed ticks whose state hash is recorded, every this many; 0
+    /// records none.
+    hash_interval: u32,
+    /// Recorded `(tick, state hash)` pairs not yet taken.
+    hashes: Vec<(u32, [u8; 32])>,
+}
+
+impl Rollback {
+    /// Starts from `state`, with this peer playing `local`. Fails with
+    /// [`SnakeError::UnknownPlayer`] for a bad index.
+    pub fn new(
+        state: VersusState,
+        local: usize,
+        max_prediction: u32,
+    ) -> Result<Rollback, SnakeError> {
+        if local >= PLAYERS {
+            return Err(SnakeError::UnknownPlayer);
+        }
+        Ok(Rollback {
+            local,
+            max_prediction: max_prediction.max(1),
+            confirmed_tick: state.ticks(),
+            predicted: state.clone(),
+            confirmed: state,
+            pending: VecDeque::new(),
+            rollbacks: 0,
+            hash_interval: 0,
+            hashes: Vec::new(),
+        })
+    }
+
+    /// Records the confirmed state hash on every tick that is a multiple
+    /// of `interval`, for [`Rollback::take_confirmed_hashes`].
+    pub fn with_hash_interval(mut self, interval: u32) -> Rollback {
+        self.hash_interval = interval;
+        self
+    }
+
+    /// The `(tick, state hash)` pairs recorded since the last call, in tick
+    /// order. Peers that agree on inputs agree on these; comparing them
+    /// catches a desync.
+    pub fn take_confirmed_hashes(&mut self) -> Vec<(u32, [u8; 32])> {
+        core::mem::take(&mut self.hashes)
+    }
+
+    /// The match as this peer should draw it, predictions included.
+    pub fn state(&self) -> &VersusState {
+        &self.predicted
+    }
+
+    /// The match up to the last tick both inputs are known for.
+    pub fn confirmed(&self) -> &VersusState {
+        &self.confirmed
+    }
+
+    pub fn confirmed_tick(&self) -> u32 {
+        self.confirmed_tick
+    }
+
+    /// Tick the next local input is for.
+    pub fn tick(&self) -> u32 {
+        self.confirmed_tick + self.local_inputs() as u32
+    }
+
+    /// Number of times a misprediction forced a re-simulation.
+    pub fn rollbacks(&self) -> u32 {
+        self.rollbacks
+    }
+
+    /// Plays the local `turn` for [`Rollback::tick`], predicting the remote
+    /// input if it has not arrived, and returns `true`. Returns `false`
+    /// without playing when the local side is already the maximum number
+    /// of ticks ahead of the remote one; send the input later.
+    pub fn advance(&mut self, turn: Option<Direction>) -> Result<bool, SnakeError> {
+        let index = self.local_inputs();
+        if index as u32 >= self.max_prediction {
+            return Ok(false);
+        }
+        if index == self.pending.len() {
+            self.pending.push_back([None; PLAYERS]);
+        }
+        self.pending[index][self.local] = Some(turn);
+        play(&mut self.predicted, &self.pending[index])?;
+        self.confirm()?;
+        Ok(true)
+    }
+
+    /// Records the remote player's `turn` for `tick`, re-simulating if it
+    /// differs from what was predicted. Fails with
+    /// [`SnakeError::BadInput`] if the input for `tick` is already known,
+    /// or `tick` is further ahead than a peer with the same window could
+    /// have played.
+    pub fn add_remote_input(
+        &mut self,
+        tick: u32,
+        turn: Option<Direction>,
+    ) -> Result<(), SnakeError> {
+        let index = tick
+            .checked_sub(self.confirmed_tick)
+            .filter(|&index| index < self.max_prediction.saturating_mul(2))
+            .ok_or(SnakeError::BadInput)? as usize;
+        while self.pending.len() <= index {
+            self.pending.push_back([None; PLAYERS]);
+        }
+        let remote = 1 - self.local;
+        if self.pending[index][remote].is_some() {
+            return Err(SnakeError::BadInput);
+        }
+        self.pending[index][remote] = Some(turn);
+        // A tick already played assumed no turn
+        let mispredicted = index < self.local_inputs() && turn.is_some();
+        self.confirm()?;
+        if mispredicted {
+            self.rollbacks = self.rollbacks.saturating_add(1);
+            self.predicted = self.confirmed.clone();
+            for inputs in self.pending.iter().take(self.local_inputs()) {
+                play(&mut self.predicted, inputs)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of pending ticks the local input is known for.
+    fn local_inputs(&self) -> usize {
+        self.pending
+            .iter()
+            .take_while(|inputs| inputs[self.local].is_some())
+            .count()
+    }
+
+    /// Moves `confirmed` past every leading tick both inputs are known for.
+    fn confirm(&mut self) -> Result<(), SnakeError> {
+        while let Some(inputs) = self.pending.front() {
+            if inputs.iter().any(Option::is_none) {
+                break;
+            }
+            play(&mut self.confirmed, inputs)?;
+            self.pending.pop_front();
+            self.confirmed_tick = self.confirmed_tick.saturating_add(1);
+            if self.hash_interval > 0 && self.confirmed_tick.is_multiple_of(self.hash_interval) {
+                self.hashes
+                    .push((self.confirmed_tick, self.confirmed.state_hash()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Plays one tick of `inputs`, unknown ones as no turn. A turn the engine
+/// refuses is dropped, as it would be live; a finished match stays as it
+/// is.
+fn play(state: &mut VersusState, inputs: &[Input; PLAYERS]) -> Result<(), SnakeError> {
+    if state.is_game_over() {
+        return Ok(());
+    }
+    for (player, input) in inputs.iter().enumerate() {
+        if let Some(Some(direction)) = *input {
+            let _ = state.change_direction(player, direction);
+        }
+    }
+    state.step()?;
+    Ok(())
+}
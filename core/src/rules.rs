@@ -0,0 +1,237 @@
+//! Scoring, speed and input rules, and engine-wide constants.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::SnakeError;
+
+/// Version of the game rules. Bump it whenever a change makes the same seed
+/// and moves play out differently, so older replays are rejected.
+pub const ENGINE_VERSION: u16 = 2;
+
+/// Most turns [`crate::GameState::queue_direction`] buffers ahead of the
+/// ticks that apply them.
+pub const INPUT_QUEUE_CAPACITY: usize = 2;
+
+/// Length of the snake when a game starts.
+pub const INITIAL_LENGTH: u32 = 3;
+
+/// What earns points.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScoringMode {
+    /// Food scores, plus any survival points.
+    #[default]
+    Food,
+    /// Only survival points score; food still grows or shrinks the snake.
+    Survival,
+}
+
+impl ScoringMode {
+    /// Stable one-byte encoding used in level encodings.
+    pub const fn to_byte(self) -> u8 {
+        match self {
+            ScoringMode::Food => 0,
+            ScoringMode::Survival => 1,
+        }
+    }
+
+    pub const fn from_byte(byte: u8) -> Option<ScoringMode> {
+        match byte {
+            0 => Some(ScoringMode::Food),
+            1 => Some(ScoringMode::Survival),
+            _ => None,
+        }
+    }
+}
+
+/// How a level scores and grows the snake. The same values drive the
+/// browser and the prover, so a rule change is made here or in the level
+/// definition and nowhere else.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ScoringRules {
+    /// Whether food or ticks survived earn points.
+    pub mode: ScoringMode,
+    /// Points for each normal food item eaten.
+    pub points_per_food: u32,
+    /// Points for each golden food item eaten.
+    pub points_per_golden_food: u32,
+    /// Segments added for each normal or golden food eaten. They are added
+    /// one per tick, starting on the tick the food is eaten.
+    pub growth_per_food: u32,
+    /// Points for each tick the snake survives, the only points in
+    /// [`ScoringMode::Survival`].
+    pub survival_points_per_tick: u32,
+    /// Food eaten within this many ticks of the previous one raises the
+    /// combo multiplier by one; a longer gap, poison or a lost life resets
+    /// it. Zero turns combos off.
+    pub combo_window_ticks: u32,
+    /// Highest combo multiplier.
+    pub max_combo_multiplier: u32,
+}
+
+/// From `min_score` on, ticks fire every `interval_ms`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpeedStep {
+    pub min_score: u32,
+    pub interval_ms: u32,
+}
+
+/// How fast a level runs: a tick interval that shrinks as the score
+/// crosses each step's threshold. The engine itself counts ticks, not
+/// time, so the schedule only paces the frontend loop; it is part of the
+/// level, and so of the config hash, to tell easy runs from hard ones.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Difficulty {
+    /// Interval until the first step is reached.
+    pub base_interval_ms: u32,
+    /// Steps by strictly increasing `min_score`.
+    pub steps: Vec<SpeedStep>,
+}
+
+impl Difficulty {
+    /// A slow, constant pace.
+    pub fn easy() -> Difficulty {
+        Difficulty {
+            base_interval_ms: 200,
+            steps: Vec::new(),
+        }
+    }
+
+    /// The classic constant pace.
+    pub fn normal() -> Difficulty {
+        Difficulty {
+            base_interval_ms: 150,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Starts quicker and speeds up as the score grows.
+    pub fn hard() -> Difficulty {
+        let step = |min_score, interval_ms| SpeedStep {
+            min_score,
+            interval_ms,
+        };
+        Difficulty {
+            base_interval_ms: 120,
+            steps: vec![step(50, 100), step(150, 80), step(300, 60)],
+        }
+    }
+
+    /// Tick interval at `score`.
+    pub fn interval_ms(&self, score: u64) -> u32 {
+        self.steps
+            .iter()
+            .rev()
+            .find(|step| score >= u64::from(step.min_score))
+            .map_or(self.base_interval_ms, |step| step.interval_ms)
+    }
+
+    /// Number of steps reached at `score`, 0 at the base interval.
+    pub fn speed_level(&self, score: u64) -> usize {
+        self.steps
+            .iter()
+            .take_while(|step| score >= u64::from(step.min_score))
+            .count()
+    }
+
+    /// Whether every interval is non-zero and the thresholds increase.
+    pub fn is_valid(&self) -> bool {
+        self.base_interval_ms > 0
+            && self.steps.iter().all(|step| step.interval_ms > 0)
+            && self
+                .steps
+                .windows(2)
+                .all(|pair| pair[0].min_score < pair[1].min_score)
+    }
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Difficulty::normal()
+    }
+}
+
+/// What happens to a turn the engine cannot take: straight back into the
+/// neck, or into the direction the snake already has.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IllegalMovePolicy {
+    /// The input is dropped and play goes on, as in the arcade game.
+    #[default]
+    Ignore,
+    /// The input is recorded and ends the game on the spot.
+    EndGame,
+}
+
+impl IllegalMovePolicy {
+    /// Stable one-byte encoding used in level encodings.
+    pub const fn to_byte(self) -> u8 {
+        match self {
+            IllegalMovePolicy::Ignore => 0,
+            IllegalMovePolicy::EndGame => 1,
+        }
+    }
+
+    pub const fn from_byte(byte: u8) -> Option<IllegalMovePolicy> {
+        match byte {
+            0 => Some(IllegalMovePolicy::Ignore),
+            1 => Some(IllegalMovePolicy::EndGame),
+            _ => None,
+        }
+    }
+}
+
+impl Default for ScoringRules {
+    fn default() -> Self {
+        ScoringRules {
+            mode: ScoringMode::Food,
+            points_per_food: 10,
+            points_per_golden_food: 50,
+            growth_per_food: 1,
+            survival_points_per_tick: 0,
+            combo_window_ticks: 0,
+            max_combo_multiplier: 5,
+        }
+    }
+}
+
+/// Whether a game with no moves left that has gone `idle_ticks` without
+/// turning or eating can never end. Going straight, the head laps its row or
+/// column within `max(grid_width, grid_height)` ticks; a classic snake hits
+/// a wall by then, and a wrap-around one that has not eaten or crashed
+/// would circle forever.
+pub fn is_stalled(idle_ticks: u32, grid_width: i32, grid_height: i32) -> bool {
+    idle_ticks > grid_width.max(grid_height).max(0) as u32
+}
+
+/// Snake length implied by `score` when only normal food scores, or `None`
+/// if normal food is worth nothing.
+pub fn expected_length(rules: &ScoringRules, score: u64) -> Option<u64> {
+    let foods = score.checked_div(u64::from(rules.points_per_food))?;
+    let growth = foods.saturating_mul(u64::from(rules.growth_per_food));
+    Some(u64::from(INITIAL_LENGTH).saturating_add(growth))
+}
+
+/// Checks that `score` and `snake_length` are exactly consistent with
+/// `rules`, assuming only normal food scores. `snake_length` includes any
+/// growth still pending.
+pub fn verify_score(rules: &ScoringRules, score: u64, snake_length: u32) -> Result<(), SnakeError> {
+    let expected_length = expected_length(rules, score).ok_or(SnakeError::ScoreMismatch)?;
+
+    let length_valid = expected_length == u64::from(snake_length);
+
+    // Score must be a multiple of the food value
+    let score_valid = score.is_multiple_of(u64::from(rules.points_per_food));
+
+    if length_valid && score_valid {
+        Ok(())
+    } else {
+        Err(SnakeError::ScoreMismatch)
+    }
+}
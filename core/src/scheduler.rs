@@ -0,0 +1,66 @@
+//! Frame-rate-independent pacing for frontends.
+//!
+//! The engine counts ticks, not time. A frontend drawing at whatever rate
+//! the display refreshes feeds each frame's timestamp to a
+//! [`TickScheduler`] and runs the number of steps it returns, so the snake
+//! moves at the level's speed on a 60 Hz and a 144 Hz screen alike.
+
+/// Most steps [`TickScheduler::advance`] returns at once. A frame arriving
+/// long after the last one, say after the tab was hidden, would otherwise
+/// play out seconds of game in a single frame.
+pub const MAX_CATCH_UP_STEPS: u32 = 5;
+
+/// Accumulates elapsed wall-clock time and hands it out in whole ticks.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TickScheduler {
+    /// Timestamp of the previous frame, `None` before the first.
+    last_ms: Option<f64>,
+    /// Time elapsed but not yet spent on a tick.
+    accumulated_ms: f64,
+}
+
+impl TickScheduler {
+    pub fn new() -> TickScheduler {
+        TickScheduler::default()
+    }
+
+    /// Takes the timestamp of a frame in milliseconds, e.g. from
+    /// `performance.now()`, and returns how many steps are due at one per
+    /// `interval_ms`, at most [`MAX_CATCH_UP_STEPS`]. The first frame only
+    /// starts the clock. A clock that runs backwards and a zero interval
+    /// yield no steps.
+    pub fn advance(&mut self, now_ms: f64, interval_ms: u32) -> u32 {
+        let elapsed = self.last_ms.map_or(0.0, |last| now_ms - last);
+        self.last_ms = Some(now_ms);
+        if interval_ms == 0 || elapsed.is_nan() || elapsed <= 0.0 {
+            return 0;
+        }
+        let interval = f64::from(interval_ms);
+        self.accumulated_ms += elapsed;
+        let mut steps = 0;
+        while self.accumulated_ms >= interval && steps < MAX_CATCH_UP_STEPS {
+            self.accumulated_ms -= interval;
+            steps += 1;
+        }
+        // Time beyond the catch-up budget is dropped, not owed
+        if steps == MAX_CATCH_UP_STEPS {
+            self.accumulated_ms %= interval;
+        }
+        steps
+    }
+
+    /// How far the game is between its last tick and the next, in `0..1`,
+    /// for renderers that interpolate movement.
+    pub fn progress(&self, interval_ms: u32) -> f64 {
+        match interval_ms {
+            0 => 0.0,
+            interval => (self.accumulated_ms / f64::from(interval)).clamp(0.0, 1.0),
+        }
+    }
+
+    /// Forgets the clock, so time spent paused is not made up afterwards.
+    /// The next [`TickScheduler::advance`] starts it again.
+    pub fn reset(&mut self) {
+        *self = TickScheduler::default();
+    }
+}
@@ -0,0 +1,90 @@
+//! Vectorised cell lookups over snake bodies.
+//!
+//! With the `simd` feature, [`contains`] compares four packed cells at a
+//! time: SSE2 on x86_64, NEON on aarch64, and simd128 on wasm32 builds
+//! compiled with `-C target-feature=+simd128`. Every other build, the zkVM
+//! guest included, runs the scalar loop, which gives the same answers.
+
+use alloc::collections::VecDeque;
+
+use crate::body::Cell;
+
+/// Cells compared per vector.
+const LANES: usize = 4;
+
+/// Whether `target` is one of `cells`.
+pub(crate) fn contains(cells: &[Cell], target: Cell) -> bool {
+    let chunks = cells.chunks_exact(LANES);
+    let rest = chunks.remainder();
+    chunks.into_iter().any(|chunk| lanes_contain(chunk, target)) || rest.contains(&target)
+}
+
+/// Whether `target` is one of the first `len` cells of `cells`.
+pub(crate) fn deque_contains(cells: &VecDeque<Cell>, len: usize, target: Cell) -> bool {
+    let (front, back) = cells.as_slices();
+    let front_len = len.min(front.len());
+    let back_len = len.saturating_sub(front.len()).min(back.len());
+    contains(&front[..front_len], target) || contains(&back[..back_len], target)
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+fn lanes_contain(chunk: &[Cell], target: Cell) -> bool {
+    // SAFETY: SSE2 is part of the x86_64 baseline
+    unsafe { sse2_lanes_contain(chunk, target) }
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+#[target_feature(enable = "sse2")]
+fn sse2_lanes_contain(chunk: &[Cell], target: Cell) -> bool {
+    use core::arch::x86_64::*;
+
+    let [a, b, c, d] = chunk else {
+        return chunk.contains(&target);
+    };
+    let lanes = |cell: &Cell| cell.bits() as i32;
+    let cells = _mm_set_epi32(lanes(d), lanes(c), lanes(b), lanes(a));
+    let hits = _mm_cmpeq_epi32(cells, _mm_set1_epi32(lanes(&target)));
+    _mm_movemask_epi8(hits) != 0
+}
+
+#[cfg(all(feature = "simd", target_arch = "aarch64"))]
+fn lanes_contain(chunk: &[Cell], target: Cell) -> bool {
+    // SAFETY: NEON is part of the aarch64 baseline
+    unsafe { neon_lanes_contain(chunk, target) }
+}
+
+#[cfg(all(feature = "simd", target_arch = "aarch64"))]
+#[target_feature(enable = "neon")]
+fn neon_lanes_contain(chunk: &[Cell], target: Cell) -> bool {
+    use core::arch::aarch64::*;
+
+    let [a, b, c, d] = chunk else {
+        return chunk.contains(&target);
+    };
+    let cells = vsetq_lane_u32::<1>(b.bits(), vdupq_n_u32(a.bits()));
+    let cells = vsetq_lane_u32::<3>(d.bits(), vsetq_lane_u32::<2>(c.bits(), cells));
+    vmaxvq_u32(vceqq_u32(cells, vdupq_n_u32(target.bits()))) != 0
+}
+
+#[cfg(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128"))]
+fn lanes_contain(chunk: &[Cell], target: Cell) -> bool {
+    use core::arch::wasm32::*;
+
+    let [a, b, c, d] = chunk else {
+        return chunk.contains(&target);
+    };
+    let cells = u32x4(a.bits(), b.bits(), c.bits(), d.bits());
+    v128_any_true(u32x4_eq(cells, u32x4_splat(target.bits())))
+}
+
+#[cfg(not(all(
+    feature = "simd",
+    any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        all(target_arch = "wasm32", target_feature = "simd128")
+    )
+)))]
+fn lanes_contain(chunk: &[Cell], target: Cell) -> bool {
+    chunk.contains(&target)
+}
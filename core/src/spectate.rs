@@ -0,0 +1,324 @@
+//! Per-tick deltas for streaming a live game to spectators.
+//!
+//! A spectator starts from [`GameState::from_level`] with the player's
+//! level and seed, so the board and every seeded food spawn are already
+//! known; all that has to cross the wire each tick is the player's turns.
+//! [`step`] advances the player's game and returns its [`StateDelta`];
+//! [`apply`] plays that delta on the spectator's copy and checks that it
+//! lands on the same tick, food and outcome. Both copies extend the same
+//! [`GameState::tick_chain`], so a server that followed a game live can
+//! check that the proof made afterwards commits the head it ended on.
+//! Encoded layout, integers little endian:
+//!
+//! | bytes | field                                               |
+//! |-------|-----------------------------------------------------|
+//! | 4     | tick after the delta                                |
+//! | 1     | flags: bit 0 game over, bit 1 food eaten            |
+//! | n     | turn count, LEB128 varint                           |
+//! | n     | one [`Direction::to_byte`] per turn                 |
+//! | 4     | eaten food cell, only with bit 1 set                |
+//! | n     | spawned food count, LEB128 varint                   |
+//! | n     | 4 bytes per spawned food cell                       |
+//!
+//! Cells are two `u16`s, `x` first.
+//!
+//! A game streamed to a server rather than to a trusted spectator goes as
+//! [`LiveFrame`]s, which carry a kind byte, 0 for a delta or 1 for the
+//! final `.skr` replay, then the payload, then an HMAC-SHA256 tag over the
+//! rest under a key the server handed out for the session.
+
+use alloc::vec::Vec;
+
+use crate::codec::{constant_time_eq, hmac_sha256, varint_len, write_varint, Reader, Sink};
+use crate::hash::Commitment;
+use crate::{Direction, GameState, Position, Replay, SnakeError, StepOutcome};
+
+const GAME_OVER: u8 = 1;
+const EATEN: u8 = 1 << 1;
+
+const DELTA: u8 = 0;
+const FINISHED: u8 = 1;
+const TAG_LEN: usize = 32;
+
+/// What one tick of a game changed, enough for a spectator holding the
+/// previous state to compute the next one.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StateDelta {
+    /// Tick count once the delta is applied. A game ended by a turn under
+    /// [`crate::rules::IllegalMovePolicy::EndGame`] ends without a step,
+    /// keeping the tick.
+    pub tick: u32,
+    /// Turns applied before the step, in order; usually none or one.
+    pub turns: Vec<Direction>,
+    /// The food the snake ate on this tick.
+    pub eaten: Option<Position>,
+    /// Food that appeared on this tick, in slot order, including food that
+    /// expired and respawned on the same cell.
+    pub spawned: Vec<Position>,
+    pub game_over: bool,
+}
+
+impl StateDelta {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.encoded_len());
+        self.write(&mut bytes);
+        bytes
+    }
+
+    /// Length of [`StateDelta::encode`] output.
+    pub(crate) fn encoded_len(&self) -> usize {
+        delta_len(self.turns.len(), self.eaten.is_some(), self.spawned.len())
+    }
+
+    /// Writes [`StateDelta::encode`] output to `bytes`.
+    pub(crate) fn write(&self, bytes: &mut impl Sink) {
+        write_delta(
+            bytes,
+            self.tick,
+            self.game_over,
+            self.turns.iter().copied(),
+            self.eaten,
+            self.spawned.iter().copied(),
+        );
+    }
+
+    /// Decodes [`StateDelta::encode`] output. Fails with
+    /// [`SnakeError::BadDelta`] if the buffer is truncated, has trailing
+    /// bytes, unknown flags or an unknown direction.
+    pub fn decode(bytes: &[u8]) -> Result<StateDelta, SnakeError> {
+        let mut reader = Reader::new(bytes, SnakeError::BadDelta);
+        let tick = reader.u32()?;
+        let flags = reader.u8()?;
+        if flags & !(GAME_OVER | EATEN) != 0 {
+            return Err(SnakeError::BadDelta);
+        }
+        let turn_count = reader.varint()?;
+        let turns = reader
+            .take(usize::try_from(turn_count).map_err(|_| SnakeError::BadDelta)?)?
+            .iter()
+            .map(|&byte| Direction::from_byte(byte).ok_or(SnakeError::BadDelta))
+            .collect::<Result<Vec<_>, SnakeError>>()?;
+        let eaten = match flags & EATEN {
+            0 => None,
+            _ => Some(read_cell(&mut reader)?),
+        };
+        let spawned_count = reader.varint()?;
+        let spawned = (0..spawned_count)
+            .map(|_| read_cell(&mut reader))
+            .collect::<Result<Vec<_>, SnakeError>>()?;
+        reader.finish()?;
+        Ok(StateDelta {
+            tick,
+            turns,
+            eaten,
+            spawned,
+            game_over: flags & GAME_OVER != 0,
+        })
+    }
+}
+
+/// One signed message of a game streamed live to a server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LiveFrame {
+    Delta(StateDelta),
+    /// Sent once at game over, so the server can verify the whole game.
+    Finished(Replay),
+}
+
+impl LiveFrame {
+    /// Encodes the frame and appends its tag under `key`.
+    pub fn encode_signed(&self, key: &[u8]) -> Vec<u8> {
+        let mut bytes = match self {
+            LiveFrame::Delta(delta) => {
+                let mut bytes = alloc::vec![DELTA];
+                bytes.extend_from_slice(&delta.encode());
+                bytes
+            }
+            LiveFrame::Finished(replay) => {
+                let mut bytes = alloc::vec![FINISHED];
+                bytes.extend_from_slice(&replay.encode());
+                bytes
+            }
+        };
+        let tag = hmac_sha256(key, &bytes);
+        bytes.extend_from_slice(&tag);
+        bytes
+    }
+
+    /// Checks the tag under `key` and decodes the frame. Fails with
+    /// [`SnakeError::HashMismatch`] for a bad tag, and otherwise as
+    /// [`StateDelta::decode`] or [`Replay::decode`] do.
+    pub fn decode_signed(bytes: &[u8], key: &[u8]) -> Result<LiveFrame, SnakeError> {
+        let split = bytes
+            .len()
+            .checked_sub(TAG_LEN)
+            .ok_or(SnakeError::BadDelta)?;
+        let (bytes, tag) = bytes.split_at(split);
+        if !constant_time_eq(&hmac_sha256(key, bytes), tag) {
+            return Err(SnakeError::HashMismatch);
+        }
+        match bytes.split_first() {
+            Some((&DELTA, delta)) => Ok(LiveFrame::Delta(StateDelta::decode(delta)?)),
+            Some((&FINISHED, replay)) => Ok(LiveFrame::Finished(Replay::decode(replay)?)),
+            _ => Err(SnakeError::BadDelta),
+        }
+    }
+}
+
+/// Steps the player's game and returns the delta to send, or `None` for a
+/// paused or countdown step, which moves nothing. Once a game has ended,
+/// by the step or by a turn, this returns its final delta, which has
+/// `game_over` set; stop streaming after it.
+pub fn step(state: &mut GameState) -> Result<Option<StateDelta>, SnakeError> {
+    Ok(step_with_outcome(state)?.1)
+}
+
+/// [`step`], also returning what the step did, for frontends that react to
+/// it as well as stream it.
+pub fn step_with_outcome(
+    state: &mut GameState,
+) -> Result<(StepOutcome, Option<StateDelta>), SnakeError> {
+    let tick = state.ticks();
+    let outcome = match state.is_game_over() {
+        true => StepOutcome::GameOver,
+        false => state.step()?,
+    };
+    if matches!(outcome, StepOutcome::Paused | StepOutcome::CountingDown) {
+        return Ok((outcome, None));
+    }
+    let delta = StateDelta {
+        tick: state.ticks(),
+        turns: turns_since(tick, state).collect(),
+        eaten: eaten(outcome, state),
+        spawned: spawned_since(tick, state).collect(),
+        game_over: state.is_game_over(),
+    };
+    Ok((outcome, Some(delta)))
+}
+
+/// [`crate::hash::chain_tick`] of `head` and the delta that took the game
+/// from tick `tick` to `state` with `outcome`, hashed as it is encoded
+/// without building a [`StateDelta`] or a buffer; the engine does this
+/// every tick for [`GameState::tick_chain`].
+pub(crate) fn chain_since(
+    head: &[u8; 32],
+    tick: u32,
+    outcome: StepOutcome,
+    state: &GameState,
+) -> [u8; 32] {
+    let turns = turns_since(tick, state);
+    let eaten = eaten(outcome, state);
+    let spawned = spawned_since(tick, state);
+    let len = delta_len(turns.len(), eaten.is_some(), spawned.clone().count());
+    let mut link = Commitment::new(head.len() + len);
+    link.put(head);
+    write_delta(
+        &mut link,
+        state.ticks(),
+        state.is_game_over(),
+        turns,
+        eaten,
+        spawned,
+    );
+    link.finalize()
+}
+
+/// Plays `delta` on a spectator's copy of the game. Fails with
+/// [`SnakeError::BadDelta`] if the delta is not the next one for this
+/// state or the game does not play out as it says, leaving `state`
+/// partly advanced; resynchronise from a snapshot after that.
+pub fn apply(state: &mut GameState, delta: &StateDelta) -> Result<(), SnakeError> {
+    if state.is_game_over() {
+        return Err(SnakeError::BadDelta);
+    }
+    let tick = state.ticks();
+    for &turn in &delta.turns {
+        state.replay_move(turn).map_err(|_| SnakeError::BadDelta)?;
+    }
+    let outcome = match state.is_game_over() {
+        true => StepOutcome::GameOver,
+        // A spectator's copy is never paused or counting down
+        false => state.step().map_err(|_| SnakeError::BadDelta)?,
+    };
+    let matches = state.ticks() == delta.tick
+        && state.is_game_over() == delta.game_over
+        && eaten(outcome, state) == delta.eaten
+        && spawned_since(tick, state).eq(delta.spawned.iter().copied());
+    if !matches {
+        return Err(SnakeError::BadDelta);
+    }
+    Ok(())
+}
+
+fn write_delta(
+    bytes: &mut impl Sink,
+    tick: u32,
+    game_over: bool,
+    turns: impl ExactSizeIterator<Item = Direction>,
+    eaten: Option<Position>,
+    spawned: impl Iterator<Item = Position> + Clone,
+) {
+    bytes.put(&tick.to_le_bytes());
+    let mut flags = 0;
+    if game_over {
+        flags |= GAME_OVER;
+    }
+    if eaten.is_some() {
+        flags |= EATEN;
+    }
+    bytes.put(&[flags]);
+    write_varint(bytes, turns.len() as u64);
+    for turn in turns {
+        bytes.put(&[turn.to_byte()]);
+    }
+    if let Some(eaten) = eaten {
+        write_cell(bytes, eaten);
+    }
+    write_varint(bytes, spawned.clone().count() as u64);
+    for food in spawned {
+        write_cell(bytes, food);
+    }
+}
+
+/// Length of an encoded delta with `turns` turns, an eaten food or not, and
+/// `spawned` spawned foods.
+fn delta_len(turns: usize, eaten: bool, spawned: usize) -> usize {
+    let eaten = match eaten {
+        true => 4,
+        false => 0,
+    };
+    5 + varint_len(turns as u64) + turns + eaten + varint_len(spawned as u64) + spawned * 4
+}
+
+/// Turns made since the step from tick `tick`, which are logged at it.
+fn turns_since(tick: u32, state: &GameState) -> impl ExactSizeIterator<Item = Direction> + '_ {
+    let moves = state.moves();
+    let first = moves.partition_point(|mv| mv.tick < tick);
+    moves[first..].iter().map(|mv| mv.direction)
+}
+
+fn eaten(outcome: StepOutcome, state: &GameState) -> Option<Position> {
+    (outcome == StepOutcome::AteFood).then(|| state.head())
+}
+
+/// Food placed by the step from tick `tick`, in slot order: none if no
+/// tick ran, otherwise the food whose spawn tick is the current one.
+fn spawned_since(tick: u32, state: &GameState) -> impl Iterator<Item = Position> + Clone + '_ {
+    let ticked = state.ticks() != tick;
+    (state.foods().iter().zip(state.food_ticks()))
+        .filter(move |&(_, &spawned)| ticked && spawned == state.ticks())
+        .map(|(&food, _)| food)
+}
+
+fn write_cell(bytes: &mut impl Sink, cell: Position) {
+    bytes.put(&(cell.x as u16).to_le_bytes());
+    bytes.put(&(cell.y as u16).to_le_bytes());
+}
+
+fn read_cell(reader: &mut Reader) -> Result<Position, SnakeError> {
+    let bytes = reader.take(4)?;
+    let x = u16::from_le_bytes([bytes[0], bytes[1]]);
+    let y = u16::from_le_bytes([bytes[2], bytes[3]]);
+    Ok(Position::new(i32::from(x), i32::from(y)))
+}
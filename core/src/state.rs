@@ -0,0 +1,1083 @@
+use alloc::vec::Vec;
+
+use crate::codec::Reader;
+use crate::invariants::{self, InvariantReport};
+use crate::occupancy::Occupancy;
+use crate::rules::{self, IllegalMovePolicy, ScoringMode};
+use crate::{
+    hash, moves, spectate, Arena, Body, Direction, FoodKind, Level, Move, Position, Rng, SnakeError,
+};
+
+/// Version byte leading every [`GameState::to_bytes`] snapshot.
+const SNAPSHOT_VERSION: u8 = 20;
+
+/// Domain of the food placement stream, see [`crate::rng`].
+pub(crate) const FOOD_STREAM: &[u8] = b"snake-food";
+/// Domain of the food-kind stream. Kinds are drawn apart from placement,
+/// so a verifier handed the food positions still rolls the same kinds from
+/// the seed.
+pub(crate) const FOOD_KIND_STREAM: &[u8] = b"snake-food-kind";
+
+/// What happened during a single [`GameState::step`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StepOutcome {
+    Moved,
+    AteFood,
+    GameOver,
+    /// The snake died with a life to spare and respawned at the start.
+    LostLife,
+    /// The game is paused; nothing moved and the tick count is unchanged.
+    Paused,
+    /// A countdown tick before the game starts; nothing moved.
+    CountingDown,
+}
+
+/// A game in progress. With the `serde` feature fields serialize in
+/// declaration order; reordering them breaks saved games.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameState {
+    /// Head first, so a move is a push at the front and a pop at the back.
+    snake: Body,
+    /// The cells under [`GameState::snake`], rebuilt from it after a serde
+    /// round trip.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    occupied: Occupancy,
+    /// The cells under the obstacles and [`GameState::foods`], rebuilt like
+    /// `occupied`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    fixtures: Occupancy,
+    /// Food on the board, in spawn slots: an eaten item is replaced in place.
+    foods: Vec<Position>,
+    /// Kind of each food, by slot.
+    food_kinds: Vec<FoodKind>,
+    /// Tick each food appeared on, by slot.
+    food_ticks: Vec<u32>,
+    direction: Direction,
+    level: Level,
+    seed: u64,
+    rng: Rng,
+    kind_rng: Rng,
+    score: u64,
+    ticks: u32,
+    /// Segments still to be added from food already eaten.
+    pending_growth: u32,
+    /// Lives left, including the current one.
+    lives: u8,
+    /// Foods eaten in the current combo, 0 when there is none.
+    combo: u32,
+    /// Tick the last food was eaten on.
+    last_food_tick: u32,
+    game_over: bool,
+    moves: Vec<Move>,
+    /// Turns waiting for a tick, see [`GameState::queue_direction`].
+    queued: Vec<Direction>,
+    paused: bool,
+    /// Steps left before the snake starts moving.
+    countdown: u32,
+    /// Head of the hash chain over every tick so far, see
+    /// [`GameState::tick_chain`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    tick_chain: [u8; 32],
+    /// Cells changed since the last [`GameState::take_dirty_cells`], or
+    /// `None` until it is first called. Not saved.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dirty: Option<Vec<Position>>,
+}
+
+impl GameState {
+    /// Starts a new game on the [`Level::classic`] board. Food placement is
+    /// derived from `seed`, so two games with the same seed and inputs play
+    /// out identically. With `wrap_walls` the snake passes through the edges
+    /// instead of dying there.
+    pub fn new(grid_width: i32, grid_height: i32, seed: u64, wrap_walls: bool) -> GameState {
+        let level = Level::classic(grid_width, grid_height, wrap_walls);
+        let mut state = GameState::start(level, seed);
+        state.spawn_initial_food();
+        state
+    }
+
+    /// Like [`GameState::new`], with static `obstacles` that kill the snake
+    /// like walls. Fails with [`SnakeError::BadLayout`] if the resulting
+    /// level is not playable (see [`Level::validate`]).
+    pub fn with_obstacles(
+        grid_width: i32,
+        grid_height: i32,
+        seed: u64,
+        wrap_walls: bool,
+        obstacles: Vec<Position>,
+    ) -> Result<GameState, SnakeError> {
+        let level = Level {
+            obstacles,
+            ..Level::classic(grid_width, grid_height, wrap_walls)
+        };
+        GameState::from_level(level, seed)
+    }
+
+    /// Starts a game on `level`. Fails with [`SnakeError::BadLayout`] if the
+    /// level is not playable.
+    pub fn from_level(level: Level, seed: u64) -> Result<GameState, SnakeError> {
+        level.validate()?;
+        let mut state = GameState::start(level, seed);
+        state.spawn_initial_food();
+        Ok(state)
+    }
+
+    /// The board before any food is placed.
+    fn start(level: Level, seed: u64) -> GameState {
+        let snake = Body::from(level.start_snake());
+        GameState {
+            occupied: Occupancy::new(level.grid_width, level.grid_height, snake.iter()),
+            fixtures: Occupancy::new(
+                level.grid_width,
+                level.grid_height,
+                level.obstacles.iter().copied(),
+            ),
+            snake,
+            foods: Vec::new(),
+            food_kinds: Vec::new(),
+            food_ticks: Vec::new(),
+            direction: level.start_direction,
+            lives: level.lives,
+            level,
+            seed,
+            rng: Rng::new(seed, FOOD_STREAM),
+            kind_rng: Rng::new(seed, FOOD_KIND_STREAM),
+            score: 0,
+            ticks: 0,
+            pending_growth: 0,
+            combo: 0,
+            last_food_tick: 0,
+            game_over: false,
+            moves: Vec::new(),
+            queued: Vec::new(),
+            paused: false,
+            countdown: 0,
+            tick_chain: [0; 32],
+            dirty: None,
+        }
+    }
+
+    /// Fills every food slot: the level's fixed first food if it has one,
+    /// then seeded spawns in order. A board too full for any food ends the
+    /// game.
+    fn spawn_initial_food(&mut self) {
+        if let Some(first) = self.level.food.first {
+            self.place_food(0, first);
+        }
+        while self.foods.len() < self.level.food.count as usize {
+            match self.random_free_cell() {
+                Some(food) => self.place_food(self.foods.len(), food),
+                None => break,
+            }
+        }
+        self.game_over = self.foods.is_empty();
+    }
+
+    /// Puts a food at `pos` in `slot`, rolling its kind from the seed.
+    fn place_food(&mut self, slot: usize, pos: Position) {
+        let kind = self.level.food.kind_for_roll(self.kind_rng.next_below(100));
+        self.foods.insert(slot, pos);
+        self.food_kinds.insert(slot, kind);
+        self.food_ticks.insert(slot, self.ticks);
+        self.fixtures.insert(pos);
+        self.mark_dirty(pos);
+    }
+
+    fn remove_food(&mut self, slot: usize) {
+        let food = self.foods.remove(slot);
+        self.food_kinds.remove(slot);
+        self.food_ticks.remove(slot);
+        // Only a custom `next_food` can stack food or put it on an obstacle
+        if !self.foods.contains(&food) && !self.level.obstacles.contains(&food) {
+            self.fixtures.remove(food);
+        }
+        self.mark_dirty(food);
+    }
+
+    /// Records a changed cell for [`GameState::take_dirty_cells`], once
+    /// it has been called.
+    fn mark_dirty(&mut self, pos: Position) {
+        if let Some(dirty) = &mut self.dirty {
+            dirty.push(pos);
+        }
+    }
+
+    /// Cells whose contents changed since the last call: the old tail, the
+    /// new head, eaten, despawned and spawned food, and the whole snake
+    /// before and after a lost life. Each cell is listed once. Tracking
+    /// starts with the first call, which returns every snake and food cell
+    /// so a renderer can draw the board from scratch. The cells a closing
+    /// ring takes from the [`GameState::arena`] are not listed.
+    pub fn take_dirty_cells(&mut self) -> Vec<Position> {
+        let Some(dirty) = self.dirty.replace(Vec::new()) else {
+            self.dirty = Some(Vec::new());
+            return self
+                .snake
+                .iter()
+                .chain(self.foods.iter().copied())
+                .collect();
+        };
+        let mut cells = Vec::with_capacity(dirty.len());
+        for cell in dirty {
+            if !cells.contains(&cell) {
+                cells.push(cell);
+            }
+        }
+        cells
+    }
+
+    /// Saves the complete game, including the RNG state and move log, so
+    /// [`GameState::from_bytes`] resumes it exactly where it left off.
+    ///
+    /// Layout (integers little endian): version byte, the level's
+    /// [`Level::encode`] bytes, seed, placement and food-kind RNG positions,
+    /// score, ticks, pending growth, lives byte, combo, last food tick,
+    /// direction byte, game-over byte, food count and cells, one kind byte
+    /// and spawn tick per food, snake length and segments, move count and
+    /// encoded moves, queued turn count byte and direction bytes, paused
+    /// byte, countdown, tick chain head.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            108 + (self.snake.len() + self.foods.len() + self.level.obstacles.len()) * 8
+                + self.foods.len() * 5
+                + self.moves.len() * moves::MOVE_SIZE
+                + self.queued.len()
+                + 37,
+        );
+        bytes.push(SNAPSHOT_VERSION);
+        self.level.write(&mut bytes);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&self.rng.position().to_le_bytes());
+        bytes.extend_from_slice(&self.kind_rng.position().to_le_bytes());
+        bytes.extend_from_slice(&self.score.to_le_bytes());
+        bytes.extend_from_slice(&self.ticks.to_le_bytes());
+        bytes.extend_from_slice(&self.pending_growth.to_le_bytes());
+        bytes.push(self.lives);
+        bytes.extend_from_slice(&self.combo.to_le_bytes());
+        bytes.extend_from_slice(&self.last_food_tick.to_le_bytes());
+        bytes.push(self.direction.to_byte());
+        bytes.push(self.game_over as u8);
+        write_cells(&mut bytes, self.foods.iter().copied());
+        bytes.extend(self.food_kinds.iter().map(|kind| kind.to_byte()));
+        for tick in &self.food_ticks {
+            bytes.extend_from_slice(&tick.to_le_bytes());
+        }
+        write_cells(&mut bytes, self.snake.iter());
+        bytes.extend_from_slice(&(self.moves.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&moves::encode_moves(&self.moves));
+        bytes.push(self.queued.len() as u8);
+        bytes.extend(self.queued.iter().map(|direction| direction.to_byte()));
+        bytes.push(self.paused as u8);
+        bytes.extend_from_slice(&self.countdown.to_le_bytes());
+        bytes.extend_from_slice(&self.tick_chain);
+        bytes
+    }
+
+    /// Restores a game saved with [`GameState::to_bytes`]. Fails with
+    /// [`SnakeError::BadSnapshot`] if the buffer is truncated, has an unknown
+    /// version or does not describe a legal board.
+    pub fn from_bytes(bytes: &[u8]) -> Result<GameState, SnakeError> {
+        let mut reader = Reader::new(bytes, SnakeError::BadSnapshot);
+        if reader.u8()? != SNAPSHOT_VERSION {
+            return Err(SnakeError::BadSnapshot);
+        }
+        let level = Level::read(&mut reader)?;
+        let seed = reader.u64()?;
+        let rng = Rng::resume(seed, FOOD_STREAM, reader.u64()?);
+        let kind_rng = Rng::resume(seed, FOOD_KIND_STREAM, reader.u64()?);
+        let score = reader.u64()?;
+        let ticks = reader.u32()?;
+        let pending_growth = reader.u32()?;
+        let lives = reader.u8()?;
+        let combo = reader.u32()?;
+        let last_food_tick = reader.u32()?;
+        let direction = Direction::from_byte(reader.u8()?).ok_or(SnakeError::BadSnapshot)?;
+        let game_over = reader.bool()?;
+        let foods = read_cells(&mut reader)?;
+        let food_kinds = (0..foods.len())
+            .map(|_| FoodKind::from_byte(reader.u8()?).ok_or(SnakeError::BadSnapshot))
+            .collect::<Result<Vec<_>, SnakeError>>()?;
+        let food_ticks = (0..foods.len())
+            .map(|_| reader.u32())
+            .collect::<Result<Vec<_>, SnakeError>>()?;
+        let snake = read_cells(&mut reader)?;
+        // Segments are packed as grid cells, so one off the grid is
+        // rejected before it could wrap onto it
+        if snake.iter().any(|&cell| !level.in_bounds(cell)) {
+            return Err(SnakeError::BadSnapshot);
+        }
+        let move_count = reader.u32()? as usize;
+        let move_bytes = reader.take(move_count.saturating_mul(moves::MOVE_SIZE))?;
+        let moves = moves::decode_moves(move_bytes).map_err(|_| SnakeError::BadSnapshot)?;
+        let queued_count = reader.u8()? as usize;
+        if queued_count > rules::INPUT_QUEUE_CAPACITY {
+            return Err(SnakeError::BadSnapshot);
+        }
+        let queued = (0..queued_count)
+            .map(|_| Direction::from_byte(reader.u8()?).ok_or(SnakeError::BadSnapshot))
+            .collect::<Result<Vec<_>, SnakeError>>()?;
+        let paused = reader.bool()?;
+        let countdown = reader.u32()?;
+        let tick_chain = reader.take(32)?.try_into().expect("32 bytes");
+        reader.finish()?;
+
+        let mut state = GameState {
+            occupied: Occupancy::new(level.grid_width, level.grid_height, snake.iter().copied()),
+            fixtures: Occupancy::default(),
+            snake: snake.into(),
+            foods: Vec::new(),
+            food_kinds,
+            food_ticks,
+            direction,
+            level,
+            seed,
+            rng,
+            kind_rng,
+            score,
+            ticks,
+            pending_growth,
+            lives,
+            combo,
+            last_food_tick,
+            game_over,
+            moves,
+            queued,
+            paused,
+            countdown,
+            tick_chain,
+            dirty: None,
+        };
+        if state.level.validate().is_err()
+            || (!game_over && state.snake.iter().any(|cell| !state.in_bounds(cell)))
+            || foods.len() > state.level.food.count as usize
+        {
+            return Err(SnakeError::BadSnapshot);
+        }
+        state.foods = foods;
+        state.build_boards();
+        if !state.validate_invariants().is_ok() {
+            return Err(SnakeError::BadSnapshot);
+        }
+        Ok(state)
+    }
+
+    /// The whole game as a JSON object, fields named as in the struct:
+    /// snake and food cells as `{ "x", "y" }` objects, the level under
+    /// `level`, the tick count under `ticks`. Meant for debugging tools;
+    /// [`GameState::to_bytes`] is the format to save games in.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> alloc::string::String {
+        serde_json::to_string(self).expect("game state serializes")
+    }
+
+    /// The board the game is played on.
+    pub fn level(&self) -> &Level {
+        &self.level
+    }
+
+    pub fn grid_width(&self) -> i32 {
+        self.level.grid_width
+    }
+
+    pub fn grid_height(&self) -> i32 {
+        self.level.grid_height
+    }
+
+    /// Snake body, head first.
+    pub fn snake(&self) -> &Body {
+        &self.snake
+    }
+
+    /// The head, or the level's start on a corrupt state with no snake,
+    /// which [`GameState::step`] rejects.
+    pub fn head(&self) -> Position {
+        self.snake.head().unwrap_or(self.level.start)
+    }
+
+    /// Food on the board, in spawn-slot order.
+    pub fn foods(&self) -> &[Position] {
+        &self.foods
+    }
+
+    /// Kind of each item in [`GameState::foods`].
+    pub fn food_kinds(&self) -> &[FoodKind] {
+        &self.food_kinds
+    }
+
+    /// Tick each item in [`GameState::foods`] appeared on.
+    pub fn food_ticks(&self) -> &[u32] {
+        &self.food_ticks
+    }
+
+    pub fn wrap_walls(&self) -> bool {
+        self.level.wrap_walls
+    }
+
+    pub fn obstacles(&self) -> &[Position] {
+        &self.level.obstacles
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    pub fn score(&self) -> u64 {
+        self.score
+    }
+
+    /// Number of ticks simulated so far.
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    /// Segments still to be added from food already eaten.
+    pub fn pending_growth(&self) -> u32 {
+        self.pending_growth
+    }
+
+    /// Multiplier the next food scores with if eaten in time, see
+    /// [`rules::ScoringRules::combo_window_ticks`].
+    pub fn combo_multiplier(&self) -> u32 {
+        self.combo.max(1)
+    }
+
+    /// What earns points on this level.
+    pub fn scoring_mode(&self) -> ScoringMode {
+        self.level.scoring.mode
+    }
+
+    /// Lives left, including the current one.
+    pub fn lives(&self) -> u8 {
+        self.lives
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.game_over
+    }
+
+    /// Every accepted direction change so far, in order.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// The move log in its compact byte encoding.
+    pub fn export_moves(&self) -> Vec<u8> {
+        moves::encode_moves(&self.moves)
+    }
+
+    /// Commitment to the current state, as committed by the prover: SHA-256,
+    /// or Keccak-256 or Poseidon2 with the feature of that name.
+    pub fn state_hash(&self) -> [u8; 32] {
+        hash::state_hash(self)
+    }
+
+    /// Head of the tick chain: 32 zero bytes at the start, then
+    /// `h' = H(h || delta)` after every tick, `delta` being that tick's
+    /// [`crate::spectate::StateDelta`] encoding and `H` the state hash's
+    /// hash (see [`hash::chain_tick`]). Paused and countdown steps, and a
+    /// turn that ends the game without a tick, add no link. Replaying the
+    /// move log, applying a stream of deltas or resuming a snapshot arrive
+    /// at the same head, which the SP1 program commits as `tickChain`.
+    pub fn tick_chain(&self) -> [u8; 32] {
+        self.tick_chain
+    }
+
+    /// The state hash once the game has ended, `None` while still playing.
+    pub fn final_state_hash(&self) -> Option<[u8; 32]> {
+        self.game_over.then(|| self.state_hash())
+    }
+
+    /// The playable area at the current tick, see [`Level::arena`].
+    pub fn arena(&self) -> Arena {
+        self.level.arena(self.ticks)
+    }
+
+    /// Whether `pos` is inside the current [`GameState::arena`].
+    pub fn in_bounds(&self, pos: Position) -> bool {
+        self.arena().contains(pos)
+    }
+
+    /// Whether `pos` is in the arena and not covered by the snake or an
+    /// obstacle.
+    pub fn is_free(&self, pos: Position) -> bool {
+        self.in_bounds(pos) && !self.covers(pos) && !self.on_obstacle(pos)
+    }
+
+    /// Whether an obstacle lies on `pos`, in constant time once the fixture
+    /// board is built unless food shares the cell.
+    fn on_obstacle(&self, pos: Position) -> bool {
+        if self
+            .fixtures
+            .fits(self.level.grid_width, self.level.grid_height)
+        {
+            // A fixture that is not food is an obstacle, and only a custom
+            // `next_food` puts food on one
+            self.fixtures.contains(pos)
+                && (!self.foods.contains(&pos) || self.level.obstacles.contains(&pos))
+        } else {
+            self.level.obstacles.contains(&pos)
+        }
+    }
+
+    /// Whether a segment lies on `pos`, in constant time once the
+    /// occupancy board is built.
+    fn covers(&self, pos: Position) -> bool {
+        if self
+            .occupied
+            .fits(self.level.grid_width, self.level.grid_height)
+        {
+            self.occupied.contains(pos)
+        } else {
+            self.snake.contains(pos)
+        }
+    }
+
+    /// Whether the occupancy boards, if built, cover exactly the snake and
+    /// exactly the obstacles and food.
+    pub(crate) fn occupancy_matches(&self) -> bool {
+        let (width, height) = (self.level.grid_width, self.level.grid_height);
+        let snake_matches = !self.occupied.fits(width, height)
+            || (self.occupied.len() == self.snake.len()
+                && self
+                    .snake
+                    .iter()
+                    .all(|segment| self.occupied.contains(segment)));
+        let fixtures = (self.level.obstacles.iter()).chain(&self.foods).copied();
+        snake_matches
+            && (!self.fixtures.fits(width, height)
+                || self.fixtures == Occupancy::new(width, height, fixtures))
+    }
+
+    /// Checks the structural invariants every reachable state keeps: a
+    /// non-empty snake of distinct, adjacent segments on the grid and off
+    /// the obstacles, food slots in step, food on free arena cells, food on
+    /// the board while the game runs, and no more lives than the level
+    /// grants. Snapshots are checked with it on load; an empty report
+    /// anywhere else means the engine is sound.
+    pub fn validate_invariants(&self) -> InvariantReport {
+        invariants::check(self)
+    }
+
+    /// Checks whether the head could occupy `head`.
+    pub fn check_collision(&self, head: Position) -> Result<(), SnakeError> {
+        // Check wall collision
+        if !self.in_bounds(head) {
+            return Err(SnakeError::OutOfBounds);
+        }
+        if self.on_obstacle(head) {
+            return Err(SnakeError::ObstacleCollision);
+        }
+
+        // Check self collision (skip the head)
+        if head != self.head() && self.covers(head) {
+            return Err(SnakeError::SelfCollision);
+        }
+        Ok(())
+    }
+
+    /// Checks `score` against the snake length exactly. On levels with
+    /// golden or poison food, survival scoring, combos or extra lives the
+    /// length no longer follows from the score, so it must match the
+    /// engine's own tally instead.
+    pub fn verify_score(&self, score: u64) -> Result<(), SnakeError> {
+        let (food, scoring) = (&self.level.food, &self.level.scoring);
+        if scoring.mode == ScoringMode::Food
+            && food.golden_percent == 0
+            && food.poison_percent == 0
+            && scoring.survival_points_per_tick == 0
+            && scoring.combo_window_ticks == 0
+            && self.level.lives == 1
+        {
+            let length = (self.snake.len() as u32).saturating_add(self.pending_growth);
+            rules::verify_score(scoring, score, length)
+        } else if score == self.score {
+            Ok(())
+        } else {
+            Err(SnakeError::ScoreMismatch)
+        }
+    }
+
+    /// Turns the snake for the next tick.
+    ///
+    /// A turn that would move the head straight back into the neck is
+    /// rejected with [`SnakeError::InvalidMove`] and leaves the direction
+    /// unchanged; repeating the current direction does nothing. Under
+    /// [`IllegalMovePolicy::EndGame`] either input is recorded and ends the
+    /// game, the reversal still failing with `InvalidMove`.
+    pub fn change_direction(&mut self, direction: Direction) -> Result<(), SnakeError> {
+        if self.game_over {
+            return Err(SnakeError::GameOver);
+        }
+        let reverses = self.snake.get(1) == Some(self.neighbour(self.head(), direction));
+        let repeats = direction == self.direction;
+        if (reverses || repeats) && self.level.illegal_moves == IllegalMovePolicy::EndGame {
+            self.moves.push(Move {
+                tick: self.ticks,
+                direction,
+            });
+            self.game_over = true;
+        }
+        if reverses {
+            return Err(SnakeError::InvalidMove);
+        }
+        if !repeats {
+            self.direction = direction;
+            self.moves.push(Move {
+                tick: self.ticks,
+                direction,
+            });
+        }
+        Ok(())
+    }
+
+    /// Buffers a turn for a later tick: each [`GameState::step`] takes the
+    /// oldest queued turn, if any, and applies it with
+    /// [`GameState::change_direction`] before moving. This keeps a second
+    /// quick turn, such as right-then-down while heading left, that would
+    /// otherwise reverse into the neck. The move log records a turn when it
+    /// is applied, so replays and the verifier need nothing extra. Fails
+    /// with [`SnakeError::InputQueueFull`] once
+    /// [`rules::INPUT_QUEUE_CAPACITY`] turns are waiting.
+    pub fn queue_direction(&mut self, direction: Direction) -> Result<(), SnakeError> {
+        if self.game_over {
+            return Err(SnakeError::GameOver);
+        }
+        // Repeats are no-ops when ignored; don't let them take up a slot
+        let last = self.queued.last().copied().unwrap_or(self.direction);
+        if direction == last && self.level.illegal_moves == IllegalMovePolicy::Ignore {
+            return Ok(());
+        }
+        if self.queued.len() >= rules::INPUT_QUEUE_CAPACITY {
+            return Err(SnakeError::InputQueueFull);
+        }
+        self.queued.push(direction);
+        Ok(())
+    }
+
+    /// Holds the game: until [`GameState::resume`], steps return
+    /// [`StepOutcome::Paused`] without counting as ticks, so a paused game's
+    /// move log replays exactly like an unpaused one.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Makes the next `steps` steps return [`StepOutcome::CountingDown`]
+    /// before the snake moves. Like paused steps they are not ticks, and
+    /// turns made meanwhile take effect on the first real tick.
+    pub fn set_countdown(&mut self, steps: u32) {
+        self.countdown = steps;
+    }
+
+    /// How long the frontend should wait before the next step, from the
+    /// level's [`rules::Difficulty`] at the current score.
+    pub fn current_tick_interval_ms(&self) -> u32 {
+        self.level.difficulty.interval_ms(self.score)
+    }
+
+    /// Countdown steps left before the snake starts moving.
+    pub fn countdown(&self) -> u32 {
+        self.countdown
+    }
+
+    /// Turns still waiting in the input queue, oldest first.
+    pub fn queued_directions(&self) -> &[Direction] {
+        &self.queued
+    }
+
+    /// Applies a move read from a recording. A move the engine refuses means
+    /// the log does not belong to this game and fails with
+    /// [`SnakeError::BadReplay`], unless the level's policy says such input
+    /// ends the game.
+    pub(crate) fn replay_move(&mut self, direction: Direction) -> Result<(), SnakeError> {
+        match self.change_direction(direction) {
+            Err(SnakeError::InvalidMove)
+                if self.level.illegal_moves == IllegalMovePolicy::EndGame =>
+            {
+                Ok(())
+            }
+            result => result.map_err(|_| SnakeError::BadReplay),
+        }
+    }
+
+    /// Advances the game by one tick. Fails with [`SnakeError::GameOver`] if
+    /// the game has already ended. On a timed level the tick that uses up
+    /// [`Level::max_ticks`] ends the game, whatever its outcome.
+    pub fn step(&mut self) -> Result<StepOutcome, SnakeError> {
+        self.step_with_food(GameState::random_free_cell)
+    }
+
+    /// Runs up to `steps` [`GameState::step`]s in one call, taking queued
+    /// turns as usual, and stops early once the game ends or is paused.
+    /// Returns how many ticks ran; countdown steps use up `steps` without
+    /// counting as ticks.
+    pub fn step_many(&mut self, steps: u32) -> Result<u32, SnakeError> {
+        let start = self.ticks;
+        for _ in 0..steps {
+            if self.game_over || self.paused {
+                break;
+            }
+            self.step()?;
+        }
+        Ok(self.ticks - start)
+    }
+
+    /// Advances the game by one tick, asking `next_food` where to place the
+    /// replacement whenever a food is eaten or moved out of a respawning
+    /// snake's way. Returning `None` leaves the slot empty; the game ends
+    /// once no food is left.
+    pub fn step_with_food(
+        &mut self,
+        next_food: impl FnMut(&mut GameState) -> Option<Position>,
+    ) -> Result<StepOutcome, SnakeError> {
+        let tick = self.ticks;
+        let outcome = self.tick(next_food)?;
+        if self.ticks != tick {
+            // The link is hashed straight from the state; this runs every
+            // tick in the guest
+            self.tick_chain = spectate::chain_since(&self.tick_chain, tick, outcome, self);
+        }
+        Ok(outcome)
+    }
+
+    /// [`GameState::step_with_food`] without extending the tick chain.
+    fn tick(
+        &mut self,
+        mut next_food: impl FnMut(&mut GameState) -> Option<Position>,
+    ) -> Result<StepOutcome, SnakeError> {
+        if self.game_over {
+            return Err(SnakeError::GameOver);
+        }
+        if self.paused {
+            return Ok(StepOutcome::Paused);
+        }
+        if self.countdown > 0 {
+            self.countdown -= 1;
+            return Ok(StepOutcome::CountingDown);
+        }
+        if self.snake.is_empty()
+            || self.food_kinds.len() != self.foods.len()
+            || self.food_ticks.len() != self.foods.len()
+        {
+            return Err(SnakeError::CorruptState);
+        }
+        self.build_boards();
+        if !self.queued.is_empty() {
+            let direction = self.queued.remove(0);
+            // A queued reversal is dropped, or ends the game if the level
+            // says so
+            let _ = self.change_direction(direction);
+            if self.game_over {
+                return Ok(StepOutcome::GameOver);
+            }
+        }
+
+        // A game as long as the tick counter allows ends there
+        let Some(ticks) = self.ticks.checked_add(1) else {
+            self.game_over = true;
+            return Ok(StepOutcome::GameOver);
+        };
+        self.ticks = ticks;
+        if self.ticks.saturating_sub(self.last_food_tick) > self.level.scoring.combo_window_ticks {
+            self.combo = 0;
+        }
+        // The ring closes before the snake moves; food caught outside it is
+        // replaced like despawned food
+        if self.level.shrink_interval_ticks > 0 {
+            self.replace_foods(
+                |state, slot| (state.foods.get(slot)).is_none_or(|&food| !state.in_bounds(food)),
+                &mut next_food,
+            );
+        }
+        let head = self.neighbour(self.head(), self.direction);
+        let eaten = self.foods.iter().position(|&food| food == head);
+        let kind = eaten.and_then(|slot| self.food_kinds.get(slot).copied());
+        let mut growth = self.pending_growth;
+        if matches!(kind, Some(FoodKind::Normal | FoodKind::Golden)) {
+            growth = growth.saturating_add(self.level.scoring.growth_per_food);
+        }
+        let grows = growth > 0;
+
+        // The tail moves out of the way unless the snake is growing
+        let hits_body = self.covers(head) && (grows || self.snake.tail() != Some(head));
+        // Only a closing ring can leave segments outside the arena
+        let caught_outside = self.level.shrink_interval_ticks > 0 && {
+            let body_len = match grows {
+                true => self.snake.len(),
+                false => self.snake.len().saturating_sub(1),
+            };
+            (self.snake.iter().take(body_len)).any(|segment| !self.in_bounds(segment))
+        };
+        let collides =
+            !self.in_bounds(head) || hits_body || self.on_obstacle(head) || caught_outside;
+        // Poison would shrink a one-segment snake to nothing
+        let starves = kind == Some(FoodKind::Poison) && self.snake.len() == 1 && !grows;
+        if collides || starves {
+            return Ok(self.lose_life(&mut next_food));
+        }
+
+        // Clear the tail first: the head may move into its cell
+        if grows {
+            self.pending_growth = growth.saturating_sub(1);
+        } else {
+            self.pop_tail();
+        }
+        self.snake.push_front(head);
+        self.occupied.insert(head);
+        self.mark_dirty(head);
+        let scoring = &self.level.scoring;
+        self.score = self
+            .score
+            .saturating_add(u64::from(scoring.survival_points_per_tick));
+        let outcome = match (eaten, kind) {
+            (Some(slot), Some(kind)) => {
+                self.eat(slot, kind, &mut next_food);
+                StepOutcome::AteFood
+            }
+            _ => StepOutcome::Moved,
+        };
+        let lifetime = self.level.food.lifetime_ticks;
+        if lifetime > 0 {
+            self.replace_foods(
+                |state, slot| {
+                    let spawned = state.food_ticks.get(slot).copied().unwrap_or(0);
+                    state.ticks.saturating_sub(spawned) >= lifetime
+                },
+                &mut next_food,
+            );
+        }
+        let max_ticks = self.level.max_ticks;
+        self.game_over = self.foods.is_empty() || (max_ticks > 0 && self.ticks >= max_ticks);
+        Ok(outcome)
+    }
+
+    /// Scores the food in `slot` and replaces it.
+    fn eat(
+        &mut self,
+        slot: usize,
+        kind: FoodKind,
+        next_food: &mut impl FnMut(&mut GameState) -> Option<Position>,
+    ) {
+        let scoring = &self.level.scoring;
+        self.last_food_tick = self.ticks;
+        self.combo = match kind {
+            FoodKind::Poison => 0,
+            _ => self
+                .combo
+                .saturating_add(1)
+                .min(scoring.max_combo_multiplier.max(1)),
+        };
+        let points = match kind {
+            FoodKind::Poison => {
+                self.pop_tail();
+                0
+            }
+            _ if scoring.mode == ScoringMode::Survival => 0,
+            FoodKind::Normal => scoring.points_per_food,
+            FoodKind::Golden => scoring.points_per_golden_food,
+        };
+        let points = u64::from(points).saturating_mul(u64::from(self.combo_multiplier()));
+        self.score = self.score.saturating_add(points);
+        self.remove_food(slot);
+        if let Some(food) = next_food(self) {
+            self.place_food(slot, food);
+        }
+    }
+
+    /// Ends the game on the last life or when the start has fallen outside
+    /// the arena; otherwise puts the snake back at the level's start with
+    /// its initial length and heading, clears pending growth and queued
+    /// turns, and moves any food under it, in slot order.
+    fn lose_life(
+        &mut self,
+        next_food: &mut impl FnMut(&mut GameState) -> Option<Position>,
+    ) -> StepOutcome {
+        self.lives = self.lives.saturating_sub(1);
+        let start = self.level.start_snake();
+        if self.lives == 0 || start.iter().any(|&cell| !self.in_bounds(cell)) {
+            self.game_over = true;
+            return StepOutcome::GameOver;
+        }
+
+        if let Some(dirty) = &mut self.dirty {
+            dirty.extend(self.snake.iter().chain(start.iter().copied()));
+        }
+        self.occupied.reset(start.iter().copied());
+        self.snake = start.into();
+        self.direction = self.level.start_direction;
+        self.pending_growth = 0;
+        self.combo = 0;
+        self.queued.clear();
+        self.replace_foods(
+            |state, slot| {
+                state
+                    .foods
+                    .get(slot)
+                    .is_some_and(|&food| state.covers(food))
+            },
+            next_food,
+        );
+        self.game_over = self.foods.is_empty();
+        if self.game_over {
+            StepOutcome::GameOver
+        } else {
+            StepOutcome::LostLife
+        }
+    }
+
+    fn pop_tail(&mut self) {
+        if let Some(tail) = self.snake.pop_back() {
+            self.occupied.remove(tail);
+            self.mark_dirty(tail);
+        }
+    }
+
+    /// Replaces every food whose slot matches `stale`, in slot order, with
+    /// the next spawn from `next_food`.
+    fn replace_foods(
+        &mut self,
+        stale: impl Fn(&GameState, usize) -> bool,
+        next_food: &mut impl FnMut(&mut GameState) -> Option<Position>,
+    ) {
+        let mut slot = 0;
+        while slot < self.foods.len() {
+            if !stale(self, slot) {
+                slot += 1;
+                continue;
+            }
+            self.remove_food(slot);
+            if let Some(food) = next_food(self) {
+                self.place_food(slot, food);
+                slot += 1;
+            }
+        }
+    }
+
+    /// Whether a replay with no moves left, `idle_ticks` since its last turn
+    /// or food, can never end. Timed games and shrinking arenas always end,
+    /// and expiring food can respawn in the snake's path however long it
+    /// has gone straight; see [`rules::is_stalled`] for the rest.
+    pub(crate) fn is_stalled(&self, idle_ticks: u32) -> bool {
+        self.level.max_ticks == 0
+            && self.level.shrink_interval_ticks == 0
+            && self.level.food.lifetime_ticks == 0
+            && rules::is_stalled(idle_ticks, self.level.grid_width, self.level.grid_height)
+    }
+
+    /// Ticks left on a timed level, `None` without a limit.
+    pub fn ticks_left(&self) -> Option<u32> {
+        let max_ticks = self.level.max_ticks;
+        (max_ticks > 0).then(|| max_ticks.saturating_sub(self.ticks))
+    }
+
+    /// See [`Level::neighbour`].
+    pub fn neighbour(&self, pos: Position, direction: Direction) -> Position {
+        self.level.neighbour(pos, direction)
+    }
+
+    /// Whether a new food may appear at `pos`: a free cell without food.
+    pub fn can_spawn_food(&self, pos: Position) -> bool {
+        self.is_free(pos) && !self.foods.contains(&pos)
+    }
+
+    /// Number of cells where food could still spawn.
+    pub fn free_cells(&self) -> u32 {
+        let arena = self.arena();
+        let taken = match self.boards_fit() {
+            true => (arena.min.y..=arena.max.y)
+                .map(|y| {
+                    self.occupied.count_row(&[], y, arena.min.x, arena.max.x)
+                        + self.fixtures.count_row(&[], y, arena.min.x, arena.max.x)
+                })
+                .sum(),
+            false => (self.snake.iter())
+                .chain(self.foods.iter().copied())
+                .chain(self.level.obstacles.iter().copied())
+                .filter(|&cell| arena.contains(cell))
+                .count(),
+        };
+        arena.cells().saturating_sub(taken as u32)
+    }
+
+    /// Picks a free cell with the game's RNG, so food never lands on the
+    /// snake or other food. Returns `None` when the board is full.
+    pub(crate) fn random_free_cell(&mut self) -> Option<Position> {
+        self.build_boards();
+        let free = self.free_cells();
+        if free == 0 {
+            return None;
+        }
+
+        let mut target = self.rng.next_below(free);
+        // The arena in row order, skipping whole rows by their count of
+        // taken cells and looking up cells only in the row the target is in
+        let arena = self.arena();
+        let row_len = (arena.max.x - arena.min.x + 1) as u32;
+        let (occupied, fixtures) = (&self.occupied, &self.fixtures);
+        for y in arena.min.y..=arena.max.y {
+            let taken = occupied.count_row(&[fixtures], y, arena.min.x, arena.max.x);
+            let row_free = row_len.saturating_sub(taken as u32);
+            if target >= row_free {
+                target -= row_free;
+                continue;
+            }
+            for x in arena.min.x..=arena.max.x {
+                let cell = Position::new(x, y);
+                if occupied.contains(cell) || fixtures.contains(cell) {
+                    continue;
+                }
+                if target == 0 {
+                    return Some(cell);
+                }
+                target -= 1;
+            }
+        }
+        None
+    }
+
+    /// Whether the occupancy boards are built for this grid.
+    fn boards_fit(&self) -> bool {
+        let (width, height) = (self.level.grid_width, self.level.grid_height);
+        self.occupied.fits(width, height) && self.fixtures.fits(width, height)
+    }
+
+    /// Rebuilds the occupancy boards if they are not built for this grid,
+    /// as after a serde round trip.
+    fn build_boards(&mut self) {
+        if self.boards_fit() {
+            return;
+        }
+        let (width, height) = (self.level.grid_width, self.level.grid_height);
+        self.occupied = Occupancy::new(width, height, self.snake.iter());
+        let fixtures = (self.level.obstacles.iter()).chain(&self.foods).copied();
+        self.fixtures = Occupancy::new(width, height, fixtures);
+    }
+}
+
+fn write_cells(bytes: &mut Vec<u8>, cells: impl ExactSizeIterator<Item = Position>) {
+    bytes.extend_from_slice(&(cells.len() as u32).to_le_bytes());
+    for cell in cells {
+        bytes.extend_from_slice(&cell.x.to_le_bytes());
+        bytes.extend_from_slice(&cell.y.to_le_bytes());
+    }
+}
+
+fn read_cells(reader: &mut Reader) -> Result<Vec<Position>, SnakeError> {
+    let len = reader.u32()? as usize;
+    (0..len)
+        .map(|_| Ok(Position::new(reader.i32()?, reader.i32()?)))
+        .collect()
+}
@@ -0,0 +1,209 @@
+//! Golden test vectors.
+//!
+//! A fixed set of games covering the level features, each with its move
+//! log, the expected [`crate::GameState::state_hash`] after every tick and
+//! the final score. Ports of the engine (a JS fallback, contracts) check
+//! themselves against the JSON from [`export_test_vectors`]; this crate
+//! loads a vector file with [`parse_test_vectors`] and checks each game
+//! with [`TestVector::check`].
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use crate::bot::{self, Strategy};
+use crate::level::FoodRules;
+use crate::rules::{self, ScoringRules};
+use crate::{conformance, Level, Move, Position, Replay, SnakeError};
+
+/// A vector file: the engine the hashes were produced with and the games.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TestVectors {
+    pub engine_version: u16,
+    pub vectors: Vec<TestVector>,
+}
+
+/// One canonical game.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TestVector {
+    pub name: String,
+    pub level: Level,
+    pub seed: u64,
+    pub moves: Vec<Move>,
+    /// Lowercase hex state hash before the first tick and after each one.
+    pub tick_hashes: Vec<String>,
+    pub final_score: u64,
+}
+
+impl TestVector {
+    /// Records `replay` as a vector.
+    fn from_replay(name: &str, replay: &Replay) -> TestVector {
+        let trace = conformance::hash_trace(replay).expect("generated games replay");
+        TestVector {
+            name: String::from(name),
+            level: replay.level.clone(),
+            seed: replay.seed,
+            moves: replay.moves.clone(),
+            tick_hashes: trace.iter().map(|hash| to_hex(hash)).collect(),
+            final_score: replay.score,
+        }
+    }
+
+    /// The vector as a replay on the current engine.
+    pub fn replay(&self) -> Replay {
+        Replay {
+            engine_version: rules::ENGINE_VERSION,
+            level: self.level.clone(),
+            seed: self.seed,
+            score: self.final_score,
+            moves: self.moves.clone(),
+        }
+    }
+
+    /// Replays the vector on this engine. Fails with
+    /// [`SnakeError::HashMismatch`] if any tick's state hash differs or the
+    /// game runs longer or shorter, with [`SnakeError::ScoreMismatch`] on a
+    /// different final score, and with [`SnakeError::BadReplay`] if the
+    /// hashes are not hex or the game is not playable.
+    pub fn check(&self) -> Result<(), SnakeError> {
+        let expected = self
+            .tick_hashes
+            .iter()
+            .map(|hash| from_hex(hash).ok_or(SnakeError::BadReplay))
+            .collect::<Result<Vec<_>, SnakeError>>()?;
+        let replay = self.replay();
+        if conformance::check_trace(&replay, &expected)?.is_some() {
+            return Err(SnakeError::HashMismatch);
+        }
+        replay.play().map(|_| ())
+    }
+}
+
+/// The canonical vector set as pretty-printed JSON.
+pub fn export_test_vectors() -> String {
+    let vectors = TestVectors {
+        engine_version: rules::ENGINE_VERSION,
+        vectors: canonical_games()
+            .iter()
+            .map(|(name, replay)| TestVector::from_replay(name, replay))
+            .collect(),
+    };
+    serde_json::to_string_pretty(&vectors).expect("vectors serialize")
+}
+
+/// Parses a vector file. Fails with [`SnakeError::BadReplay`] if the JSON
+/// is malformed or was produced by another engine version.
+pub fn parse_test_vectors(json: &str) -> Result<TestVectors, SnakeError> {
+    let vectors: TestVectors = serde_json::from_str(json).map_err(|_| SnakeError::BadReplay)?;
+    if vectors.engine_version != rules::ENGINE_VERSION {
+        return Err(SnakeError::BadReplay);
+    }
+    Ok(vectors)
+}
+
+/// The games behind the vectors, each exercising one group of rules.
+fn canonical_games() -> Vec<(&'static str, Replay)> {
+    let classic = Level::classic(20, 20, false);
+    let wrap = Level {
+        max_ticks: 400,
+        ..Level::classic(16, 12, true)
+    };
+    let obstacles = Level {
+        obstacles: vec![
+            Position::new(5, 5),
+            Position::new(5, 6),
+            Position::new(5, 7),
+            Position::new(14, 7),
+            Position::new(14, 8),
+            Position::new(14, 9),
+        ],
+        ..Level::classic(20, 15, false)
+    };
+    let special_food = Level {
+        food: FoodRules {
+            count: 3,
+            golden_percent: 20,
+            poison_percent: 10,
+            lifetime_ticks: 60,
+            ..FoodRules::default()
+        },
+        scoring: ScoringRules {
+            combo_window_ticks: 10,
+            ..ScoringRules::default()
+        },
+        ..Level::classic(20, 20, false)
+    };
+    let lives_timed = Level {
+        lives: 3,
+        max_ticks: 500,
+        scoring: ScoringRules {
+            survival_points_per_tick: 1,
+            ..ScoringRules::default()
+        },
+        ..Level::classic(20, 20, false)
+    };
+    let shrinking = Level {
+        shrink_interval_ticks: 40,
+        ..Level::classic(20, 20, false)
+    };
+    // Never turning, the snake laps its row long past the stall limit while
+    // expiring food respawns until some lands in its path
+    let wrap_expiring_food = Level {
+        food: FoodRules {
+            lifetime_ticks: 8,
+            ..FoodRules::default()
+        },
+        scoring: ScoringRules {
+            growth_per_food: 2,
+            ..ScoringRules::default()
+        },
+        ..Level::classic(10, 8, true)
+    };
+    let games = [
+        ("classic", classic, 1, 200, Strategy::AStar),
+        ("wrap", wrap, 2, 150, Strategy::AStar),
+        ("obstacles", obstacles, 3, 100, Strategy::Greedy),
+        ("special_food", special_food, 4, 300, Strategy::AStar),
+        ("lives_timed", lives_timed, 5, 200, Strategy::AStar),
+        ("shrinking", shrinking, 6, 100, Strategy::AStar),
+        // A target of 0 never turns
+        (
+            "wrap_expiring_food",
+            wrap_expiring_food,
+            69,
+            0,
+            Strategy::AStar,
+        ),
+        (
+            "hamiltonian",
+            Level::classic(8, 8, false),
+            7,
+            u64::MAX,
+            Strategy::Hamiltonian,
+        ),
+    ];
+    games
+        .into_iter()
+        .map(|(name, level, seed, target, strategy)| {
+            let replay = bot::generate_game_on(level, seed, target, strategy)
+                .expect("canonical levels are playable");
+            (name, replay)
+        })
+        .collect()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Option<[u8; 32]> {
+    if hex.len() != 64 {
+        return None;
+    }
+    let mut bytes = [0; 32];
+    let mut digits = hex.chars().map(|digit| digit.to_digit(16));
+    for byte in &mut bytes {
+        *byte = (digits.next()?? * 16 + digits.next()??) as u8;
+    }
+    Some(bytes)
+}
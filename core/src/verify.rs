@@ -0,0 +1,361 @@
+//! Move-by-move replay verification, run by the SP1 program.
+
+use alloc::vec::Vec;
+use core::cell::Cell;
+
+use crate::inputs::{ChunkPublicInputs, SnakeGamePublicInputs};
+use crate::versus::PLAYERS;
+use crate::{
+    hash, rules, GameState, Level, Move, Position, Replay, SnakeError, StepOutcome, VersusState,
+};
+
+/// Replays `moves` on `level` until the game ends, spawning food from
+/// `seed` exactly as the original game did. `food_positions` is the food
+/// the player saw, in spawn order: the food on the board at the start, then
+/// each replacement for a food eaten. It must match the seeded spawns, so a
+/// proof shows the committed seed was really used. They may be given as
+/// [`Position`]s or as the `(x, y)` cells of the private inputs, which are
+/// read in place.
+///
+/// Every tick goes through the normal engine, so walls, self collision,
+/// growth and scoring are enforced exactly as in the browser. Returns the
+/// final state, or [`SnakeError::BadReplay`] if the inputs do not describe a
+/// legal game: an unplayable level, food positions that differ from the
+/// seeded spawns, a move log that the engine would not have recorded or
+/// that runs past the level's tick budget, or a game that would never end
+/// (see [`crate::rules::is_stalled`]).
+pub fn replay_moves<P: Copy + Into<Position>>(
+    level: &Level,
+    seed: u64,
+    food_positions: &[P],
+    moves: &[Move],
+) -> Result<GameState, SnakeError> {
+    replay_moves_with(level, seed, food_positions, moves, |_, _| {})
+}
+
+/// [`replay_moves`], calling `observe` with the state and outcome after
+/// every tick, e.g. to track [`crate::achievements`].
+pub fn replay_moves_with<P: Copy + Into<Position>>(
+    level: &Level,
+    seed: u64,
+    food_positions: &[P],
+    moves: &[Move],
+    observe: impl FnMut(&GameState, StepOutcome),
+) -> Result<GameState, SnakeError> {
+    let mut state =
+        GameState::from_level(level.clone(), seed).map_err(|_| SnakeError::BadReplay)?;
+    let initial_count = state.foods().len().min(food_positions.len());
+    let (initial_foods, mut food_positions) = food_positions.split_at(initial_count);
+    let initial_foods = initial_foods.iter().map(|&food| food.into());
+    if !state.foods().iter().copied().eq(initial_foods) {
+        return Err(SnakeError::BadReplay);
+    }
+
+    let bad_food = Cell::new(false);
+    play_to_end(&mut state, moves, None, &bad_food, observe, |state| {
+        let spawned = state.random_free_cell();
+        match (spawned, food_positions.split_first()) {
+            (Some(food), Some((&seen, rest))) if food == seen.into() => {
+                food_positions = rest;
+                Some(food)
+            }
+            // A full board spawns nothing; unused positions fail below
+            (None, _) => None,
+            _ => {
+                bad_food.set(true);
+                None
+            }
+        }
+    })?;
+    if !food_positions.is_empty() {
+        return Err(SnakeError::BadReplay);
+    }
+    Ok(state)
+}
+
+/// Plays `moves` on `state` until the game ends, or until tick `until` if
+/// given, placing food with `next_food` and passing each tick to `observe`,
+/// and fails with [`SnakeError::BadReplay`] as soon as `bad_food` is set or
+/// the move log is not legal, see [`replay_moves`]. `moves` are the moves
+/// made from `state` on; those already in its log are left alone.
+fn play_to_end(
+    state: &mut GameState,
+    moves: &[Move],
+    until: Option<u32>,
+    bad_food: &Cell<bool>,
+    mut observe: impl FnMut(&GameState, StepOutcome),
+    mut next_food: impl FnMut(&mut GameState) -> Option<Position>,
+) -> Result<(), SnakeError> {
+    let max_ticks = state.level().max_ticks;
+    if max_ticks > 0 && moves.last().is_some_and(|mv| mv.tick >= max_ticks) {
+        return Err(SnakeError::BadReplay);
+    }
+
+    let logged = state.moves().len();
+    let mut next_move = 0;
+    let mut idle_ticks = 0u32;
+    while !state.is_game_over() && until.is_none_or(|until| state.ticks() < until) {
+        while let Some(mv) = moves.get(next_move) {
+            if mv.tick != state.ticks() {
+                break;
+            }
+            state.replay_move(mv.direction)?;
+            next_move += 1;
+            idle_ticks = 0;
+        }
+        // An illegal move can end the game before the tick runs
+        if state.is_game_over() {
+            break;
+        }
+
+        let outcome = state.step_with_food(&mut next_food)?;
+        if bad_food.get() {
+            return Err(SnakeError::BadReplay);
+        }
+        observe(state, outcome);
+        idle_ticks = match outcome {
+            StepOutcome::AteFood | StepOutcome::LostLife => 0,
+            StepOutcome::Paused | StepOutcome::CountingDown => idle_ticks,
+            _ => idle_ticks.saturating_add(1),
+        };
+        // A chunk cannot tell whether later chunks turn, but it cannot run
+        // forever either
+        let last_moves = until.is_none() && next_move == moves.len();
+        if !state.is_game_over() && last_moves && state.is_stalled(idle_ticks) {
+            return Err(SnakeError::BadReplay);
+        }
+    }
+
+    // Moves left over, out of order or redundant never reach the engine's
+    // own log; the input must match it exactly
+    if state.moves()[logged..] != *moves {
+        return Err(SnakeError::BadReplay);
+    }
+    Ok(())
+}
+
+/// What a verified game proves: everything the SP1 program commits except
+/// the player address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VerifiedOutcome {
+    pub game_state_hash: [u8; 32],
+    pub score: u64,
+    pub snake_length: u32,
+    pub ticks: u32,
+    pub grid_width: u32,
+    pub grid_height: u32,
+    pub wrap_walls: bool,
+    pub level_hash: [u8; 32],
+    pub seed: u64,
+    pub config_hash: [u8; 32],
+    pub tick_chain: [u8; 32],
+}
+
+impl VerifiedOutcome {
+    /// The outcome of a game that ended in `state`.
+    pub fn from_state(state: &GameState) -> VerifiedOutcome {
+        let level = state.level();
+        VerifiedOutcome {
+            game_state_hash: state.state_hash(),
+            score: state.score(),
+            snake_length: state.snake().len() as u32,
+            ticks: state.ticks(),
+            grid_width: level.grid_width as u32,
+            grid_height: level.grid_height as u32,
+            wrap_walls: level.wrap_walls,
+            level_hash: level.hash(),
+            seed: state.seed(),
+            config_hash: hash::config_hash(level),
+            tick_chain: state.tick_chain(),
+        }
+    }
+
+    /// The values the SP1 program commits for this game, crediting
+    /// `player` with the nullifier of `nonce`.
+    pub fn public_inputs(&self, player: [u8; 20], nonce: u64) -> SnakeGamePublicInputs {
+        SnakeGamePublicInputs {
+            game_state_hash: self.game_state_hash,
+            score: self.score,
+            snake_length: self.snake_length,
+            grid_width: self.grid_width,
+            grid_height: self.grid_height,
+            wrap_walls: self.wrap_walls,
+            level_hash: self.level_hash,
+            seed: self.seed,
+            config_hash: self.config_hash,
+            player,
+            challenge_day: 0,
+            challenge_salt_hash: [0; 32],
+            nullifier: hash::nullifier(&player, self.seed, nonce),
+            player_key: [0; 32],
+            tick_chain: self.tick_chain,
+        }
+    }
+}
+
+/// Checks a `.skr` replay natively with the same rules the SP1 program
+/// applies, for servers screening submissions before proving them. Food is
+/// spawned from the replay's seed. Fails with [`SnakeError::BadReplay`] if
+/// it was recorded by another engine version or is not a legal game (see
+/// [`replay_moves`]), and with [`SnakeError::ScoreMismatch`] if it does not
+/// reach the recorded score.
+pub fn verify_replay(replay: &Replay) -> Result<VerifiedOutcome, SnakeError> {
+    verify_to_end(replay, None)
+}
+
+/// [`verify_replay`] for replays from anyone: fails with
+/// [`SnakeError::BadReplay`] as well if the game is not over after
+/// `max_ticks` ticks, so a hostile replay costs at most that many.
+pub fn verify_replay_within(
+    replay: &Replay,
+    max_ticks: u32,
+) -> Result<VerifiedOutcome, SnakeError> {
+    verify_to_end(replay, Some(max_ticks))
+}
+
+fn verify_to_end(replay: &Replay, until: Option<u32>) -> Result<VerifiedOutcome, SnakeError> {
+    if replay.engine_version != rules::ENGINE_VERSION {
+        return Err(SnakeError::BadReplay);
+    }
+    let mut state = GameState::from_level(replay.level.clone(), replay.seed)
+        .map_err(|_| SnakeError::BadReplay)?;
+    play_to_end(
+        &mut state,
+        &replay.moves,
+        until,
+        &Cell::new(false),
+        |_, _| {},
+        GameState::random_free_cell,
+    )?;
+    if !state.is_game_over() {
+        return Err(SnakeError::BadReplay);
+    }
+    if state.score() != replay.score {
+        return Err(SnakeError::ScoreMismatch);
+    }
+    Ok(VerifiedOutcome::from_state(&state))
+}
+
+/// Plays one chunk of a game too long to prove in one go: at most `ticks`
+/// ticks of `state`, applying `moves`, the moves made during those ticks,
+/// and spawning food from the state's seed as [`verify_replay`] does.
+/// Stops early if the game ends. A chain of chunks, each starting from the
+/// [`hash::snapshot_hash`] the previous one ended on, plays exactly the
+/// game [`verify_replay`] would, except that a game that would never end
+/// is only caught by never reaching game over. Fails with
+/// [`SnakeError::BadReplay`] if `moves` are not exactly the moves the
+/// engine records in the chunk, or if `state` is paused, which would never
+/// tick.
+pub fn replay_chunk(state: &mut GameState, moves: &[Move], ticks: u32) -> Result<(), SnakeError> {
+    if state.is_paused() {
+        return Err(SnakeError::BadReplay);
+    }
+    let until = state.ticks().saturating_add(ticks);
+    play_to_end(
+        state,
+        moves,
+        Some(until),
+        &Cell::new(false),
+        |_, _| {},
+        GameState::random_free_cell,
+    )
+}
+
+/// Checks that `chunks`, the values committed by a game's chunk proofs in
+/// order, link the seeded start of the game saved in `final_snapshot` to
+/// that finished game, and returns its outcome. Fails with
+/// [`SnakeError::BadReplay`] if the snapshot is corrupt or not game over,
+/// or a chunk does not start where the previous one ended.
+pub fn verify_chain(
+    chunks: &[ChunkPublicInputs],
+    final_snapshot: &[u8],
+) -> Result<VerifiedOutcome, SnakeError> {
+    let last = GameState::from_bytes(final_snapshot).map_err(|_| SnakeError::BadReplay)?;
+    let first = GameState::from_level(last.level().clone(), last.seed())
+        .map_err(|_| SnakeError::BadReplay)?;
+    let mut hash = hash::snapshot_hash(&first);
+    for chunk in chunks {
+        if chunk.input_hash != hash {
+            return Err(SnakeError::BadReplay);
+        }
+        hash = chunk.output_hash;
+    }
+    if !last.is_game_over() || hash != hash::snapshot_hash(&last) {
+        return Err(SnakeError::BadReplay);
+    }
+    Ok(VerifiedOutcome::from_state(&last))
+}
+
+/// Runs [`verify_replay`] on every replay, spread over the available
+/// cores, and returns each result in input order.
+#[cfg(feature = "parallel")]
+pub fn verify_replays_batch(replays: &[Replay]) -> Vec<Result<VerifiedOutcome, SnakeError>> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = replays.len().div_ceil(threads).max(1);
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = replays
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(|| chunk.iter().map(verify_replay).collect::<Vec<_>>()))
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    })
+}
+
+/// The versus counterpart of [`replay_moves`]: replays both players' move
+/// logs on `level` until the match ends. `food_positions` lists every food
+/// in spawn order across both players, as in a single-player game. Fails
+/// with [`SnakeError::BadReplay`] on a level that cannot host a match, food
+/// that differs from the seeded spawns, a move log the engine would not
+/// have recorded or that runs past the tick budget, or a match that would
+/// never end.
+pub fn replay_versus(
+    level: &Level,
+    seed: u64,
+    food_positions: &[Position],
+    moves: [&[Move]; PLAYERS],
+) -> Result<VersusState, SnakeError> {
+    let mut state =
+        VersusState::from_level(level.clone(), seed).map_err(|_| SnakeError::BadReplay)?;
+    let past_budget = |log: &[Move]| log.last().is_some_and(|mv| mv.tick >= level.max_ticks);
+    if level.max_ticks > 0 && moves.iter().any(|log| past_budget(log)) {
+        return Err(SnakeError::BadReplay);
+    }
+    let initial_count = state.foods().len().min(food_positions.len());
+    let (initial_foods, mut food_positions) = food_positions.split_at(initial_count);
+    if state.foods() != initial_foods {
+        return Err(SnakeError::BadReplay);
+    }
+
+    let mut bad_food = false;
+    state.play_out(moves, |state| {
+        let spawned = state.random_free_cell();
+        match (spawned, food_positions.split_first()) {
+            (Some(food), Some((&seen, rest))) if food == seen => {
+                food_positions = rest;
+                Some(food)
+            }
+            (None, _) => None,
+            _ => {
+                bad_food = true;
+                None
+            }
+        }
+    })?;
+    if bad_food || !food_positions.is_empty() {
+        return Err(SnakeError::BadReplay);
+    }
+    Ok(state)
+}
+
+/// Convenience wrapper for the verifier's `(x, y)` tuples.
+pub fn to_positions(cells: &[(u32, u32)]) -> Vec<Position> {
+    cells.iter().map(|&cell| cell.into()).collect()
+}
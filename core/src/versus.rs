@@ -0,0 +1,489 @@
+//! Two-snake versus mode.
+//!
+//! Two snakes share one level and its seeded food. Both move on every tick,
+//! so collisions are resolved against where the other snake ends up: a head
+//! entering either snake's body dies, and two heads meeting on one cell, or
+//! swapping places, both die. The first death ends the match. Combos and
+//! extra lives are single-player rules and do not apply here; the illegal
+//! move policy is always [`crate::rules::IllegalMovePolicy::Ignore`].
+
+use alloc::vec::Vec;
+use core::array;
+use core::cmp::Ordering;
+
+use crate::rules::{self, ScoringMode};
+use crate::state::{FOOD_KIND_STREAM, FOOD_STREAM};
+use crate::{
+    hash, Arena, Body, Direction, FoodKind, Level, Move, Position, Rng, SnakeError, StepOutcome,
+};
+
+/// Number of snakes in a versus match. Player 0 starts at the level's
+/// start, player 1 at [`Level::rival_start`].
+pub const PLAYERS: usize = 2;
+
+/// One player's snake and tally.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Contender {
+    snake: Body,
+    direction: Direction,
+    score: u64,
+    /// Segments still to be added from food already eaten.
+    pending_growth: u32,
+    alive: bool,
+    moves: Vec<Move>,
+}
+
+impl Contender {
+    fn new(snake: Vec<Position>, direction: Direction) -> Contender {
+        Contender {
+            snake: snake.into(),
+            direction,
+            score: 0,
+            pending_growth: 0,
+            alive: true,
+            moves: Vec::new(),
+        }
+    }
+
+    /// The head; a corrupt, empty snake is caught by
+    /// [`VersusState::step`] before this is used.
+    fn head(&self) -> Position {
+        self.snake.head().unwrap_or(Position::new(0, 0))
+    }
+}
+
+/// A versus match in progress. Getters taking a player index panic on
+/// anything not below [`PLAYERS`].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VersusState {
+    players: [Contender; PLAYERS],
+    /// Food on the board, in spawn slots: an eaten item is replaced in place.
+    foods: Vec<Position>,
+    /// Kind of each food, by slot.
+    food_kinds: Vec<FoodKind>,
+    level: Level,
+    seed: u64,
+    rng: Rng,
+    kind_rng: Rng,
+    ticks: u32,
+    game_over: bool,
+}
+
+impl VersusState {
+    /// Starts a match on `level`, spawning food from `seed` as a
+    /// single-player game would. Fails with [`SnakeError::BadLayout`] if the
+    /// level is not playable or the second snake does not fit clear of the
+    /// first, the walls and the first food.
+    pub fn from_level(level: Level, seed: u64) -> Result<VersusState, SnakeError> {
+        level.validate()?;
+        let first = level.start_snake();
+        let (rival_head, rival_direction) = level.rival_start();
+        let rival = level.snake_at(rival_head, rival_direction);
+        let rival_valid = rival.iter().enumerate().all(|(i, &segment)| {
+            level.in_bounds(segment)
+                && !rival[..i].contains(&segment)
+                && !first.contains(&segment)
+                && !level.obstacles.contains(&segment)
+                && level.food.first != Some(segment)
+        });
+        if !rival_valid {
+            return Err(SnakeError::BadLayout);
+        }
+
+        let mut state = VersusState {
+            players: [
+                Contender::new(first, level.start_direction),
+                Contender::new(rival, rival_direction),
+            ],
+            foods: Vec::new(),
+            food_kinds: Vec::new(),
+            rng: Rng::new(seed, FOOD_STREAM),
+            kind_rng: Rng::new(seed, FOOD_KIND_STREAM),
+            level,
+            seed,
+            ticks: 0,
+            game_over: false,
+        };
+        if let Some(first) = state.level.food.first {
+            state.place_food(0, first);
+        }
+        while state.foods.len() < state.level.food.count as usize {
+            match state.random_free_cell() {
+                Some(food) => state.place_food(state.foods.len(), food),
+                None => break,
+            }
+        }
+        state.game_over = state.foods.is_empty();
+        Ok(state)
+    }
+
+    /// Puts a food at `pos` in `slot`, rolling its kind from the seed.
+    fn place_food(&mut self, slot: usize, pos: Position) {
+        let kind = self.level.food.kind_for_roll(self.kind_rng.next_below(100));
+        self.foods.insert(slot, pos);
+        self.food_kinds.insert(slot, kind);
+    }
+
+    fn remove_food(&mut self, slot: usize) {
+        self.foods.remove(slot);
+        self.food_kinds.remove(slot);
+    }
+
+    /// The board the match is played on.
+    pub fn level(&self) -> &Level {
+        &self.level
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Number of ticks simulated so far.
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    /// `player`'s snake, head first.
+    pub fn snake(&self, player: usize) -> &Body {
+        &self.players[player].snake
+    }
+
+    pub fn direction(&self, player: usize) -> Direction {
+        self.players[player].direction
+    }
+
+    pub fn score(&self, player: usize) -> u64 {
+        self.players[player].score
+    }
+
+    /// Whether `player`'s snake has not crashed.
+    pub fn is_alive(&self, player: usize) -> bool {
+        self.players[player].alive
+    }
+
+    /// Every direction change `player` made, in order.
+    pub fn moves(&self, player: usize) -> &[Move] {
+        &self.players[player].moves
+    }
+
+    /// Food on the board, in spawn-slot order.
+    pub fn foods(&self) -> &[Position] {
+        &self.foods
+    }
+
+    /// Kind of each item in [`VersusState::foods`].
+    pub fn food_kinds(&self) -> &[FoodKind] {
+        &self.food_kinds
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.game_over
+    }
+
+    /// The winning player once the match is over: the only snake still
+    /// alive, or else the higher score. `None` while playing and on a draw.
+    pub fn winner(&self) -> Option<usize> {
+        if !self.game_over {
+            return None;
+        }
+        let [first, second] = &self.players;
+        match (first.alive, second.alive) {
+            (true, false) => Some(0),
+            (false, true) => Some(1),
+            _ => match first.score.cmp(&second.score) {
+                Ordering::Greater => Some(0),
+                Ordering::Less => Some(1),
+                Ordering::Equal => None,
+            },
+        }
+    }
+
+    /// Commitment to the current match, as committed by the versus prover:
+    /// the same hash as [`crate::GameState::state_hash`].
+    pub fn state_hash(&self) -> [u8; 32] {
+        hash::versus_state_hash(self)
+    }
+
+    /// The playable area at the current tick, see [`Level::arena`].
+    pub fn arena(&self) -> Arena {
+        self.level.arena(self.ticks)
+    }
+
+    /// Whether `pos` is inside the current [`VersusState::arena`].
+    pub fn in_bounds(&self, pos: Position) -> bool {
+        self.arena().contains(pos)
+    }
+
+    /// Whether a new food may appear at `pos`: a cell in the arena clear of
+    /// both snakes, obstacles and other food.
+    pub fn can_spawn_food(&self, pos: Position) -> bool {
+        self.in_bounds(pos)
+            && !self.players.iter().any(|player| player.snake.contains(pos))
+            && !self.level.obstacles.contains(&pos)
+            && !self.foods.contains(&pos)
+    }
+
+    /// Turns `player`'s snake for the next tick. Fails with
+    /// [`SnakeError::UnknownPlayer`] for a bad index and with
+    /// [`SnakeError::InvalidMove`] on a turn back into the neck, leaving
+    /// the direction unchanged; repeating the current direction does
+    /// nothing.
+    pub fn change_direction(
+        &mut self,
+        player: usize,
+        direction: Direction,
+    ) -> Result<(), SnakeError> {
+        if self.game_over {
+            return Err(SnakeError::GameOver);
+        }
+        let contender = self
+            .players
+            .get_mut(player)
+            .ok_or(SnakeError::UnknownPlayer)?;
+        if contender.snake.get(1) == Some(self.level.neighbour(contender.head(), direction)) {
+            return Err(SnakeError::InvalidMove);
+        }
+        if direction != contender.direction {
+            contender.direction = direction;
+            contender.moves.push(Move {
+                tick: self.ticks,
+                direction,
+            });
+        }
+        Ok(())
+    }
+
+    /// Plays both recorded move logs to the end of the match, spawning food
+    /// with `next_food`. A move the engine refuses, a log that does not
+    /// match the one the engine records, or a match that would never end
+    /// once both logs are exhausted (see [`rules::is_stalled`]) fails with
+    /// [`SnakeError::BadReplay`].
+    pub(crate) fn play_out(
+        &mut self,
+        moves: [&[Move]; PLAYERS],
+        mut next_food: impl FnMut(&mut VersusState) -> Option<Position>,
+    ) -> Result<(), SnakeError> {
+        let mut next_move = [0; PLAYERS];
+        let mut idle_ticks = 0u32;
+        while !self.game_over {
+            for (player, log) in moves.iter().enumerate() {
+                while let Some(mv) = log.get(next_move[player]) {
+                    if mv.tick != self.ticks {
+                        break;
+                    }
+                    self.change_direction(player, mv.direction)
+                        .map_err(|_| SnakeError::BadReplay)?;
+                    next_move[player] += 1;
+                    idle_ticks = 0;
+                }
+            }
+
+            let outcomes = self.step_with_food(&mut next_food)?;
+            if outcomes.contains(&StepOutcome::AteFood) {
+                idle_ticks = 0;
+            } else {
+                idle_ticks = idle_ticks.saturating_add(1);
+            }
+            let exhausted = (0..PLAYERS).all(|player| next_move[player] == moves[player].len());
+            if !self.game_over
+                && exhausted
+                && self.level.max_ticks == 0
+                && self.level.shrink_interval_ticks == 0
+                && rules::is_stalled(idle_ticks, self.level.grid_width, self.level.grid_height)
+            {
+                return Err(SnakeError::BadReplay);
+            }
+        }
+
+        // Leftover, out-of-order or redundant moves never reach the log
+        if (0..PLAYERS).any(|player| self.players[player].moves != moves[player]) {
+            return Err(SnakeError::BadReplay);
+        }
+        Ok(())
+    }
+
+    /// Advances both snakes by one tick and returns each player's outcome,
+    /// [`StepOutcome::GameOver`] for a snake that crashed. Fails with
+    /// [`SnakeError::GameOver`] if the match has already ended.
+    pub fn step(&mut self) -> Result<[StepOutcome; PLAYERS], SnakeError> {
+        self.step_with_food(VersusState::random_free_cell)
+    }
+
+    /// Like [`VersusState::step`], asking `next_food` where to place the
+    /// replacement whenever a food is eaten or caught outside a shrinking
+    /// arena. Returning `None` leaves the slot empty; the match ends once no
+    /// food is left.
+    pub fn step_with_food(
+        &mut self,
+        mut next_food: impl FnMut(&mut VersusState) -> Option<Position>,
+    ) -> Result<[StepOutcome; PLAYERS], SnakeError> {
+        if self.game_over {
+            return Err(SnakeError::GameOver);
+        }
+        let corrupt = self.players.iter().any(|player| player.snake.is_empty())
+            || self.food_kinds.len() != self.foods.len();
+        if corrupt {
+            return Err(SnakeError::CorruptState);
+        }
+        // A match as long as the tick counter allows ends there
+        let Some(ticks) = self.ticks.checked_add(1) else {
+            self.game_over = true;
+            return Ok([StepOutcome::GameOver; PLAYERS]);
+        };
+        self.ticks = ticks;
+        if self.level.shrink_interval_ticks > 0 {
+            let mut slot = 0;
+            while slot < self.foods.len() {
+                if self.in_bounds(self.foods[slot]) {
+                    slot += 1;
+                    continue;
+                }
+                self.remove_food(slot);
+                if let Some(food) = next_food(self) {
+                    self.place_food(slot, food);
+                    slot += 1;
+                }
+            }
+        }
+
+        // Both snakes move at once, so every crash is judged against where
+        // the other one ends up
+        let scoring = &self.level.scoring;
+        let heads: [Position; PLAYERS] = array::from_fn(|i| {
+            let player = &self.players[i];
+            self.level.neighbour(player.head(), player.direction)
+        });
+        let kinds: [Option<FoodKind>; PLAYERS] = array::from_fn(|i| {
+            let slot = self.foods.iter().position(|&food| food == heads[i]);
+            slot.and_then(|slot| self.food_kinds.get(slot).copied())
+        });
+        let growth: [u32; PLAYERS] = array::from_fn(|i| {
+            let pending = self.players[i].pending_growth;
+            match kinds[i] {
+                Some(FoodKind::Normal | FoodKind::Golden) => {
+                    pending.saturating_add(scoring.growth_per_food)
+                }
+                _ => pending,
+            }
+        });
+        let body_len = |i: usize| {
+            let len = self.players[i].snake.len();
+            match growth[i] {
+                0 => len.saturating_sub(1),
+                _ => len,
+            }
+        };
+        let body = |i: usize| self.players[i].snake.iter().take(body_len(i));
+        let crashed: [bool; PLAYERS] = array::from_fn(|i| {
+            let head = heads[i];
+            let starves = kinds[i] == Some(FoodKind::Poison)
+                && self.players[i].snake.len() == 1
+                && growth[i] == 0;
+            !self.in_bounds(head)
+                || self.level.obstacles.contains(&head)
+                || body(i).any(|segment| !self.in_bounds(segment))
+                || (0..PLAYERS).any(|j| {
+                    self.players[j].snake.contains_in(body_len(j), head)
+                        || (j != i && heads[j] == head)
+                })
+                || starves
+        });
+
+        let mut outcomes = [StepOutcome::GameOver; PLAYERS];
+        for i in 0..PLAYERS {
+            if crashed[i] {
+                self.players[i].alive = false;
+                continue;
+            }
+            let scoring = &self.level.scoring;
+            let player = &mut self.players[i];
+            player.snake.push_front(heads[i]);
+            if growth[i] > 0 {
+                player.pending_growth = growth[i].saturating_sub(1);
+            } else {
+                player.snake.pop_back();
+            }
+            player.score = player
+                .score
+                .saturating_add(u64::from(scoring.survival_points_per_tick));
+            outcomes[i] = StepOutcome::Moved;
+            if let Some(kind) = kinds[i] {
+                self.eat(i, kind, &mut next_food);
+                outcomes[i] = StepOutcome::AteFood;
+            }
+        }
+
+        let max_ticks = self.level.max_ticks;
+        self.game_over = crashed.contains(&true)
+            || self.foods.is_empty()
+            || (max_ticks > 0 && self.ticks >= max_ticks);
+        Ok(outcomes)
+    }
+
+    /// Scores the food under `player`'s head and replaces it.
+    fn eat(
+        &mut self,
+        player: usize,
+        kind: FoodKind,
+        next_food: &mut impl FnMut(&mut VersusState) -> Option<Position>,
+    ) {
+        let scoring = &self.level.scoring;
+        let contender = &mut self.players[player];
+        // Slots may have moved if the other player's food was not replaced
+        let Some(slot) = self.foods.iter().position(|&food| food == contender.head()) else {
+            return;
+        };
+        let points = match kind {
+            FoodKind::Poison => {
+                contender.snake.pop_back();
+                0
+            }
+            _ if scoring.mode == ScoringMode::Survival => 0,
+            FoodKind::Normal => scoring.points_per_food,
+            FoodKind::Golden => scoring.points_per_golden_food,
+        };
+        contender.score = contender.score.saturating_add(u64::from(points));
+        self.remove_food(slot);
+        if let Some(food) = next_food(self) {
+            self.place_food(slot, food);
+        }
+    }
+
+    /// Number of cells where food could still spawn.
+    pub fn free_cells(&self) -> u32 {
+        let arena = self.arena();
+        let taken = (self.players.iter())
+            .flat_map(|player| player.snake.iter())
+            .chain(self.foods.iter().copied())
+            .chain(self.level.obstacles.iter().copied())
+            .filter(|&cell| arena.contains(cell))
+            .count();
+        arena.cells().saturating_sub(taken as u32)
+    }
+
+    /// Picks a free cell with the match's RNG, so food never lands on a
+    /// snake or other food. Returns `None` when the board is full.
+    pub(crate) fn random_free_cell(&mut self) -> Option<Position> {
+        let width = self.level.grid_width;
+        let cells = width.saturating_mul(self.level.grid_height).max(0) as u32;
+        let free = self.free_cells();
+        if free == 0 {
+            return None;
+        }
+
+        let mut target = self.rng.next_below(free);
+        for index in 0..cells as i32 {
+            let cell = Position::new(index % width, index / width);
+            if !self.can_spawn_food(cell) {
+                continue;
+            }
+            if target == 0 {
+                return Some(cell);
+            }
+            target -= 1;
+        }
+        None
+    }
+}
@@ -0,0 +1,50 @@
+//! Snake Achievements Verifier
+//! This is an SP1 program that proves the badges a Snake game earned. It
+//! reads a recorded game from stdin, replays the moves with the shared
+//! `snake-core` engine while tracking every achievement tick by tick, and
+//! commits only the achievement flags, config hash and player as
+//! ABI-encoded public values; the moves and score stay private.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use snake_core::achievements::AchievementTracker;
+use snake_core::inputs::{AchievementPublicInputs, SnakeGamePrivateInputs};
+use snake_core::{abi, hash, moves, verify, GameState, SnakeError};
+
+// Replays the game and returns the values to commit, or an error if the
+// recording is not a legal game
+pub fn snake_achievements_verifier(
+    private_inputs: &SnakeGamePrivateInputs,
+) -> Result<AchievementPublicInputs, SnakeError> {
+    let moves = moves::decompress_moves(&private_inputs.game_moves)?;
+    let initial = GameState::from_level(private_inputs.level.clone(), private_inputs.seed)
+        .map_err(|_| SnakeError::BadReplay)?;
+
+    // Badges are judged on the same ticks the engine plays, so a flag can
+    // only be set by a legal game
+    let mut tracker = AchievementTracker::new(&initial);
+    let final_state = verify::replay_moves_with(
+        &private_inputs.level,
+        private_inputs.seed,
+        &private_inputs.food_positions,
+        &moves,
+        |state, outcome| tracker.observe(state, outcome),
+    )?;
+
+    Ok(AchievementPublicInputs {
+        achievements: tracker.finish(&final_state),
+        config_hash: hash::config_hash(&private_inputs.level),
+        player: private_inputs.player,
+    })
+}
+
+pub fn main() {
+    let private_inputs = sp1_zkvm::io::read::<SnakeGamePrivateInputs>();
+
+    // An illegal game aborts execution, so no proof can be produced for it
+    let public_inputs = snake_achievements_verifier(&private_inputs)
+        .unwrap_or_else(|err| panic!("invalid game replay: {err}"));
+
+    sp1_zkvm::io::commit_slice(&abi::encode_achievement_inputs(&public_inputs));
+}
@@ -0,0 +1,55 @@
+//! Snake Aggregate Verifier
+//! This is an SP1 program that settles many proven Snake games with one
+//! proof, e.g. a whole tournament bracket. It reads the public values of
+//! each game proof from stdin, verifies every proof recursively against the
+//! single-game program's verification key and commits each game's player,
+//! score, config hash, nullifier, seed and signing key as ABI-encoded
+//! public values. A game whose nullifier repeats is rejected, so one proven
+//! game cannot be settled twice.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use std::collections::BTreeSet;
+
+use sha2::{Digest, Sha256};
+use snake_core::inputs::{AggregatePrivateInputs, AggregatePublicInputs};
+use snake_core::{abi, SnakeError};
+
+// Decodes the games and returns the values to commit, or an error if a
+// game appears twice. The proofs themselves are checked in `main`
+pub fn snake_aggregate(
+    private_inputs: &AggregatePrivateInputs,
+) -> Result<AggregatePublicInputs, SnakeError> {
+    let games = private_inputs
+        .public_values
+        .iter()
+        .map(|values| abi::decode_public_inputs(values).map_err(|_| SnakeError::BadInput))
+        .collect::<Result<Vec<_>, _>>()?;
+    // The same proof listed again, or the game proven again, has the same
+    // nullifier
+    let mut nullifiers = BTreeSet::new();
+    if !games.iter().all(|game| nullifiers.insert(game.nullifier)) {
+        return Err(SnakeError::BadInput);
+    }
+    Ok(AggregatePublicInputs::from_games(
+        private_inputs.vkey_digest,
+        &games,
+    ))
+}
+
+pub fn main() {
+    let private_inputs = sp1_zkvm::io::read::<AggregatePrivateInputs>();
+
+    // Each game is only counted if a proof of exactly these public values
+    // was written to stdin by the host; a missing one aborts execution
+    for values in &private_inputs.public_values {
+        let digest: [u8; 32] = Sha256::digest(values).into();
+        sp1_zkvm::lib::verify::verify_sp1_proof(&private_inputs.vkey_digest, &digest);
+    }
+
+    let public_inputs =
+        snake_aggregate(&private_inputs).unwrap_or_else(|err| panic!("invalid games: {err}"));
+
+    sp1_zkvm::io::commit_slice(&abi::encode_aggregate_inputs(&public_inputs));
+}
@@ -0,0 +1,103 @@
+//! Proving cost across game lengths.
+//!
+//! Plays one bot game per target score with `snake_core::bot::generate_game`,
+//! executes and proves each, verifies the proof and writes a CSV row of
+//! what it took:
+//!
+//! ```text
+//! target_score,score,ticks,cycles,prove_ms,proof_bytes,verify_ms
+//! ```
+//!
+//! The games depend only on the seed and the targets, so two runs on
+//! different engine revisions prove the same replays and their rows compare
+//! directly.
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use snake_core::bot;
+use snake_core::inputs::SnakeGamePrivateInputs;
+use snake_prover::prover::{ProofMode, ProverBackend, ProverConfig, SnakeProver};
+
+#[derive(Parser)]
+#[command(about = "Measure SP1 proving cost of bot games of increasing length")]
+struct Cli {
+    /// Scores the bot plays to, one game each, comma separated.
+    #[arg(long, value_delimiter = ',', default_values_t = [1, 10, 25, 50, 100, 200])]
+    scores: Vec<u64>,
+    /// Seed every game is played on.
+    #[arg(long, default_value_t = 1)]
+    seed: u64,
+    #[arg(long, value_enum, default_value_t = ProofMode::Core)]
+    mode: ProofMode,
+    /// CSV file to write, standard output if unset.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// JSON prover config, see `ProverConfig`; without it the backend and
+    /// network credentials come from `SP1_PROVER` and `NETWORK_*`.
+    #[arg(long)]
+    prover_config: Option<PathBuf>,
+    /// Prove on this backend, overriding the configured one.
+    #[arg(long, value_enum)]
+    backend: Option<ProverBackend>,
+}
+
+fn main() -> Result<()> {
+    sp1_sdk::utils::setup_logger();
+    let cli = Cli::parse();
+    let mut config = match &cli.prover_config {
+        Some(path) => ProverConfig::load(path)?,
+        None => ProverConfig::from_env()?,
+    };
+    if let Some(backend) = cli.backend {
+        config.backend = backend;
+    }
+    let prover = SnakeProver::with_config(&config)?;
+
+    let mut csv: Box<dyn Write> = match &cli.output {
+        Some(path) => Box::new(
+            File::create(path).with_context(|| format!("failed to create {}", path.display()))?,
+        ),
+        None => Box::new(io::stdout()),
+    };
+    writeln!(
+        csv,
+        "target_score,score,ticks,cycles,prove_ms,proof_bytes,verify_ms"
+    )?;
+    for &target in &cli.scores {
+        let replay = bot::generate_game(cli.seed, target);
+        let inputs =
+            SnakeGamePrivateInputs::from_seeded_game(&replay.level, replay.seed, &replay.moves)
+                .context("bot game does not match its seeded game")?;
+        let report = prover.execute(&inputs)?;
+
+        let start = Instant::now();
+        let proof = prover.prove_game_with_mode(&inputs, cli.mode)?;
+        let prove_time = start.elapsed();
+        let proof_bytes = bincode::serialize(&proof.proof)
+            .context("failed to serialize proof")?
+            .len();
+
+        let start = Instant::now();
+        prover.verify_proof(&proof)?;
+        let verify_time = start.elapsed();
+
+        writeln!(
+            csv,
+            "{},{},{},{},{},{},{}",
+            target,
+            replay.score,
+            report.ticks,
+            report.cycles,
+            prove_time.as_millis(),
+            proof_bytes,
+            verify_time.as_millis()
+        )?;
+        csv.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,294 @@
+//! Command line front end for the snake prover.
+//!
+//! A replay file is JSON holding the grid size, the seed the game was
+//! started with, the hex-encoded move log from `GameState::export_moves` and
+//! optionally the player address to credit:
+//!
+//! ```json
+//! { "grid_width": 40, "grid_height": 25, "seed": 42, "moves": "0300000000...", "player": "0x..." }
+//! ```
+//!
+//! Add `"wrap_walls": true` for games played in wrap-around mode and
+//! `"obstacles": [[x, y], ...]` for obstacle tiles. Games on a custom level
+//! give the whole `snake_core::Level` as `"level"` instead of the grid
+//! fields. Daily-challenge games add `"challenge_date": "YYYY-MM-DD"` and
+//! `"challenge_salt"`, the deployment's domain salt, and only prove if the
+//! seed is that day's. `"nonce"` (default 0) goes into the committed
+//! nullifier, telling apart a player's games on the same seed. Signed games
+//! add the player's Ed25519 `"public_key"` and their `"signature"` over
+//! `GameState::move_digest`, both hex.
+//!
+//! Files ending in `.skr` are read as binary replays exported by the
+//! frontend (`snake_core::Replay`) and credit the zero address.
+//!
+//! Proofs are written and read as `.skp` bundles
+//! (`snake_core::bundle::ProofBundle`), the format the server and the wasm
+//! verifier take too.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{anyhow, Context, Result};
+use clap::{Parser, Subcommand};
+use serde::Deserialize;
+use snake_core::bundle::ProofBundle;
+use snake_core::challenge::Challenge;
+use snake_core::inputs::{PlayerSignature, SnakeGamePrivateInputs, SnakeGamePublicInputs};
+use snake_core::{moves, verify, Level, Replay};
+use snake_prover::cache::ProofCache;
+use snake_prover::fixture;
+use snake_prover::prover::{self, ProofMode, ProverBackend, ProverConfig, SnakeProver};
+
+#[derive(Parser)]
+#[command(about = "Prove and verify Snake games with SP1")]
+struct Cli {
+    /// JSON prover config, see `ProverConfig`; without it the backend and
+    /// network credentials come from `SP1_PROVER` and `NETWORK_*`.
+    #[arg(long, global = true)]
+    prover_config: Option<PathBuf>,
+    /// Prove on this backend, overriding the configured one.
+    #[arg(long, global = true, value_enum)]
+    backend: Option<ProverBackend>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+impl Cli {
+    fn prover(&self) -> Result<SnakeProver> {
+        let mut config = match &self.prover_config {
+            Some(path) => ProverConfig::load(path)?,
+            None => ProverConfig::from_env()?,
+        };
+        if let Some(backend) = self.backend {
+            config.backend = backend;
+        }
+        SnakeProver::with_config(&config)
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the guest without proving and report cycle counts, to estimate
+    /// proving cost.
+    Execute { replay: PathBuf },
+    /// Generate a proof for a replay.
+    Prove {
+        replay: PathBuf,
+        #[arg(short, long, default_value = "proof.skp")]
+        output: PathBuf,
+        /// Groth16 and PLONK proofs can be verified on-chain.
+        #[arg(long, value_enum, default_value_t = ProofMode::Core)]
+        mode: ProofMode,
+        /// Also write a Solidity test fixture (requires groth16 or plonk).
+        #[arg(long)]
+        fixture: Option<PathBuf>,
+        /// Proof cache directory; a game proven before is copied from it
+        /// instead of proven again.
+        #[arg(long)]
+        proof_cache: Option<PathBuf>,
+    },
+    /// Verify an existing proof bundle.
+    Verify { proof: PathBuf },
+    /// Print the game program's verification key hash, the `bytes32`
+    /// contracts and verifiers pin.
+    Vkey {
+        /// Also write the verification key, bincode-encoded.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Deserialize)]
+struct ReplayFile {
+    level: Option<Level>,
+    grid_width: Option<u32>,
+    grid_height: Option<u32>,
+    seed: u64,
+    /// Wrap-around mode, defaults to classic walls.
+    #[serde(default)]
+    wrap_walls: bool,
+    /// Obstacle cells, defaults to none.
+    #[serde(default)]
+    obstacles: Vec<(u32, u32)>,
+    moves: String,
+    /// Address credited with the score, defaults to zero.
+    player: Option<String>,
+    /// Day of the daily challenge the game was played on, if any.
+    challenge_date: Option<String>,
+    #[serde(default)]
+    challenge_salt: String,
+    #[serde(default)]
+    nonce: u64,
+    public_key: Option<String>,
+    signature: Option<String>,
+}
+
+fn load_replay(path: &Path) -> Result<SnakeGamePrivateInputs> {
+    if path.extension().is_some_and(|ext| ext == "skr") {
+        return load_skr(path);
+    }
+    let json =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let replay: ReplayFile = serde_json::from_str(&json).context("invalid replay file")?;
+    let bytes =
+        hex::decode(replay.moves.trim_start_matches("0x")).context("invalid move log hex")?;
+    let moves = moves::decode_moves(&bytes).context("invalid move log")?;
+    let level = match (replay.level, replay.grid_width, replay.grid_height) {
+        (Some(level), None, None) => level,
+        (None, Some(width), Some(height)) => Level {
+            obstacles: verify::to_positions(&replay.obstacles),
+            ..Level::classic(width as i32, height as i32, replay.wrap_walls)
+        },
+        _ => return Err(anyhow!("give either a level or grid_width and grid_height")),
+    };
+    let mut inputs = SnakeGamePrivateInputs::from_seeded_game(&level, replay.seed, &moves)
+        .context("replay does not match the seeded game")?;
+    if let Some(player) = replay.player {
+        let bytes = hex::decode(player.trim_start_matches("0x")).context("invalid player hex")?;
+        inputs.player = bytes
+            .try_into()
+            .map_err(|_| anyhow!("player must be a 20-byte address"))?;
+    }
+    inputs.nonce = replay.nonce;
+    match (replay.public_key, replay.signature) {
+        (Some(public_key), Some(signature)) => {
+            let public_key = hex::decode(public_key.trim_start_matches("0x"))
+                .context("invalid public key hex")?;
+            inputs.signature = Some(PlayerSignature {
+                public_key: public_key
+                    .try_into()
+                    .map_err(|_| anyhow!("public key must be 32 bytes"))?,
+                signature: hex::decode(signature.trim_start_matches("0x"))
+                    .context("invalid signature hex")?,
+            });
+        }
+        (None, None) => {}
+        _ => return Err(anyhow!("give both public_key and signature, or neither")),
+    }
+    if let Some(date) = replay.challenge_date {
+        let challenge = Challenge::new(&date, replay.challenge_salt.as_bytes())
+            .context("invalid challenge date")?;
+        challenge
+            .check(replay.seed)
+            .context("seed is not the challenge seed of that day")?;
+        inputs.challenge = Some(challenge);
+    }
+    Ok(inputs)
+}
+
+fn load_skr(path: &Path) -> Result<SnakeGamePrivateInputs> {
+    let bytes = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let replay = Replay::decode(&bytes).context("invalid .skr replay")?;
+    // Screen natively before spending prover cycles
+    verify::verify_replay(&replay).context("replay does not verify")?;
+    SnakeGamePrivateInputs::from_seeded_game(&replay.level, replay.seed, &replay.moves)
+        .context("replay does not match the seeded game")
+}
+
+fn print_public_inputs(public_inputs: &SnakeGamePublicInputs) {
+    println!("score:           {}", public_inputs.score);
+    println!("snake length:    {}", public_inputs.snake_length);
+    println!(
+        "game state hash: 0x{}",
+        hex::encode(public_inputs.game_state_hash)
+    );
+    println!(
+        "grid:            {}x{}",
+        public_inputs.grid_width, public_inputs.grid_height
+    );
+    println!(
+        "walls:           {}",
+        if public_inputs.wrap_walls {
+            "wrap"
+        } else {
+            "classic"
+        }
+    );
+    println!(
+        "level hash:      0x{}",
+        hex::encode(public_inputs.level_hash)
+    );
+    println!("seed:            {}", public_inputs.seed);
+    println!(
+        "config hash:     0x{}",
+        hex::encode(public_inputs.config_hash)
+    );
+    println!("player:          0x{}", hex::encode(public_inputs.player));
+    println!(
+        "nullifier:       0x{}",
+        hex::encode(public_inputs.nullifier)
+    );
+    println!(
+        "tick chain:      0x{}",
+        hex::encode(public_inputs.tick_chain)
+    );
+    if public_inputs.player_key != [0; 32] {
+        println!(
+            "player key:      0x{}",
+            hex::encode(public_inputs.player_key)
+        );
+    }
+    if public_inputs.challenge_day != 0 {
+        println!("challenge day:   {}", public_inputs.challenge_day);
+    }
+}
+
+fn main() -> Result<()> {
+    sp1_sdk::utils::setup_logger();
+    let cli = Cli::parse();
+
+    match &cli.command {
+        Command::Execute { replay } => {
+            let report = prover::execute(&load_replay(replay)?)?;
+            print_public_inputs(&report.public_inputs);
+            println!("ticks:           {}", report.ticks);
+            println!("cycles:          {}", report.cycles);
+            println!("cycles per tick: {}", report.cycles_per_tick());
+            println!("syscalls:        {}", report.syscalls);
+            println!("touched memory:  {} addresses", report.touched_memory);
+        }
+        Command::Prove {
+            replay,
+            output,
+            mode,
+            fixture,
+            proof_cache,
+        } => {
+            let prover = cli.prover()?;
+            let replay = load_replay(replay)?;
+            let started_at = SystemTime::now();
+            let proof = match proof_cache {
+                Some(dir) => prover.prove_game_cached(&replay, *mode, &ProofCache::open(dir)?)?,
+                None => prover.prove_game_with_mode(&replay, *mode)?,
+            };
+            let bundle = proof.to_bundle(&replay, prover.vkey(), started_at, SystemTime::now())?;
+            fs::write(output, bundle.encode()).context("failed to write proof")?;
+            print_public_inputs(&proof.public_inputs);
+            println!("proof written to {}", output.display());
+
+            if let Some(path) = fixture {
+                let fixture = fixture::generate_fixture(&proof, prover.vkey())?;
+                fixture::write_fixture(&fixture, path)?;
+                println!("fixture written to {}", path.display());
+            }
+        }
+        Command::Verify { proof } => {
+            let bytes = fs::read(proof).context("failed to read proof")?;
+            let bundle = ProofBundle::decode(&bytes).context("invalid proof bundle")?;
+            let public_inputs = cli.prover()?.verify_bundle(&bundle)?;
+            print_public_inputs(&public_inputs);
+            println!("proof is valid");
+        }
+        Command::Vkey { output } => {
+            let prover = cli.prover()?;
+            println!("vkey hash: 0x{}", hex::encode(prover.vkey_hash()?));
+            if let Some(path) = output {
+                let bytes = bincode::serialize(prover.vkey()).context("failed to encode vkey")?;
+                fs::write(path, bytes).context("failed to write vkey")?;
+                println!("vkey written to {}", path.display());
+            }
+        }
+    }
+    Ok(())
+}
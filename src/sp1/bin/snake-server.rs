@@ -0,0 +1,89 @@
+//! HTTP server for submitting, proving and listing Snake scores; see
+//! `snake_prover::server` for the endpoints.
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use snake_core::registry::VkeyRegistry;
+use snake_prover::cache::ProofCache;
+use snake_prover::prover::{ProofMode, ProverBackend, ProverConfig};
+use snake_prover::server::{self, AppState};
+use snake_prover::store::ScoreStore;
+
+#[derive(Parser)]
+#[command(about = "Serve Snake score submissions and proofs over HTTP")]
+struct Cli {
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    addr: SocketAddr,
+    /// Kind of proof made for `POST /proofs/{id}`.
+    #[arg(long, value_enum, default_value_t = ProofMode::Core)]
+    mode: ProofMode,
+    /// SQLite database of verified submissions, created if missing.
+    #[arg(long, default_value = "scores.db")]
+    db: PathBuf,
+    /// Directory proof files are written to.
+    #[arg(long, default_value = "proofs")]
+    proof_dir: PathBuf,
+    /// Proof cache directory; a game proven before is answered from it.
+    #[arg(long)]
+    proof_cache: Option<PathBuf>,
+    /// JSON vkey registry manifest, see `snake_core::registry`; only
+    /// bundles of the program releases it lists verify.
+    #[arg(long)]
+    vkey_registry: Option<PathBuf>,
+    /// JSON prover config, see `ProverConfig`; without it the backend and
+    /// network credentials come from `SP1_PROVER` and `NETWORK_*`.
+    #[arg(long)]
+    prover_config: Option<PathBuf>,
+    /// Prove on this backend, overriding the configured one.
+    #[arg(long, value_enum)]
+    backend: Option<ProverBackend>,
+}
+
+fn main() -> Result<()> {
+    sp1_sdk::utils::setup_logger();
+    let cli = Cli::parse();
+    let mut config = match &cli.prover_config {
+        Some(path) => ProverConfig::load(path)?,
+        None => ProverConfig::from_env()?,
+    };
+    if let Some(backend) = cli.backend {
+        config.backend = backend;
+    }
+    let registry = match &cli.vkey_registry {
+        Some(path) => {
+            let json = std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            Some(VkeyRegistry::from_json(&json).context("invalid vkey registry")?)
+        }
+        None => None,
+    };
+    let store = ScoreStore::open(&cli.db)?;
+    let proof_cache = cli
+        .proof_cache
+        .as_deref()
+        .map(ProofCache::open)
+        .transpose()?;
+    // The blocking prover runs its own runtime, so set it up outside ours
+    let state = AppState::start(
+        &config,
+        cli.mode,
+        store,
+        cli.proof_dir,
+        proof_cache,
+        registry,
+    )?;
+
+    let runtime = tokio::runtime::Runtime::new().context("failed to start the runtime")?;
+    runtime.block_on(async {
+        let listener = tokio::net::TcpListener::bind(cli.addr)
+            .await
+            .with_context(|| format!("failed to listen on {}", cli.addr))?;
+        println!("listening on http://{}", cli.addr);
+        axum::serve(listener, server::router(state))
+            .await
+            .context("server failed")
+    })
+}
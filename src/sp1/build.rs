@@ -0,0 +1,33 @@
+use sp1_build::{build_program_with_args, BuildArgs};
+
+const PROGRAMS: [&str; 8] = [
+    "program",
+    "versus-program",
+    "leaderboard-program",
+    "threshold-program",
+    "achievements-program",
+    "chunk-program",
+    "chain-program",
+    "aggregate-program",
+];
+
+fn main() {
+    // The guests must hash exactly as the host does, see the `keccak` and
+    // `poseidon` features
+    let mut features = Vec::new();
+    for feature in ["keccak", "poseidon"] {
+        let var = format!("CARGO_FEATURE_{}", feature.to_uppercase());
+        if std::env::var_os(var).is_some() {
+            features.push(feature.to_string());
+        }
+    }
+    for program in PROGRAMS {
+        build_program_with_args(
+            program,
+            BuildArgs {
+                features: features.clone(),
+                ..Default::default()
+            },
+        );
+    }
+}
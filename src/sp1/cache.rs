@@ -0,0 +1,86 @@
+//! On-disk cache of game proofs.
+//!
+//! Proving the same game twice gives an equally valid proof, so a proof is
+//! kept under the hash of everything it depends on and handed back when the
+//! same run is submitted again:
+//!
+//! `key = sha256("snake-proof-cache" || bincode(replay) || config_hash || mode || vkey)`
+//!
+//! where `replay` is the [`SnakeGamePrivateInputs`], so the player, nonce,
+//! signature and challenge count, `config_hash` is the level's
+//! [`hash::config_hash`], `mode` the [`ProofMode`] name and `vkey` the
+//! program's `bytes32` verification key hash. A rebuilt guest has a new
+//! vkey and never sees proofs of the old one. Entries are proof files as
+//! `snake-prove` writes them, named by the hex key.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use snake_core::hash;
+use snake_core::inputs::SnakeGamePrivateInputs;
+use sp1_sdk::{HashableKey, SP1ProofWithPublicValues, SP1VerifyingKey};
+
+use crate::prover::{ProofMode, SnakeProof};
+
+const DOMAIN: &[u8] = b"snake-proof-cache";
+
+pub struct ProofCache {
+    dir: PathBuf,
+}
+
+impl ProofCache {
+    /// Opens the cache in `dir`, creating the directory if missing.
+    pub fn open(dir: &Path) -> Result<ProofCache> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+        Ok(ProofCache {
+            dir: dir.to_owned(),
+        })
+    }
+
+    /// The key of a `mode` proof of `replay` under `vkey`.
+    pub fn key(
+        replay: &SnakeGamePrivateInputs,
+        mode: ProofMode,
+        vkey: &SP1VerifyingKey,
+    ) -> Result<[u8; 32]> {
+        let replay_bytes = bincode::serialize(replay).context("failed to serialize replay")?;
+        Ok(Sha256::new()
+            .chain_update(DOMAIN)
+            .chain_update(replay_bytes)
+            .chain_update(hash::config_hash(&replay.level))
+            .chain_update(format!("{mode:?}"))
+            .chain_update(vkey.bytes32())
+            .finalize()
+            .into())
+    }
+
+    /// The proof cached under `key`, if any.
+    pub fn get(&self, key: &[u8; 32]) -> Result<Option<SnakeProof>> {
+        let path = self.path(key);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let proof = SP1ProofWithPublicValues::load(&path)
+            .with_context(|| format!("failed to read cached proof {}", path.display()))?;
+        proof.try_into().map(Some)
+    }
+
+    /// Caches `proof` under `key`. The file is written aside and renamed
+    /// into place, so a crash never leaves a truncated entry.
+    pub fn insert(&self, key: &[u8; 32], proof: &SnakeProof) -> Result<()> {
+        let path = self.path(key);
+        let partial = path.with_extension("partial");
+        proof
+            .proof
+            .save(&partial)
+            .context("failed to write cached proof")?;
+        std::fs::rename(&partial, &path)
+            .with_context(|| format!("failed to move proof to {}", path.display()))
+    }
+
+    fn path(&self, key: &[u8; 32]) -> PathBuf {
+        self.dir.join(format!("{}.bin", hex::encode(key)))
+    }
+}
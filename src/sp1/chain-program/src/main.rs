@@ -0,0 +1,45 @@
+//! Snake Chain Verifier
+//! This is an SP1 program that joins the chunk proofs of one long game into
+//! a single proof. It reads the public values of every chunk and the final
+//! game state from stdin, verifies each chunk proof recursively against the
+//! chunk program's verification key, checks the chunks link the seeded
+//! start of the game to its end, and commits the same ABI-encoded public
+//! values as the single-game program.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use sha2::{Digest, Sha256};
+use snake_core::inputs::{ChainPrivateInputs, SnakeGamePublicInputs};
+use snake_core::{abi, verify, SnakeError};
+
+// Links the chunks and returns the values to commit, or an error if they
+// do not make up one finished game. The proofs themselves are checked in
+// `main`
+pub fn snake_chain_verifier(
+    private_inputs: &ChainPrivateInputs,
+) -> Result<SnakeGamePublicInputs, SnakeError> {
+    let chunks = private_inputs
+        .chunks
+        .iter()
+        .map(|values| abi::decode_chunk_inputs(values).map_err(|_| SnakeError::BadReplay))
+        .collect::<Result<Vec<_>, _>>()?;
+    let outcome = verify::verify_chain(&chunks, &private_inputs.final_snapshot)?;
+    Ok(outcome.public_inputs(private_inputs.player, private_inputs.nonce))
+}
+
+pub fn main() {
+    let private_inputs = sp1_zkvm::io::read::<ChainPrivateInputs>();
+
+    // Each chunk only counts if a proof of exactly these public values was
+    // written to stdin by the host; a missing one aborts execution
+    for values in &private_inputs.chunks {
+        let digest: [u8; 32] = Sha256::digest(values).into();
+        sp1_zkvm::lib::verify::verify_sp1_proof(&private_inputs.vkey_digest, &digest);
+    }
+
+    let public_inputs = snake_chain_verifier(&private_inputs)
+        .unwrap_or_else(|err| panic!("invalid chunk chain: {err}"));
+
+    sp1_zkvm::io::commit_slice(&abi::encode_public_inputs(&public_inputs));
+}
@@ -0,0 +1,40 @@
+//! Snake Chunk Verifier
+//! This is an SP1 program that verifies one stretch of a game too long to
+//! prove in one go. It reads a saved game state, the moves made during the
+//! chunk and its length from stdin, plays the chunk with the shared
+//! `snake-core` engine and commits the snapshot hashes of the state before
+//! and after as ABI-encoded public values, for the chain program to link.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use snake_core::inputs::{ChunkPrivateInputs, ChunkPublicInputs};
+use snake_core::{abi, hash, moves, verify, GameState, SnakeError};
+
+// Plays the chunk and returns the values to commit, or an error if the
+// snapshot is corrupt or the moves are not legal
+pub fn snake_chunk_verifier(
+    private_inputs: &ChunkPrivateInputs,
+) -> Result<ChunkPublicInputs, SnakeError> {
+    let mut state =
+        GameState::from_bytes(&private_inputs.snapshot).map_err(|_| SnakeError::BadReplay)?;
+    let input_hash = hash::snapshot_hash(&state);
+    let moves = moves::decompress_moves(&private_inputs.game_moves)?;
+
+    verify::replay_chunk(&mut state, &moves, private_inputs.ticks)?;
+
+    Ok(ChunkPublicInputs {
+        input_hash,
+        output_hash: hash::snapshot_hash(&state),
+    })
+}
+
+pub fn main() {
+    let private_inputs = sp1_zkvm::io::read::<ChunkPrivateInputs>();
+
+    // An illegal chunk aborts execution, so no proof can be produced for it
+    let public_inputs = snake_chunk_verifier(&private_inputs)
+        .unwrap_or_else(|err| panic!("invalid game chunk: {err}"));
+
+    sp1_zkvm::io::commit_slice(&abi::encode_chunk_inputs(&public_inputs));
+}
@@ -0,0 +1,73 @@
+//! Solidity test fixtures for proven games.
+//!
+//! The JSON written here follows the layout used by the SP1 contract
+//! templates, so a Foundry test can load it with `vm.parseJson` and call
+//! `ISP1Verifier.verifyProof(vkey, publicValues, proof)`.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{ensure, Context, Result};
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{HashableKey, SP1Proof, SP1VerifyingKey};
+
+use crate::prover::SnakeProof;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnakeProofFixture {
+    pub score: u64,
+    pub snake_length: u32,
+    pub wrap_walls: bool,
+    pub seed: u64,
+    /// Daily challenge day as `YYYYMMDD`, or 0 for a free game.
+    pub challenge_day: u32,
+    pub game_state_hash: String,
+    /// Rule-set hash a contract can whitelist, `bytes32` hex.
+    pub config_hash: String,
+    /// Nullifier the contract records as spent, `bytes32` hex.
+    pub nullifier: String,
+    /// Ed25519 key that signed the game, `bytes32` hex, zero if unsigned.
+    pub player_key: String,
+    /// Hash chain head over every tick, `bytes32` hex.
+    pub tick_chain: String,
+    /// Program verification key hash, `bytes32` hex.
+    pub vkey: String,
+    /// Public values exactly as committed by the guest.
+    pub public_values: String,
+    /// Proof bytes in the encoding the on-chain verifier expects.
+    pub proof: String,
+}
+
+/// Builds a fixture from a Groth16 or PLONK proof.
+pub fn generate_fixture(proof: &SnakeProof, vkey: &SP1VerifyingKey) -> Result<SnakeProofFixture> {
+    ensure!(
+        matches!(proof.proof.proof, SP1Proof::Groth16(_) | SP1Proof::Plonk(_)),
+        "fixtures require a Groth16 or PLONK proof"
+    );
+
+    Ok(SnakeProofFixture {
+        score: proof.public_inputs.score,
+        snake_length: proof.public_inputs.snake_length,
+        wrap_walls: proof.public_inputs.wrap_walls,
+        seed: proof.public_inputs.seed,
+        challenge_day: proof.public_inputs.challenge_day,
+        game_state_hash: format!("0x{}", hex::encode(proof.public_inputs.game_state_hash)),
+        config_hash: format!("0x{}", hex::encode(proof.public_inputs.config_hash)),
+        nullifier: format!("0x{}", hex::encode(proof.public_inputs.nullifier)),
+        player_key: format!("0x{}", hex::encode(proof.public_inputs.player_key)),
+        tick_chain: format!("0x{}", hex::encode(proof.public_inputs.tick_chain)),
+        vkey: vkey.bytes32(),
+        public_values: format!("0x{}", hex::encode(proof.proof.public_values.as_slice())),
+        proof: format!("0x{}", hex::encode(proof.proof.bytes())),
+    })
+}
+
+/// Writes `fixture` as pretty-printed JSON, creating parent directories.
+pub fn write_fixture(fixture: &SnakeProofFixture, path: &Path) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    let json = serde_json::to_string_pretty(fixture)?;
+    fs::write(path, json).with_context(|| format!("failed to write {}", path.display()))
+}
@@ -0,0 +1,52 @@
+//! Snake Leaderboard Aggregator
+//! This is an SP1 program that ranks proven Snake games. It reads the public
+//! values of any number of game proofs from stdin, verifies each proof
+//! recursively against the single-game program's verification key, ranks
+//! the players by their best score and commits the top-N board (Merkle
+//! root, players and scores) as ABI-encoded public values.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use sha2::{Digest, Sha256};
+use snake_core::inputs::{self, LeaderboardPrivateInputs, LeaderboardPublicInputs};
+use snake_core::{abi, leaderboard, SnakeError};
+
+// Ranks the games and returns the values to commit, or an error if they
+// cannot be ranked together. The proofs themselves are checked in `main`
+pub fn snake_leaderboard(
+    private_inputs: &LeaderboardPrivateInputs,
+) -> Result<LeaderboardPublicInputs, SnakeError> {
+    let games = private_inputs
+        .public_values
+        .iter()
+        .map(|values| abi::decode_public_inputs(values).map_err(|_| SnakeError::BadInput))
+        .collect::<Result<Vec<_>, _>>()?;
+    let board = leaderboard::rank_games(&games, private_inputs.top_n as usize)?;
+
+    let first = games.first();
+    Ok(LeaderboardPublicInputs {
+        game_vkey_digest: inputs::digest_bytes(private_inputs.vkey_digest),
+        config_hash: first.map_or([0; 32], |game| game.config_hash),
+        seed: first.map_or(0, |game| game.seed),
+        root: board.root(),
+        players: board.iter().map(|(player, _)| *player).collect(),
+        scores: board.iter().map(|(_, score)| score).collect(),
+    })
+}
+
+pub fn main() {
+    let private_inputs = sp1_zkvm::io::read::<LeaderboardPrivateInputs>();
+
+    // Each game is only counted if a proof of exactly these public values
+    // was written to stdin by the host; a missing one aborts execution
+    for values in &private_inputs.public_values {
+        let digest: [u8; 32] = Sha256::digest(values).into();
+        sp1_zkvm::lib::verify::verify_sp1_proof(&private_inputs.vkey_digest, &digest);
+    }
+
+    let public_inputs = snake_leaderboard(&private_inputs)
+        .unwrap_or_else(|err| panic!("invalid leaderboard: {err}"));
+
+    sp1_zkvm::io::commit_slice(&abi::encode_leaderboard_inputs(&public_inputs));
+}
@@ -0,0 +1,7 @@
+//! Host-side tooling for the SP1 Snake verifier.
+
+pub mod cache;
+pub mod fixture;
+pub mod prover;
+pub mod server;
+pub mod store;
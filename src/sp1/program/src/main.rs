@@ -0,0 +1,70 @@
+//! Snake Game Verifier
+//! This is an SP1 program that verifies the integrity of a Snake game score.
+//! It reads a recorded game from stdin, replays the moves with the shared
+//! `snake-core` engine and commits the resulting game state hash, score, snake
+//! length, grid size, wall mode, level hash, seed, config hash, player,
+//! daily challenge, nullifier and signing key as ABI-encoded public values.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use ed25519_dalek::{Signature, VerifyingKey};
+use snake_core::inputs::{SnakeGamePrivateInputs, SnakeGamePublicInputs};
+use snake_core::verify::{self, VerifiedOutcome};
+use snake_core::{abi, challenge, hash, moves, SnakeError};
+
+// Replays the game and returns the values to commit, or an error if the
+// recording is not a legal game
+pub fn snake_game_verifier(
+    private_inputs: &SnakeGamePrivateInputs,
+) -> Result<SnakeGamePublicInputs, SnakeError> {
+    let moves = moves::decompress_moves(&private_inputs.game_moves)?;
+
+    // Replay the whole game tick by tick with the shared engine; this
+    // enforces wall/self collision, growth and scoring on every move, and
+    // regenerates every food spawn from the seed, failing unless the
+    // recorded food positions are exactly that sequence
+    let final_state = verify::replay_moves(
+        &private_inputs.level,
+        private_inputs.seed,
+        &private_inputs.food_positions,
+        &moves,
+    )?;
+
+    // The same outcome `verify::verify_replay` reports to servers, with the
+    // nullifier that stops the proof being accepted twice
+    let mut public_inputs = VerifiedOutcome::from_state(&final_state)
+        .public_inputs(private_inputs.player, private_inputs.nonce);
+
+    // A challenge score only counts if the game really started from the
+    // day's seed
+    if let Some(challenge) = &private_inputs.challenge {
+        challenge.check(private_inputs.seed)?;
+        public_inputs.challenge_day = challenge.day;
+        public_inputs.challenge_salt_hash = challenge::salt_hash(&challenge.domain_salt);
+    }
+
+    // A signed game commits the key that signed it, so a contract can
+    // insist the prover held the player's key rather than a copied replay
+    if let Some(signed) = &private_inputs.signature {
+        let key =
+            VerifyingKey::from_bytes(&signed.public_key).map_err(|_| SnakeError::BadSignature)?;
+        let signature =
+            Signature::from_slice(&signed.signature).map_err(|_| SnakeError::BadSignature)?;
+        let digest = hash::move_digest(&private_inputs.game_moves, private_inputs.seed);
+        key.verify_strict(&digest, &signature)
+            .map_err(|_| SnakeError::BadSignature)?;
+        public_inputs.player_key = signed.public_key;
+    }
+    Ok(public_inputs)
+}
+
+pub fn main() {
+    let private_inputs = sp1_zkvm::io::read::<SnakeGamePrivateInputs>();
+
+    // An illegal game aborts execution, so no proof can be produced for it
+    let public_inputs = snake_game_verifier(&private_inputs)
+        .unwrap_or_else(|err| panic!("invalid game replay: {err}"));
+
+    sp1_zkvm::io::commit_slice(&abi::encode_public_inputs(&public_inputs));
+}
@@ -0,0 +1,1210 @@
+//! Proof generation and verification for recorded Snake games.
+//!
+//! [`SnakeProver`] runs the program setup once and can then prove and verify
+//! any number of games. Every prover proves on the backend of a
+//! [`ProverConfig`]: the local CPU, a local GPU, or the Succinct prover
+//! network for machines too weak to prove themselves. `new` reads it from
+//! the environment, `with_config` takes it from the caller.
+//!
+//! [`VersusProver`] does the same for two-player matches with the versus
+//! program, whose public values settle the match.
+//!
+//! [`LeaderboardProver`] aggregates compressed game proofs into one proof of
+//! the top-N leaderboard, which verifies each game proof inside the guest.
+//!
+//! [`ThresholdProver`] proves a game scored at least some threshold while
+//! keeping the score and moves private, and [`AchievementProver`] which
+//! badges a game earned.
+//!
+//! [`ChunkedProver`] proves games too long for one proof in fixed-size
+//! chunks and joins them into one proof with the same public values as a
+//! single-game proof, and [`AggregateProver`] settles many independent game
+//! proofs, e.g. a tournament bracket, with one.
+//!
+//! [`execute`] runs the game guest without proving, reporting its cycle
+//! count, to estimate proving cost before paying for a proof.
+//!
+//! [`vkey`] and [`vkey_hash`] give the game program's verification key
+//! without a prover, for contract deployers and third-party verifiers who
+//! pin the exact program they accept.
+//!
+//! [`SnakeProof::to_bundle`] packs a game proof into the [`ProofBundle`]
+//! every tool exchanges, and [`SnakeProver::verify_bundle`] checks one.
+//!
+//! [`SnakeProver::prove_game_cached`] answers a game proven before from a
+//! [`ProofCache`] instead of proving it again.
+//!
+//! [`SnakeProver::prove_game_tracked`] reports each [`ProvePhase`] as it
+//! starts and stops early once its [`CancelToken`] is cancelled. The SDK's
+//! proving call cannot be interrupted, so a proof cancelled during it runs
+//! to the end and is then discarded.
+//!
+//! Core proofs are the fastest to produce; leaderboard contracts need the
+//! STARK wrapped into a Groth16 or PLONK proof, see [`ProofMode`].
+
+use std::fmt;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use snake_core::bundle::{ProofBundle, ProofKind};
+use snake_core::inputs::{
+    AchievementPublicInputs, AggregatePrivateInputs, AggregatePublicInputs, ChainPrivateInputs,
+    ChunkPrivateInputs, LeaderboardPrivateInputs, LeaderboardPublicInputs, SnakeGamePrivateInputs,
+    SnakeGamePublicInputs, ThresholdPrivateInputs, ThresholdPublicInputs, VersusPrivateInputs,
+    VersusPublicInputs,
+};
+use snake_core::registry::{ProgramVersion, VkeyRegistry};
+use snake_core::{abi, moves, rules, verify, GameState, Replay};
+use sp1_sdk::blocking::{
+    EnvProver, EnvProvingKey, ProveRequest, Prover, ProverClient, SP1ProofMode,
+};
+use sp1_sdk::{
+    include_elf, Elf, HashableKey, ProvingKey, SP1Proof, SP1ProofWithPublicValues, SP1Stdin,
+    SP1VerifyingKey,
+};
+
+use crate::cache::ProofCache;
+
+/// Host version recorded in every [`ProofBundle`].
+pub const PROGRAM_VERSION: &str = concat!("snake-prover ", env!("CARGO_PKG_VERSION"));
+
+/// The compiled guest program.
+pub const SNAKE_VERIFIER_ELF: Elf = include_elf!("snake-verifier-program");
+
+/// The compiled versus guest program.
+pub const SNAKE_VERSUS_ELF: Elf = include_elf!("snake-versus-program");
+
+/// The compiled leaderboard guest program.
+pub const SNAKE_LEADERBOARD_ELF: Elf = include_elf!("snake-leaderboard-program");
+
+/// The compiled threshold guest program.
+pub const SNAKE_THRESHOLD_ELF: Elf = include_elf!("snake-threshold-program");
+
+/// The compiled achievements guest program.
+pub const SNAKE_ACHIEVEMENTS_ELF: Elf = include_elf!("snake-achievements-program");
+
+/// The compiled chunk guest program.
+pub const SNAKE_CHUNK_ELF: Elf = include_elf!("snake-chunk-program");
+
+/// The compiled chain guest program.
+pub const SNAKE_CHAIN_ELF: Elf = include_elf!("snake-chain-program");
+
+/// The compiled aggregate guest program.
+pub const SNAKE_AGGREGATE_ELF: Elf = include_elf!("snake-aggregate-program");
+
+/// Kind of proof to generate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ProofMode {
+    /// STARK proof whose size grows with the game length.
+    #[default]
+    Core,
+    /// Constant-size recursive STARK proof.
+    Compressed,
+    /// Compressed proof wrapped in Groth16, for EVM verification.
+    Groth16,
+    /// Compressed proof wrapped in PLONK, for EVM verification.
+    Plonk,
+}
+
+impl From<ProofMode> for SP1ProofMode {
+    fn from(mode: ProofMode) -> Self {
+        match mode {
+            ProofMode::Core => SP1ProofMode::Core,
+            ProofMode::Compressed => SP1ProofMode::Compressed,
+            ProofMode::Groth16 => SP1ProofMode::Groth16,
+            ProofMode::Plonk => SP1ProofMode::Plonk,
+        }
+    }
+}
+
+/// Where proofs are generated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProverBackend {
+    /// On this machine's CPU.
+    #[default]
+    Cpu,
+    /// On this machine's GPU; needs the `cuda` feature and Docker.
+    Cuda,
+    /// On the Succinct prover network, paid from the configured account;
+    /// needs the `network` feature.
+    Network,
+    /// Fake proofs that only check execution, for fast local testing.
+    Mock,
+}
+
+/// The prover backend and, for the network, its credentials. Loaded from
+/// a JSON file with [`ProverConfig::load`], e.g.
+///
+/// ```json
+/// { "backend": "network", "network_private_key": "0x...", "network_rpc_url": "https://..." }
+/// ```
+///
+/// or from the variables the SDK itself reads with
+/// [`ProverConfig::from_env`]. Its `Debug` output never shows the key.
+#[derive(Clone, Default, Deserialize)]
+pub struct ProverConfig {
+    #[serde(default)]
+    pub backend: ProverBackend,
+    /// Hex private key of the account that pays the prover network.
+    pub network_private_key: Option<String>,
+    /// Prover network RPC endpoint, the SDK's default if unset.
+    pub network_rpc_url: Option<String>,
+}
+
+impl fmt::Debug for ProverConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProverConfig")
+            .field("backend", &self.backend)
+            .field(
+                "network_private_key",
+                &self.network_private_key.as_ref().map(|_| "<redacted>"),
+            )
+            .field("network_rpc_url", &self.network_rpc_url)
+            .finish()
+    }
+}
+
+impl ProverConfig {
+    /// From `SP1_PROVER` (`cpu` if unset), `NETWORK_PRIVATE_KEY` and
+    /// `NETWORK_RPC_URL`.
+    pub fn from_env() -> Result<ProverConfig> {
+        let backend = match std::env::var("SP1_PROVER") {
+            Ok(name) => ProverBackend::from_str(&name, true)
+                .map_err(|_| anyhow!("unknown SP1_PROVER backend {name:?}"))?,
+            Err(_) => ProverBackend::default(),
+        };
+        Ok(ProverConfig {
+            backend,
+            network_private_key: std::env::var("NETWORK_PRIVATE_KEY").ok(),
+            network_rpc_url: std::env::var("NETWORK_RPC_URL").ok(),
+        })
+    }
+
+    /// Reads a JSON config file.
+    pub fn load(path: &Path) -> Result<ProverConfig> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_str(&text)
+            .with_context(|| format!("invalid prover config {}", path.display()))
+    }
+
+    /// A client on this backend, built directly rather than through the
+    /// process environment. Fails for the network backend without a private
+    /// key, and for `cuda` or `network` when this crate was built without
+    /// the feature of that name.
+    pub fn client(&self) -> Result<EnvProver> {
+        let builder = ProverClient::builder();
+        Ok(match self.backend {
+            ProverBackend::Cpu => EnvProver::Cpu(builder.cpu().build()),
+            ProverBackend::Mock => EnvProver::Mock(builder.mock().build()),
+            #[cfg(feature = "cuda")]
+            ProverBackend::Cuda => EnvProver::Cuda(builder.cuda().build()),
+            #[cfg(feature = "network")]
+            ProverBackend::Network => {
+                let Some(key) = &self.network_private_key else {
+                    bail!("the network backend needs network_private_key");
+                };
+                let mut network = builder.network().private_key(key);
+                if let Some(url) = &self.network_rpc_url {
+                    network = network.rpc_url(url);
+                }
+                EnvProver::Network(Box::new(network.build()))
+            }
+            #[cfg(not(feature = "cuda"))]
+            ProverBackend::Cuda => bail!("the cuda backend needs snake-prover's `cuda` feature"),
+            #[cfg(not(feature = "network"))]
+            ProverBackend::Network => {
+                bail!("the network backend needs snake-prover's `network` feature")
+            }
+        })
+    }
+}
+
+/// What running the game guest on one replay took. Proving time and cost
+/// grow roughly with `cycles`, so `cycles_per_tick` estimates what longer
+/// games will cost.
+#[derive(Clone, Debug)]
+pub struct ExecutionReport {
+    /// The values the guest would commit.
+    pub public_inputs: SnakeGamePublicInputs,
+    /// RISC-V instructions executed.
+    pub cycles: u64,
+    /// Syscalls made, precompiles and I/O alike.
+    pub syscalls: u64,
+    /// Distinct memory addresses the guest touched. The guest never frees
+    /// memory, so this is its peak footprint.
+    pub touched_memory: u64,
+    /// Length of the game in ticks.
+    pub ticks: u32,
+}
+
+impl ExecutionReport {
+    pub fn cycles_per_tick(&self) -> u64 {
+        self.cycles / u64::from(self.ticks.max(1))
+    }
+}
+
+/// Runs the game guest on `replay` without proving, which needs no program
+/// setup. Fails if the guest rejects the game.
+pub fn execute(replay: &SnakeGamePrivateInputs) -> Result<ExecutionReport> {
+    execute_game(&ProverConfig::from_env()?.client()?, replay)
+}
+
+/// Verification key of the game program, the key its proofs verify
+/// against. Runs the program setup.
+pub fn vkey() -> Result<SP1VerifyingKey> {
+    let pk = ProverConfig::from_env()?
+        .client()?
+        .setup(SNAKE_VERIFIER_ELF)
+        .context("failed to set up the snake verifier program")?;
+    Ok(pk.verifying_key().clone())
+}
+
+/// The `bytes32` hash of [`vkey`], as `ISP1Verifier.verifyProof` and
+/// [`ProofBundle`] take it.
+pub fn vkey_hash() -> Result<[u8; 32]> {
+    hash_vkey(&vkey()?)
+}
+
+fn execute_game(client: &EnvProver, replay: &SnakeGamePrivateInputs) -> Result<ExecutionReport> {
+    let (public_values, report) = client
+        .execute(SNAKE_VERIFIER_ELF, stdin_for(replay))
+        .run()
+        .context("failed to execute game")?;
+    let public_inputs = abi::decode_public_inputs(public_values.as_slice())
+        .context("public values are not ABI-encoded snake public inputs")?;
+    // The public values leave the length out; the native replay is cheap
+    let game_moves = moves::decompress_moves(&replay.game_moves).context("invalid move log")?;
+    let ticks = verify::replay_moves(
+        &replay.level,
+        replay.seed,
+        &replay.food_positions,
+        &game_moves,
+    )?
+    .ticks();
+    Ok(ExecutionReport {
+        public_inputs,
+        cycles: report.total_instruction_count(),
+        syscalls: report.total_syscall_count(),
+        touched_memory: report.touched_memory_addresses,
+        ticks,
+    })
+}
+
+/// A stage of [`SnakeProver::prove_game_tracked`]. Program setup is not
+/// one: it runs once per prover, in [`SnakeProver::new`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "phase", rename_all = "snake_case")]
+pub enum ProvePhase {
+    /// Executing the guest to generate its trace.
+    Executing,
+    /// Proving the trace of `cycles` cycles, including any Groth16 or PLONK
+    /// wrapping; nearly all of the time goes here.
+    Proving { cycles: u64 },
+}
+
+/// Asks a tracked proof to stop. Clones share one flag, so a handle kept
+/// by another thread cancels the proof it was given to.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn check(&self) -> Result<()> {
+        ensure!(!self.is_cancelled(), "proving was cancelled");
+        Ok(())
+    }
+}
+
+/// A proof of a game together with the public inputs it commits to.
+pub struct SnakeProof {
+    pub proof: SP1ProofWithPublicValues,
+    pub public_inputs: SnakeGamePublicInputs,
+}
+
+impl TryFrom<SP1ProofWithPublicValues> for SnakeProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proof: SP1ProofWithPublicValues) -> Result<Self> {
+        let public_inputs = abi::decode_public_inputs(proof.public_values.as_slice())
+            .context("public values are not ABI-encoded snake public inputs")?;
+        Ok(SnakeProof {
+            proof,
+            public_inputs,
+        })
+    }
+}
+
+impl SnakeProof {
+    /// Packs the proof of `replay` into a [`ProofBundle`] for the program
+    /// `vkey`, proven between `started_at` and `finished_at`.
+    pub fn to_bundle(
+        &self,
+        replay: &SnakeGamePrivateInputs,
+        vkey: &SP1VerifyingKey,
+        started_at: SystemTime,
+        finished_at: SystemTime,
+    ) -> Result<ProofBundle> {
+        let (kind, proof) = match self.proof.proof {
+            SP1Proof::Core(_) => (ProofKind::Core, bincode::serialize(&self.proof)?),
+            SP1Proof::Compressed(_) => (ProofKind::Compressed, bincode::serialize(&self.proof)?),
+            SP1Proof::Groth16(_) => (ProofKind::Groth16, self.proof.bytes()),
+            SP1Proof::Plonk(_) => (ProofKind::Plonk, self.proof.bytes()),
+        };
+        let game = Replay {
+            engine_version: rules::ENGINE_VERSION,
+            level: replay.level.clone(),
+            seed: replay.seed,
+            score: self.public_inputs.score,
+            moves: moves::decompress_moves(&replay.game_moves).context("invalid move log")?,
+        };
+        Ok(ProofBundle {
+            kind,
+            vkey_hash: hash_vkey(vkey)?,
+            engine_version: rules::ENGINE_VERSION,
+            program_version: PROGRAM_VERSION.to_owned(),
+            started_at: unix_seconds(started_at),
+            finished_at: unix_seconds(finished_at),
+            replay_hash: Sha256::digest(game.encode()).into(),
+            public_values: self.proof.public_values.to_vec(),
+            proof,
+        })
+    }
+}
+
+fn hash_vkey(vkey: &SP1VerifyingKey) -> Result<[u8; 32]> {
+    let bytes = hex::decode(vkey.bytes32().trim_start_matches("0x"))?;
+    bytes
+        .try_into()
+        .map_err(|_| anyhow!("vkey hash is not 32 bytes"))
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+pub struct SnakeProver {
+    client: EnvProver,
+    pk: EnvProvingKey,
+}
+
+impl SnakeProver {
+    /// Creates a client from the environment and sets up the guest program.
+    pub fn new() -> Result<SnakeProver> {
+        SnakeProver::with_config(&ProverConfig::from_env()?)
+    }
+
+    /// Like [`SnakeProver::new`], on the backend `config` picks.
+    pub fn with_config(config: &ProverConfig) -> Result<SnakeProver> {
+        let client = config.client()?;
+        let pk = client
+            .setup(SNAKE_VERIFIER_ELF)
+            .context("failed to set up the snake verifier program")?;
+        Ok(SnakeProver { client, pk })
+    }
+
+    /// Verification key of the guest program.
+    pub fn vkey(&self) -> &SP1VerifyingKey {
+        self.pk.verifying_key()
+    }
+
+    /// The `bytes32` hash of [`SnakeProver::vkey`], see [`vkey_hash`].
+    pub fn vkey_hash(&self) -> Result<[u8; 32]> {
+        hash_vkey(self.vkey())
+    }
+
+    /// Runs the guest on `replay` without proving, see [`execute`].
+    pub fn execute(&self, replay: &SnakeGamePrivateInputs) -> Result<ExecutionReport> {
+        execute_game(&self.client, replay)
+    }
+
+    /// Proves that `replay` is a legal game with a core proof. Fails if the
+    /// guest rejects it.
+    pub fn prove_game(&self, replay: &SnakeGamePrivateInputs) -> Result<SnakeProof> {
+        self.prove_game_with_mode(replay, ProofMode::Core)
+    }
+
+    /// Like [`SnakeProver::prove_game`], producing a proof of the given kind.
+    pub fn prove_game_with_mode(
+        &self,
+        replay: &SnakeGamePrivateInputs,
+        mode: ProofMode,
+    ) -> Result<SnakeProof> {
+        let proof = self
+            .client
+            .prove(&self.pk, stdin_for(replay))
+            .mode(mode.into())
+            .run()
+            .with_context(|| format!("failed to generate {mode:?} proof"))?;
+        proof.try_into()
+    }
+
+    /// Like [`SnakeProver::prove_game_with_mode`], returning the proof in
+    /// `cache` if this game was proven in `mode` before and caching a new
+    /// one otherwise.
+    pub fn prove_game_cached(
+        &self,
+        replay: &SnakeGamePrivateInputs,
+        mode: ProofMode,
+        cache: &ProofCache,
+    ) -> Result<SnakeProof> {
+        let key = ProofCache::key(replay, mode, self.vkey())?;
+        if let Some(proof) = cache.get(&key)? {
+            return Ok(proof);
+        }
+        let proof = self.prove_game_with_mode(replay, mode)?;
+        cache.insert(&key, &proof)?;
+        Ok(proof)
+    }
+
+    /// Like [`SnakeProver::prove_game_with_mode`], calling `progress` as
+    /// each phase starts. Checks `cancel` between phases and fails once it
+    /// is cancelled, including after the proof is done. Executing first to
+    /// size the proof costs a fraction of proving it.
+    pub fn prove_game_tracked(
+        &self,
+        replay: &SnakeGamePrivateInputs,
+        mode: ProofMode,
+        mut progress: impl FnMut(ProvePhase),
+        cancel: &CancelToken,
+    ) -> Result<SnakeProof> {
+        cancel.check()?;
+        progress(ProvePhase::Executing);
+        let report = self.execute(replay)?;
+        cancel.check()?;
+        progress(ProvePhase::Proving {
+            cycles: report.cycles,
+        });
+        let proof = self.prove_game_with_mode(replay, mode)?;
+        cancel.check()?;
+        Ok(proof)
+    }
+
+    /// Checks the proof against the program's verification key and that the
+    /// committed values are the ones carried alongside it.
+    pub fn verify_proof(&self, proof: &SnakeProof) -> Result<()> {
+        self.client
+            .verify(&proof.proof, self.vkey(), None)
+            .context("proof verification failed")?;
+
+        ensure!(
+            abi::encode_public_inputs(&proof.public_inputs) == proof.proof.public_values.as_slice(),
+            "public inputs do not match the proof's committed values"
+        );
+        Ok(())
+    }
+
+    /// Checks a bundle of a game proven by this program and returns the
+    /// public inputs it commits to. Groth16 and PLONK bundles are checked
+    /// with `sp1-verifier`, exactly as the browser checks them; core and
+    /// compressed ones with the SDK.
+    pub fn verify_bundle(&self, bundle: &ProofBundle) -> Result<SnakeGamePublicInputs> {
+        bundle.validate().context("invalid proof bundle")?;
+        ensure!(
+            bundle.vkey_hash == self.vkey_hash()?,
+            "the bundle is for another program"
+        );
+        match bundle.kind {
+            ProofKind::Core | ProofKind::Compressed => {
+                let proof: SP1ProofWithPublicValues =
+                    bincode::deserialize(&bundle.proof).context("bundle holds no SP1 proof")?;
+                ensure!(
+                    proof.public_values.as_slice() == bundle.public_values,
+                    "the bundle's public values are not its proof's"
+                );
+                self.verify_proof(&SnakeProof::try_from(proof)?)?;
+            }
+            ProofKind::Groth16 | ProofKind::Plonk => verify_onchain(bundle)?,
+        }
+        Ok(bundle.public_inputs()?)
+    }
+
+    /// Checks a bundle made by any program release in `registry` and
+    /// returns the release and the public inputs. Bundles of this program
+    /// are checked like [`SnakeProver::verify_bundle`]; those of other
+    /// releases only verify as Groth16 or PLONK proofs, since the SDK needs
+    /// the full key of a program to check its other proofs.
+    pub fn verify_registered_bundle(
+        &self,
+        bundle: &ProofBundle,
+        registry: &VkeyRegistry,
+    ) -> Result<(ProgramVersion, SnakeGamePublicInputs)> {
+        let version = registry.check(bundle)?;
+        if bundle.vkey_hash == self.vkey_hash()? {
+            return Ok((version, self.verify_bundle(bundle)?));
+        }
+        bundle.validate().context("invalid proof bundle")?;
+        ensure!(
+            bundle.kind.is_onchain(),
+            "only Groth16 and PLONK bundles of program {version} verify here"
+        );
+        verify_onchain(bundle)?;
+        Ok((version, bundle.public_inputs()?))
+    }
+}
+
+/// Checks a Groth16 or PLONK bundle with `sp1-verifier`, against the vkey
+/// hash it names.
+fn verify_onchain(bundle: &ProofBundle) -> Result<()> {
+    let vkey_hash = format!("0x{}", hex::encode(bundle.vkey_hash));
+    let result = match bundle.kind {
+        ProofKind::Groth16 => sp1_verifier::Groth16Verifier::verify(
+            &bundle.proof,
+            &bundle.public_values,
+            &vkey_hash,
+            &sp1_verifier::GROTH16_VK_BYTES,
+        )
+        .map_err(|error| error.to_string()),
+        ProofKind::Plonk => sp1_verifier::PlonkVerifier::verify(
+            &bundle.proof,
+            &bundle.public_values,
+            &vkey_hash,
+            &sp1_verifier::PLONK_VK_BYTES,
+        )
+        .map_err(|error| error.to_string()),
+        ProofKind::Core | ProofKind::Compressed => bail!("{:?} proofs need the SDK", bundle.kind),
+    };
+    result.map_err(|error| anyhow!("proof verification failed: {error}"))
+}
+
+/// A proof of a versus match together with the public inputs it commits to.
+pub struct VersusProof {
+    pub proof: SP1ProofWithPublicValues,
+    pub public_inputs: VersusPublicInputs,
+}
+
+impl TryFrom<SP1ProofWithPublicValues> for VersusProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proof: SP1ProofWithPublicValues) -> Result<Self> {
+        let public_inputs = abi::decode_versus_inputs(proof.public_values.as_slice())
+            .context("public values are not ABI-encoded versus public inputs")?;
+        Ok(VersusProof {
+            proof,
+            public_inputs,
+        })
+    }
+}
+
+pub struct VersusProver {
+    client: EnvProver,
+    pk: EnvProvingKey,
+}
+
+impl VersusProver {
+    /// Creates a client from the environment and sets up the versus program.
+    pub fn new() -> Result<VersusProver> {
+        VersusProver::with_config(&ProverConfig::from_env()?)
+    }
+
+    /// Like [`VersusProver::new`], on the backend `config` picks.
+    pub fn with_config(config: &ProverConfig) -> Result<VersusProver> {
+        let client = config.client()?;
+        let pk = client
+            .setup(SNAKE_VERSUS_ELF)
+            .context("failed to set up the snake versus program")?;
+        Ok(VersusProver { client, pk })
+    }
+
+    /// Verification key of the versus program.
+    pub fn vkey(&self) -> &SP1VerifyingKey {
+        self.pk.verifying_key()
+    }
+
+    /// Runs the guest on `replay` without proving, returning the values it
+    /// would commit and the execution report (cycle counts).
+    pub fn execute(
+        &self,
+        replay: &VersusPrivateInputs,
+    ) -> Result<(VersusPublicInputs, sp1_sdk::ExecutionReport)> {
+        let (public_values, report) = self
+            .client
+            .execute(SNAKE_VERSUS_ELF, stdin_for(replay))
+            .run()
+            .context("failed to execute match")?;
+        let public_inputs = abi::decode_versus_inputs(public_values.as_slice())
+            .context("public values are not ABI-encoded versus public inputs")?;
+        Ok((public_inputs, report))
+    }
+
+    /// Proves that `replay` is a legal match, producing a proof of the given
+    /// kind. Fails if the guest rejects it.
+    pub fn prove_match(
+        &self,
+        replay: &VersusPrivateInputs,
+        mode: ProofMode,
+    ) -> Result<VersusProof> {
+        let proof = self
+            .client
+            .prove(&self.pk, stdin_for(replay))
+            .mode(mode.into())
+            .run()
+            .with_context(|| format!("failed to generate {mode:?} proof"))?;
+        proof.try_into()
+    }
+
+    /// Checks the proof against the program's verification key and that the
+    /// committed values are the ones carried alongside it.
+    pub fn verify_proof(&self, proof: &VersusProof) -> Result<()> {
+        self.client
+            .verify(&proof.proof, self.vkey(), None)
+            .context("proof verification failed")?;
+
+        ensure!(
+            abi::encode_versus_inputs(&proof.public_inputs) == proof.proof.public_values.as_slice(),
+            "public inputs do not match the proof's committed values"
+        );
+        Ok(())
+    }
+}
+
+/// A proof of a leaderboard together with the public inputs it commits to.
+pub struct LeaderboardProof {
+    pub proof: SP1ProofWithPublicValues,
+    pub public_inputs: LeaderboardPublicInputs,
+}
+
+impl TryFrom<SP1ProofWithPublicValues> for LeaderboardProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proof: SP1ProofWithPublicValues) -> Result<Self> {
+        let public_inputs = abi::decode_leaderboard_inputs(proof.public_values.as_slice())
+            .context("public values are not ABI-encoded leaderboard public inputs")?;
+        Ok(LeaderboardProof {
+            proof,
+            public_inputs,
+        })
+    }
+}
+
+pub struct LeaderboardProver {
+    client: EnvProver,
+    pk: EnvProvingKey,
+    /// Verification key of the single-game program the games were proven
+    /// with.
+    game_vkey: SP1VerifyingKey,
+}
+
+impl LeaderboardProver {
+    /// Creates a client from the environment and sets up the leaderboard
+    /// program for games proven under `game_vkey`, e.g.
+    /// [`SnakeProver::vkey`].
+    pub fn new(game_vkey: SP1VerifyingKey) -> Result<LeaderboardProver> {
+        LeaderboardProver::with_config(&ProverConfig::from_env()?, game_vkey)
+    }
+
+    /// Like [`LeaderboardProver::new`], on the backend `config` picks.
+    pub fn with_config(
+        config: &ProverConfig,
+        game_vkey: SP1VerifyingKey,
+    ) -> Result<LeaderboardProver> {
+        let client = config.client()?;
+        let pk = client
+            .setup(SNAKE_LEADERBOARD_ELF)
+            .context("failed to set up the snake leaderboard program")?;
+        Ok(LeaderboardProver {
+            client,
+            pk,
+            game_vkey,
+        })
+    }
+
+    /// Verification key of the leaderboard program.
+    pub fn vkey(&self) -> &SP1VerifyingKey {
+        self.pk.verifying_key()
+    }
+
+    /// Proves the board of the best `top_n` players among `games`, producing
+    /// a proof of the given kind. Every game must have a compressed proof
+    /// and share one config hash and seed; the guest rejects anything else.
+    pub fn prove_leaderboard(
+        &self,
+        games: &[SnakeProof],
+        top_n: u32,
+        mode: ProofMode,
+    ) -> Result<LeaderboardProof> {
+        let private_inputs = LeaderboardPrivateInputs {
+            vkey_digest: self.game_vkey.hash_u32(),
+            public_values: games
+                .iter()
+                .map(|game| game.proof.public_values.to_vec())
+                .collect(),
+            top_n,
+        };
+        let mut stdin = stdin_for(&private_inputs);
+        write_game_proofs(&mut stdin, games, &self.game_vkey)?;
+
+        let proof = self
+            .client
+            .prove(&self.pk, stdin)
+            .mode(mode.into())
+            .run()
+            .with_context(|| format!("failed to generate {mode:?} proof"))?;
+        proof.try_into()
+    }
+
+    /// Checks the proof against the program's verification key, that it
+    /// aggregates games proven under the expected game key and that the
+    /// committed values are the ones carried alongside it.
+    pub fn verify_proof(&self, proof: &LeaderboardProof) -> Result<()> {
+        self.client
+            .verify(&proof.proof, self.vkey(), None)
+            .context("proof verification failed")?;
+
+        ensure!(
+            proof.public_inputs.game_vkey_digest == self.game_vkey.hash_bytes(),
+            "leaderboard aggregates games of a different program"
+        );
+        ensure!(
+            abi::encode_leaderboard_inputs(&proof.public_inputs)
+                == proof.proof.public_values.as_slice(),
+            "public inputs do not match the proof's committed values"
+        );
+        Ok(())
+    }
+}
+
+/// A proof that a game reached a threshold, with the public inputs it
+/// commits to.
+pub struct ThresholdProof {
+    pub proof: SP1ProofWithPublicValues,
+    pub public_inputs: ThresholdPublicInputs,
+}
+
+impl TryFrom<SP1ProofWithPublicValues> for ThresholdProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proof: SP1ProofWithPublicValues) -> Result<Self> {
+        let public_inputs = abi::decode_threshold_inputs(proof.public_values.as_slice())
+            .context("public values are not ABI-encoded threshold public inputs")?;
+        Ok(ThresholdProof {
+            proof,
+            public_inputs,
+        })
+    }
+}
+
+pub struct ThresholdProver {
+    client: EnvProver,
+    pk: EnvProvingKey,
+}
+
+impl ThresholdProver {
+    /// Creates a client from the environment and sets up the threshold
+    /// program.
+    pub fn new() -> Result<ThresholdProver> {
+        ThresholdProver::with_config(&ProverConfig::from_env()?)
+    }
+
+    /// Like [`ThresholdProver::new`], on the backend `config` picks.
+    pub fn with_config(config: &ProverConfig) -> Result<ThresholdProver> {
+        let client = config.client()?;
+        let pk = client
+            .setup(SNAKE_THRESHOLD_ELF)
+            .context("failed to set up the snake threshold program")?;
+        Ok(ThresholdProver { client, pk })
+    }
+
+    /// Verification key of the threshold program.
+    pub fn vkey(&self) -> &SP1VerifyingKey {
+        self.pk.verifying_key()
+    }
+
+    /// Proves that `replay` is a legal game scoring at least `threshold`,
+    /// producing a proof of the given kind. Fails if the guest rejects it,
+    /// including when the score falls short.
+    pub fn prove_threshold(
+        &self,
+        replay: &SnakeGamePrivateInputs,
+        threshold: u64,
+        mode: ProofMode,
+    ) -> Result<ThresholdProof> {
+        let private_inputs = ThresholdPrivateInputs {
+            game: replay.clone(),
+            threshold,
+        };
+        let proof = self
+            .client
+            .prove(&self.pk, stdin_for(&private_inputs))
+            .mode(mode.into())
+            .run()
+            .with_context(|| format!("failed to generate {mode:?} proof"))?;
+        proof.try_into()
+    }
+
+    /// Checks the proof against the program's verification key and that the
+    /// committed values are the ones carried alongside it.
+    pub fn verify_proof(&self, proof: &ThresholdProof) -> Result<()> {
+        self.client
+            .verify(&proof.proof, self.vkey(), None)
+            .context("proof verification failed")?;
+
+        ensure!(
+            abi::encode_threshold_inputs(&proof.public_inputs)
+                == proof.proof.public_values.as_slice(),
+            "public inputs do not match the proof's committed values"
+        );
+        Ok(())
+    }
+}
+
+/// A proof of the badges a game earned, with the public inputs it commits
+/// to.
+pub struct AchievementProof {
+    pub proof: SP1ProofWithPublicValues,
+    pub public_inputs: AchievementPublicInputs,
+}
+
+impl TryFrom<SP1ProofWithPublicValues> for AchievementProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proof: SP1ProofWithPublicValues) -> Result<Self> {
+        let public_inputs = abi::decode_achievement_inputs(proof.public_values.as_slice())
+            .context("public values are not ABI-encoded achievement public inputs")?;
+        Ok(AchievementProof {
+            proof,
+            public_inputs,
+        })
+    }
+}
+
+pub struct AchievementProver {
+    client: EnvProver,
+    pk: EnvProvingKey,
+}
+
+impl AchievementProver {
+    /// Creates a client from the environment and sets up the achievements
+    /// program.
+    pub fn new() -> Result<AchievementProver> {
+        AchievementProver::with_config(&ProverConfig::from_env()?)
+    }
+
+    /// Like [`AchievementProver::new`], on the backend `config` picks.
+    pub fn with_config(config: &ProverConfig) -> Result<AchievementProver> {
+        let client = config.client()?;
+        let pk = client
+            .setup(SNAKE_ACHIEVEMENTS_ELF)
+            .context("failed to set up the snake achievements program")?;
+        Ok(AchievementProver { client, pk })
+    }
+
+    /// Verification key of the achievements program.
+    pub fn vkey(&self) -> &SP1VerifyingKey {
+        self.pk.verifying_key()
+    }
+
+    /// Proves the badges `replay` earned, producing a proof of the given
+    /// kind. Fails if the guest rejects the game.
+    pub fn prove_achievements(
+        &self,
+        replay: &SnakeGamePrivateInputs,
+        mode: ProofMode,
+    ) -> Result<AchievementProof> {
+        let proof = self
+            .client
+            .prove(&self.pk, stdin_for(replay))
+            .mode(mode.into())
+            .run()
+            .with_context(|| format!("failed to generate {mode:?} proof"))?;
+        proof.try_into()
+    }
+
+    /// Checks the proof against the program's verification key and that the
+    /// committed values are the ones carried alongside it.
+    pub fn verify_proof(&self, proof: &AchievementProof) -> Result<()> {
+        self.client
+            .verify(&proof.proof, self.vkey(), None)
+            .context("proof verification failed")?;
+
+        ensure!(
+            abi::encode_achievement_inputs(&proof.public_inputs)
+                == proof.proof.public_values.as_slice(),
+            "public inputs do not match the proof's committed values"
+        );
+        Ok(())
+    }
+}
+
+pub struct ChunkedProver {
+    client: EnvProver,
+    chunk_pk: EnvProvingKey,
+    chain_pk: EnvProvingKey,
+}
+
+impl ChunkedProver {
+    /// Creates a client from the environment and sets up the chunk and
+    /// chain programs.
+    pub fn new() -> Result<ChunkedProver> {
+        ChunkedProver::with_config(&ProverConfig::from_env()?)
+    }
+
+    /// Like [`ChunkedProver::new`], on the backend `config` picks.
+    pub fn with_config(config: &ProverConfig) -> Result<ChunkedProver> {
+        let client = config.client()?;
+        let chunk_pk = client
+            .setup(SNAKE_CHUNK_ELF)
+            .context("failed to set up the snake chunk program")?;
+        let chain_pk = client
+            .setup(SNAKE_CHAIN_ELF)
+            .context("failed to set up the snake chain program")?;
+        Ok(ChunkedProver {
+            client,
+            chunk_pk,
+            chain_pk,
+        })
+    }
+
+    /// Verification key of the chain program, which final proofs verify
+    /// against.
+    pub fn vkey(&self) -> &SP1VerifyingKey {
+        self.chain_pk.verifying_key()
+    }
+
+    /// Proves that `replay` is a legal game by proving every `chunk_ticks`
+    /// ticks separately as a compressed proof, then joining the chunks into
+    /// a proof of the given kind. The whole game is replayed natively first,
+    /// so an illegal or endless one fails before any proving. Daily
+    /// challenges and player signatures are not checked in chunked proofs,
+    /// so games claiming either are refused.
+    pub fn prove_game_chunked(
+        &self,
+        replay: &SnakeGamePrivateInputs,
+        chunk_ticks: u32,
+        mode: ProofMode,
+    ) -> Result<SnakeProof> {
+        ensure!(chunk_ticks > 0, "chunks must be at least one tick long");
+        ensure!(
+            replay.challenge.is_none() && replay.signature.is_none(),
+            "chunked proofs do not support challenges or signatures"
+        );
+        let moves = moves::decompress_moves(&replay.game_moves).context("invalid move log")?;
+        // Chunks are replayed with stall detection off, and each is paid for
+        // as soon as it is played
+        verify::replay_moves(&replay.level, replay.seed, &replay.food_positions, &moves)
+            .context("game is not legal")?;
+        let mut state = GameState::from_level(replay.level.clone(), replay.seed)
+            .context("level is not playable")?;
+
+        let mut chunks = Vec::new();
+        let mut next_move = 0;
+        while !state.is_game_over() {
+            let end = state.ticks().saturating_add(chunk_ticks);
+            let count = moves[next_move..]
+                .iter()
+                .take_while(|mv| mv.tick < end)
+                .count();
+            let chunk_moves = &moves[next_move..next_move + count];
+            let private_inputs = ChunkPrivateInputs {
+                snapshot: state.to_bytes(),
+                game_moves: moves::compress_moves(chunk_moves),
+                ticks: chunk_ticks,
+            };
+            verify::replay_chunk(&mut state, chunk_moves, chunk_ticks)
+                .with_context(|| format!("chunk {} is not legal", chunks.len()))?;
+
+            let proof = self
+                .client
+                .prove(&self.chunk_pk, stdin_for(&private_inputs))
+                .mode(SP1ProofMode::Compressed)
+                .run()
+                .with_context(|| format!("failed to prove chunk {}", chunks.len()))?;
+            chunks.push(proof);
+            next_move += count;
+        }
+
+        let chunk_vkey = self.chunk_pk.verifying_key();
+        let private_inputs = ChainPrivateInputs {
+            vkey_digest: chunk_vkey.hash_u32(),
+            chunks: chunks
+                .iter()
+                .map(|chunk| chunk.public_values.to_vec())
+                .collect(),
+            final_snapshot: state.to_bytes(),
+            player: replay.player,
+            nonce: replay.nonce,
+        };
+        let mut stdin = stdin_for(&private_inputs);
+        for chunk in chunks {
+            let SP1Proof::Compressed(proof) = chunk.proof else {
+                anyhow::bail!("chunk proof is not compressed");
+            };
+            stdin.write_proof(*proof, chunk_vkey.vk.clone());
+        }
+
+        let proof = self
+            .client
+            .prove(&self.chain_pk, stdin)
+            .mode(mode.into())
+            .run()
+            .with_context(|| format!("failed to generate {mode:?} proof"))?;
+        proof.try_into()
+    }
+
+    /// Checks the proof against the chain program's verification key and
+    /// that the committed values are the ones carried alongside it.
+    pub fn verify_proof(&self, proof: &SnakeProof) -> Result<()> {
+        self.client
+            .verify(&proof.proof, self.vkey(), None)
+            .context("proof verification failed")?;
+
+        ensure!(
+            abi::encode_public_inputs(&proof.public_inputs) == proof.proof.public_values.as_slice(),
+            "public inputs do not match the proof's committed values"
+        );
+        Ok(())
+    }
+}
+
+/// A proof settling several games together with the public inputs it
+/// commits to.
+pub struct AggregateProof {
+    pub proof: SP1ProofWithPublicValues,
+    pub public_inputs: AggregatePublicInputs,
+}
+
+impl TryFrom<SP1ProofWithPublicValues> for AggregateProof {
+    type Error = anyhow::Error;
+
+    fn try_from(proof: SP1ProofWithPublicValues) -> Result<Self> {
+        let public_inputs = abi::decode_aggregate_inputs(proof.public_values.as_slice())
+            .context("public values are not ABI-encoded aggregate public inputs")?;
+        Ok(AggregateProof {
+            proof,
+            public_inputs,
+        })
+    }
+}
+
+pub struct AggregateProver {
+    client: EnvProver,
+    pk: EnvProvingKey,
+    /// Verification key of the single-game program the games were proven
+    /// with.
+    game_vkey: SP1VerifyingKey,
+}
+
+impl AggregateProver {
+    /// Creates a client from the environment and sets up the aggregate
+    /// program for games proven under `game_vkey`, e.g.
+    /// [`SnakeProver::vkey`].
+    pub fn new(game_vkey: SP1VerifyingKey) -> Result<AggregateProver> {
+        AggregateProver::with_config(&ProverConfig::from_env()?, game_vkey)
+    }
+
+    /// Like [`AggregateProver::new`], on the backend `config` picks.
+    pub fn with_config(
+        config: &ProverConfig,
+        game_vkey: SP1VerifyingKey,
+    ) -> Result<AggregateProver> {
+        let client = config.client()?;
+        let pk = client
+            .setup(SNAKE_AGGREGATE_ELF)
+            .context("failed to set up the snake aggregate program")?;
+        Ok(AggregateProver {
+            client,
+            pk,
+            game_vkey,
+        })
+    }
+
+    /// Verification key of the aggregate program.
+    pub fn vkey(&self) -> &SP1VerifyingKey {
+        self.pk.verifying_key()
+    }
+
+    /// Proves `games` together, producing a proof of the given kind that
+    /// commits each game's player, score, config hash, nullifier, seed and
+    /// signing key in order. Every game must have a compressed proof, and
+    /// no game may appear twice.
+    pub fn prove_aggregate(&self, games: &[SnakeProof], mode: ProofMode) -> Result<AggregateProof> {
+        let private_inputs = AggregatePrivateInputs {
+            vkey_digest: self.game_vkey.hash_u32(),
+            public_values: games
+                .iter()
+                .map(|game| game.proof.public_values.to_vec())
+                .collect(),
+        };
+        let mut stdin = stdin_for(&private_inputs);
+        write_game_proofs(&mut stdin, games, &self.game_vkey)?;
+
+        let proof = self
+            .client
+            .prove(&self.pk, stdin)
+            .mode(mode.into())
+            .run()
+            .with_context(|| format!("failed to generate {mode:?} proof"))?;
+        proof.try_into()
+    }
+
+    /// Checks the proof against the program's verification key, that it
+    /// aggregates games proven under the expected game key and that the
+    /// committed values are the ones carried alongside it.
+    pub fn verify_proof(&self, proof: &AggregateProof) -> Result<()> {
+        self.client
+            .verify(&proof.proof, self.vkey(), None)
+            .context("proof verification failed")?;
+
+        ensure!(
+            proof.public_inputs.game_vkey_digest == self.game_vkey.hash_bytes(),
+            "proof aggregates games of a different program"
+        );
+        ensure!(
+            abi::encode_aggregate_inputs(&proof.public_inputs)
+                == proof.proof.public_values.as_slice(),
+            "public inputs do not match the proof's committed values"
+        );
+        Ok(())
+    }
+}
+
+fn stdin_for(replay: &impl serde::Serialize) -> SP1Stdin {
+    let mut stdin = SP1Stdin::new();
+    stdin.write(replay);
+    stdin
+}
+
+/// Passes the proofs of `games` to a guest that verifies them recursively
+/// under `game_vkey`, which only compressed proofs allow.
+fn write_game_proofs(
+    stdin: &mut SP1Stdin,
+    games: &[SnakeProof],
+    game_vkey: &SP1VerifyingKey,
+) -> Result<()> {
+    for (i, game) in games.iter().enumerate() {
+        let SP1Proof::Compressed(proof) = &game.proof.proof else {
+            anyhow::bail!("game {i} needs a compressed proof to be aggregated");
+        };
+        stdin.write_proof(*proof.clone(), game_vkey.vk.clone());
+    }
+    Ok(())
+}
+
+/// One-shot helper for [`SnakeProver::prove_game`].
+pub fn prove_game(replay: &SnakeGamePrivateInputs) -> Result<SnakeProof> {
+    SnakeProver::new()?.prove_game(replay)
+}
+
+/// One-shot helper for [`SnakeProver::verify_proof`].
+pub fn verify_proof(proof: &SnakeProof) -> Result<()> {
+    SnakeProver::new()?.verify_proof(proof)
+}
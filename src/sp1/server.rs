@@ -0,0 +1,607 @@
+//! HTTP service in front of the verifier, for leaderboards that accept
+//! submissions from untrusted clients.
+//!
+//! A submission is a `.skr` replay. It is checked natively with
+//! [`verify::verify_replay_within`] as it arrives, playing at most
+//! [`MAX_SUBMIT_TICKS`] ticks on a grid of at most
+//! [`snake_core::level::MAX_CELLS`] cells, one submission per core at a
+//! time so they cannot take over the host, and stored in a [`ScoreStore`]
+//! under a hash of the game it records, so the same game is stored once
+//! however it is encoded. The `player` a submission credits is taken on
+//! trust: the endpoint does not authenticate it, so a leaderboard that
+//! pays out should rely on the proof's signed `playerKey` instead. Proving is expensive, so it only runs on
+//! request, one game at a time on a dedicated thread; clients poll for the
+//! result, which names the phase a running proof is in, and can cancel one
+//! that runs too long. Cancelling takes effect between phases, see
+//! [`SnakeProver::prove_game_tracked`]. With a [`ProofCache`] a game proven
+//! before, under any submission, is answered from the cache without
+//! proving. With a [`VkeyRegistry`], `POST /verify` accepts bundles of
+//! every registered program release and nothing else, so proofs of a
+//! modified rule set are turned away.
+//! Endpoints, ids and hashes as hex, `limit` defaulting to 10 and capped
+//! at [`MAX_SCORES`]:
+//!
+//! | route                         | does                                            |
+//! |-------------------------------|-------------------------------------------------|
+//! | `POST /submit?player=0x..`    | verify and record a replay, body `.skr` bytes   |
+//! | `POST /verify`                | check a `.skp` proof bundle                     |
+//! | `POST /proofs/{id}`           | start proving a submission                      |
+//! | `GET /proofs/{id}`            | proof status                                    |
+//! | `DELETE /proofs/{id}`         | cancel a queued or running proof                |
+//! | `GET /proofs/{id}/file`       | the proof bundle, once ready                    |
+//! | `GET /scores?limit=n`         | best submissions, highest score first           |
+//! | `GET /scores/{id}`            | one submission                                  |
+//! | `GET /players?limit=n`        | each player's best submission                   |
+//! | `GET /players/{addr}?limit=n` | one player's submissions                        |
+//! | `GET /days/{date}?limit=n`    | best submissions on a UTC `YYYY-MM-DD`          |
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Context, Result};
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use snake_core::bundle::ProofBundle;
+use snake_core::inputs::{SnakeGamePrivateInputs, SnakeGamePublicInputs};
+use snake_core::registry::VkeyRegistry;
+use snake_core::{moves, verify, Replay};
+use tokio::sync::{oneshot, Semaphore};
+
+use crate::cache::ProofCache;
+use crate::prover::{CancelToken, ProofMode, ProvePhase, ProverConfig, SnakeProver};
+use crate::store::{ScoreStore, StoredScore};
+
+/// Most entries a listing returns.
+pub const MAX_SCORES: usize = 100;
+
+/// Most ticks a submitted game may last, far more than any game a person
+/// plays on the browser's 40×25 board.
+pub const MAX_SUBMIT_TICKS: u32 = 1_000_000;
+
+/// Where a submission's proof stands.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ProofStatus {
+    NotRequested,
+    /// Queued, or running in the phase `progress` names.
+    Pending {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        progress: Option<ProvePhase>,
+    },
+    Ready,
+    Failed {
+        error: String,
+    },
+    Cancelled,
+}
+
+/// A submission as the API reports it.
+#[derive(Clone, Debug, Serialize)]
+pub struct ScoreEntry {
+    pub id: String,
+    pub player: String,
+    pub score: u64,
+    pub snake_length: u32,
+    pub ticks: u32,
+    pub seed: u64,
+    pub level_hash: String,
+    pub config_hash: String,
+    /// Unix time of the submission, in seconds.
+    pub submitted_at: i64,
+    pub proof: ProofStatus,
+}
+
+struct VerifyJob {
+    bundle: ProofBundle,
+    reply: oneshot::Sender<Result<SnakeGamePublicInputs>>,
+}
+
+/// What the handlers and the prover thread share.
+struct Shared {
+    store: Mutex<ScoreStore>,
+    /// Proofs queued or failed since startup; a proof that is ready is
+    /// recorded in the store instead.
+    jobs: Mutex<HashMap<[u8; 32], ProofStatus>>,
+    /// Cancels each queued or running proof.
+    cancels: Mutex<HashMap<[u8; 32], CancelToken>>,
+    /// Where proof files are written.
+    proof_dir: PathBuf,
+    proof_cache: Option<ProofCache>,
+}
+
+impl Shared {
+    fn store(&self) -> MutexGuard<'_, ScoreStore> {
+        // A handler that panicked mid-query leaves nothing half-written
+        self.store
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner())
+    }
+
+    fn jobs(&self) -> MutexGuard<'_, HashMap<[u8; 32], ProofStatus>> {
+        self.jobs
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner())
+    }
+
+    fn cancels(&self) -> MutexGuard<'_, HashMap<[u8; 32], CancelToken>> {
+        self.cancels
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner())
+    }
+
+    fn status(&self, score: &StoredScore) -> ProofStatus {
+        match score.proof {
+            Some(_) => ProofStatus::Ready,
+            None => (self.jobs().get(&score.replay_hash))
+                .cloned()
+                .unwrap_or(ProofStatus::NotRequested),
+        }
+    }
+
+    fn entry(&self, score: &StoredScore) -> ScoreEntry {
+        ScoreEntry {
+            id: hex::encode(score.replay_hash),
+            player: format!("0x{}", hex::encode(score.player)),
+            score: score.score,
+            snake_length: score.snake_length,
+            ticks: score.ticks,
+            seed: score.seed,
+            level_hash: format!("0x{}", hex::encode(score.level_hash)),
+            config_hash: format!("0x{}", hex::encode(score.config_hash)),
+            submitted_at: score.submitted_at,
+            proof: self.status(score),
+        }
+    }
+
+    fn entries(&self, scores: Result<Vec<StoredScore>>) -> Result<Json<Vec<ScoreEntry>>, ApiError> {
+        let scores = scores.map_err(ApiError::internal)?;
+        Ok(Json(scores.iter().map(|score| self.entry(score)).collect()))
+    }
+}
+
+/// Shared state of the handlers.
+#[derive(Clone)]
+pub struct AppState {
+    shared: Arc<Shared>,
+    prove_jobs: mpsc::Sender<[u8; 32]>,
+    verify_jobs: mpsc::Sender<VerifyJob>,
+    /// One per core; a submission holds one while it is checked.
+    replay_checks: Arc<Semaphore>,
+}
+
+impl AppState {
+    /// Sets up the guest program on a proving and a verifying thread, both
+    /// on the backend `config` picks, and waits until both are ready.
+    /// Submissions go to `store`; proofs are made in `mode`, or taken from
+    /// `proof_cache`, and written to `proof_dir`. Without a `registry` only
+    /// bundles of this server's own program verify.
+    pub fn start(
+        config: &ProverConfig,
+        mode: ProofMode,
+        store: ScoreStore,
+        proof_dir: PathBuf,
+        proof_cache: Option<ProofCache>,
+        registry: Option<VkeyRegistry>,
+    ) -> Result<AppState> {
+        std::fs::create_dir_all(&proof_dir)
+            .with_context(|| format!("failed to create {}", proof_dir.display()))?;
+        let shared = Arc::new(Shared {
+            store: Mutex::new(store),
+            jobs: Mutex::default(),
+            cancels: Mutex::default(),
+            proof_dir,
+            proof_cache,
+        });
+        let (prove_jobs, prove_queue) = mpsc::channel();
+        let prover_shared = Arc::clone(&shared);
+        spawn_worker("prover", config, move |prover| {
+            for id in prove_queue {
+                let cancel = (prover_shared.cancels().get(&id).cloned()).unwrap_or_default();
+                let result = prove(&prover, &prover_shared, &id, mode, &cancel);
+                prover_shared.cancels().remove(&id);
+                let status = match result {
+                    Ok(()) => None,
+                    Err(_) if cancel.is_cancelled() => Some(ProofStatus::Cancelled),
+                    Err(error) => Some(ProofStatus::Failed {
+                        error: format!("{error:#}"),
+                    }),
+                };
+                let mut jobs = prover_shared.jobs();
+                match status {
+                    Some(status) => jobs.insert(id, status),
+                    None => jobs.remove(&id),
+                };
+            }
+        })?;
+        let (verify_jobs, verify_queue) = mpsc::channel::<VerifyJob>();
+        spawn_worker("verifier", config, move |prover| {
+            for job in verify_queue {
+                let result = match &registry {
+                    Some(registry) => prover
+                        .verify_registered_bundle(&job.bundle, registry)
+                        .map(|(_, public_inputs)| public_inputs),
+                    None => prover.verify_bundle(&job.bundle),
+                };
+                let _ = job.reply.send(result);
+            }
+        })?;
+        let cores = thread::available_parallelism().map_or(1, |cores| cores.get());
+        Ok(AppState {
+            shared,
+            prove_jobs,
+            verify_jobs,
+            replay_checks: Arc::new(Semaphore::new(cores)),
+        })
+    }
+}
+
+/// The API routes over `state`.
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/submit", post(submit))
+        .route("/verify", post(verify_proof))
+        .route(
+            "/proofs/:id",
+            post(request_proof).get(proof_status).delete(cancel_proof),
+        )
+        .route("/proofs/:id/file", get(proof_file))
+        .route("/scores", get(scores))
+        .route("/scores/:id", get(score))
+        .route("/players", get(players))
+        .route("/players/:player", get(player_scores))
+        .route("/days/:day", get(day_scores))
+        .with_state(state)
+}
+
+/// Runs `work` on a named thread that owns its own [`SnakeProver`], once
+/// the prover is set up.
+fn spawn_worker(
+    name: &str,
+    config: &ProverConfig,
+    work: impl FnOnce(SnakeProver) + Send + 'static,
+) -> Result<()> {
+    let (ready, setup) = mpsc::channel();
+    let config = config.clone();
+    thread::Builder::new()
+        .name(name.to_owned())
+        .spawn(move || match SnakeProver::with_config(&config) {
+            Ok(prover) => {
+                let _ = ready.send(Ok(()));
+                work(prover);
+            }
+            Err(error) => {
+                let _ = ready.send(Err(error));
+            }
+        })
+        .with_context(|| format!("failed to start the {name} thread"))?;
+    setup
+        .recv()
+        .map_err(|_| anyhow!("the {name} thread exited during setup"))?
+}
+
+/// Proves submission `id`, or finds it in the cache, writes the proof file
+/// and records it. Each phase is reported as the job's progress until
+/// `cancel` stops it.
+fn prove(
+    prover: &SnakeProver,
+    shared: &Shared,
+    id: &[u8; 32],
+    mode: ProofMode,
+    cancel: &CancelToken,
+) -> Result<()> {
+    let score = (shared.store().get(id)?).context("the submission is gone")?;
+    let replay = Replay::decode(&score.replay).context("stored replay is corrupt")?;
+    let mut inputs =
+        SnakeGamePrivateInputs::from_seeded_game(&replay.level, replay.seed, &replay.moves)
+            .context("stored replay does not match the seeded game")?;
+    inputs.player = score.player;
+    let started_at = SystemTime::now();
+    let progress = |phase| {
+        let status = ProofStatus::Pending {
+            progress: Some(phase),
+        };
+        shared.jobs().insert(*id, status);
+    };
+    let proof = match &shared.proof_cache {
+        Some(cache) => {
+            let key = ProofCache::key(&inputs, mode, prover.vkey())?;
+            match cache.get(&key)? {
+                Some(proof) => proof,
+                None => {
+                    let proof = prover.prove_game_tracked(&inputs, mode, progress, cancel)?;
+                    cache.insert(&key, &proof)?;
+                    proof
+                }
+            }
+        }
+        None => prover.prove_game_tracked(&inputs, mode, progress, cancel)?,
+    };
+    let bundle = proof.to_bundle(&inputs, prover.vkey(), started_at, SystemTime::now())?;
+    let path = shared.proof_dir.join(format!("{}.skp", hex::encode(id)));
+    std::fs::write(&path, bundle.encode()).context("failed to write proof")?;
+    shared.store().set_proof(id, &path.to_string_lossy())?;
+    Ok(())
+}
+
+/// An error response: the status and a JSON `{ "error": message }` body.
+pub struct ApiError(StatusCode, String);
+
+impl ApiError {
+    fn bad_request(error: impl std::fmt::Display) -> ApiError {
+        ApiError(StatusCode::BAD_REQUEST, error.to_string())
+    }
+
+    fn not_found(what: &str) -> ApiError {
+        ApiError(StatusCode::NOT_FOUND, format!("no such {what}"))
+    }
+
+    fn unavailable(what: &str) -> ApiError {
+        ApiError(
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("the {what} has stopped"),
+        )
+    }
+
+    fn internal(error: anyhow::Error) -> ApiError {
+        ApiError(StatusCode::INTERNAL_SERVER_ERROR, format!("{error:#}"))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({ "error": self.1 });
+        (self.0, Json(body)).into_response()
+    }
+}
+
+fn parse_hex<const N: usize>(text: &str, what: &str) -> Result<[u8; N], ApiError> {
+    hex::decode(text.trim_start_matches("0x"))
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| ApiError::bad_request(format!("{what} must be {N}-byte hex")))
+}
+
+/// The stored submission `id`, hex in the URL.
+fn find(shared: &Shared, id: &str) -> Result<StoredScore, ApiError> {
+    let id = parse_hex(id, "ids")?;
+    (shared.store().get(&id))
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::not_found("submission"))
+}
+
+#[derive(Deserialize)]
+struct SubmitParams {
+    /// Address credited with the score, defaults to zero. Nothing checks
+    /// that the submitter controls it.
+    player: Option<String>,
+}
+
+/// The id a replay is stored under: SHA-256 of its level hash, seed (little
+/// endian) and move log in the [`moves::compress_moves`] encoding. Encodings
+/// of the same game that differ elsewhere, e.g. in the claimed score, share
+/// it.
+fn replay_key(replay: &Replay) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(replay.level.hash())
+        .chain_update(replay.seed.to_le_bytes())
+        .chain_update(moves::compress_moves(&replay.moves))
+        .finalize()
+        .into()
+}
+
+async fn submit(
+    State(state): State<AppState>,
+    Query(params): Query<SubmitParams>,
+    body: Bytes,
+) -> Result<Json<ScoreEntry>, ApiError> {
+    let player = match params.player {
+        Some(player) => parse_hex(&player, "player")?,
+        None => [0; 20],
+    };
+    let replay = Replay::decode(&body).map_err(ApiError::bad_request)?;
+    let replay_hash = replay_key(&replay);
+    let known = state.shared.store().get(&replay_hash);
+    if let Some(score) = known.map_err(ApiError::internal)? {
+        return Ok(Json(state.shared.entry(&score)));
+    }
+    let permit = (Arc::clone(&state.replay_checks).acquire_owned().await)
+        .map_err(|_| ApiError::unavailable("verifier"))?;
+    let score = tokio::task::spawn_blocking(move || {
+        let _permit = permit;
+        let outcome = verify::verify_replay_within(&replay, MAX_SUBMIT_TICKS)
+            .map_err(ApiError::bad_request)?;
+        let submitted_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as i64);
+        Ok::<_, ApiError>(StoredScore {
+            replay_hash,
+            player,
+            score: outcome.score,
+            snake_length: outcome.snake_length,
+            ticks: outcome.ticks,
+            seed: outcome.seed,
+            level_hash: outcome.level_hash,
+            config_hash: outcome.config_hash,
+            replay: body.to_vec(),
+            proof: None,
+            submitted_at,
+        })
+    })
+    .await
+    .map_err(|_| ApiError::unavailable("verifier"))??;
+    // A concurrent submission of the same replay may have won the race
+    let stored = {
+        let store = state.shared.store();
+        store.insert(&score).map_err(ApiError::internal)?;
+        (store.get(&replay_hash))
+            .map_err(ApiError::internal)?
+            .unwrap_or(score)
+    };
+    Ok(Json(state.shared.entry(&stored)))
+}
+
+/// The values a checked proof commits, hashes and addresses as hex.
+#[derive(Serialize)]
+struct VerifiedProof {
+    score: u64,
+    snake_length: u32,
+    seed: u64,
+    wrap_walls: bool,
+    game_state_hash: String,
+    level_hash: String,
+    config_hash: String,
+    player: String,
+}
+
+impl From<&SnakeGamePublicInputs> for VerifiedProof {
+    fn from(inputs: &SnakeGamePublicInputs) -> VerifiedProof {
+        VerifiedProof {
+            score: inputs.score,
+            snake_length: inputs.snake_length,
+            seed: inputs.seed,
+            wrap_walls: inputs.wrap_walls,
+            game_state_hash: format!("0x{}", hex::encode(inputs.game_state_hash)),
+            level_hash: format!("0x{}", hex::encode(inputs.level_hash)),
+            config_hash: format!("0x{}", hex::encode(inputs.config_hash)),
+            player: format!("0x{}", hex::encode(inputs.player)),
+        }
+    }
+}
+
+async fn verify_proof(
+    State(state): State<AppState>,
+    body: Bytes,
+) -> Result<Json<VerifiedProof>, ApiError> {
+    let bundle = ProofBundle::decode(&body).map_err(ApiError::bad_request)?;
+    let (reply, result) = oneshot::channel();
+    (state.verify_jobs)
+        .send(VerifyJob { bundle, reply })
+        .map_err(|_| ApiError::unavailable("verifier"))?;
+    let public_inputs = result
+        .await
+        .map_err(|_| ApiError::unavailable("verifier"))?
+        .map_err(|error| ApiError::bad_request(format!("{error:#}")))?;
+    Ok(Json(VerifiedProof::from(&public_inputs)))
+}
+
+/// Queues a submission for proving, unless it is already proven or
+/// queued; a failed or cancelled proof is retried.
+async fn request_proof(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<(StatusCode, Json<ProofStatus>), ApiError> {
+    let score = find(&state.shared, &id)?;
+    let status = state.shared.status(&score);
+    if !matches!(
+        status,
+        ProofStatus::NotRequested | ProofStatus::Failed { .. } | ProofStatus::Cancelled
+    ) {
+        return Ok((StatusCode::ACCEPTED, Json(status)));
+    }
+    let pending = ProofStatus::Pending { progress: None };
+    (state.shared.jobs()).insert(score.replay_hash, pending.clone());
+    (state.shared.cancels()).insert(score.replay_hash, CancelToken::new());
+    if state.prove_jobs.send(score.replay_hash).is_err() {
+        state.shared.jobs().remove(&score.replay_hash);
+        state.shared.cancels().remove(&score.replay_hash);
+        return Err(ApiError::unavailable("prover"));
+    }
+    Ok((StatusCode::ACCEPTED, Json(pending)))
+}
+
+/// Cancels a queued or running proof. It stays pending until the prover
+/// thread reaches the next phase and marks it cancelled.
+async fn cancel_proof(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<(StatusCode, Json<ProofStatus>), ApiError> {
+    let score = find(&state.shared, &id)?;
+    let cancel = (state.shared.cancels().get(&score.replay_hash).cloned())
+        .ok_or_else(|| ApiError::not_found("pending proof"))?;
+    cancel.cancel();
+    Ok((StatusCode::ACCEPTED, Json(state.shared.status(&score))))
+}
+
+async fn proof_status(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<ProofStatus>, ApiError> {
+    let score = find(&state.shared, &id)?;
+    Ok(Json(state.shared.status(&score)))
+}
+
+async fn proof_file(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Vec<u8>, ApiError> {
+    let score = find(&state.shared, &id)?;
+    let path = score
+        .proof
+        .ok_or_else(|| ApiError::not_found("proof yet"))?;
+    tokio::fs::read(&path)
+        .await
+        .map_err(|error| ApiError::internal(anyhow!("failed to read {path}: {error}")))
+}
+
+#[derive(Deserialize)]
+struct ListParams {
+    limit: Option<usize>,
+}
+
+impl ListParams {
+    fn limit(&self) -> usize {
+        self.limit.unwrap_or(10).min(MAX_SCORES)
+    }
+}
+
+async fn scores(
+    State(state): State<AppState>,
+    Query(params): Query<ListParams>,
+) -> Result<Json<Vec<ScoreEntry>>, ApiError> {
+    let scores = state.shared.store().top(params.limit());
+    state.shared.entries(scores)
+}
+
+async fn score(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<ScoreEntry>, ApiError> {
+    let score = find(&state.shared, &id)?;
+    Ok(Json(state.shared.entry(&score)))
+}
+
+async fn players(
+    State(state): State<AppState>,
+    Query(params): Query<ListParams>,
+) -> Result<Json<Vec<ScoreEntry>>, ApiError> {
+    let scores = state.shared.store().player_bests(params.limit());
+    state.shared.entries(scores)
+}
+
+async fn player_scores(
+    State(state): State<AppState>,
+    Path(player): Path<String>,
+    Query(params): Query<ListParams>,
+) -> Result<Json<Vec<ScoreEntry>>, ApiError> {
+    let player = parse_hex(&player, "player")?;
+    let scores = state.shared.store().for_player(&player, params.limit());
+    state.shared.entries(scores)
+}
+
+async fn day_scores(
+    State(state): State<AppState>,
+    Path(day): Path<String>,
+    Query(params): Query<ListParams>,
+) -> Result<Json<Vec<ScoreEntry>>, ApiError> {
+    let scores = state.shared.store().day(&day, params.limit());
+    state.shared.entries(scores)
+}
@@ -0,0 +1,201 @@
+//! SQLite storage for verified submissions.
+//!
+//! Only games that passed [`snake_core::verify::verify_replay`] are stored,
+//! keyed by a hash of their level, seed and moves. Their `.skr` bytes are
+//! kept so a game can be proven or re-checked later. Scores are compared within a rule set by
+//! filtering on the config hash, which the queries leave to the caller.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS scores (
+    replay_hash  BLOB PRIMARY KEY,
+    player       BLOB NOT NULL,
+    score        INTEGER NOT NULL,
+    snake_length INTEGER NOT NULL,
+    ticks        INTEGER NOT NULL,
+    seed         INTEGER NOT NULL,
+    level_hash   BLOB NOT NULL,
+    config_hash  BLOB NOT NULL,
+    replay       BLOB NOT NULL,
+    proof        TEXT,
+    submitted_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS scores_by_score ON scores (score DESC, submitted_at);
+CREATE INDEX IF NOT EXISTS scores_by_player ON scores (player, score DESC);
+";
+
+const COLUMNS: &str = "replay_hash, player, score, snake_length, ticks, seed, level_hash, \
+                       config_hash, replay, proof, submitted_at";
+
+/// One verified submission.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoredScore {
+    /// SHA-256 of the level hash, seed and move log, see the server's
+    /// `submit`.
+    pub replay_hash: [u8; 32],
+    pub player: [u8; 20],
+    pub score: u64,
+    pub snake_length: u32,
+    pub ticks: u32,
+    pub seed: u64,
+    pub level_hash: [u8; 32],
+    pub config_hash: [u8; 32],
+    /// The `.skr` bytes.
+    pub replay: Vec<u8>,
+    /// Where the proof is kept, once proven, e.g. a file path.
+    pub proof: Option<String>,
+    /// Unix time of the submission, in seconds.
+    pub submitted_at: i64,
+}
+
+pub struct ScoreStore {
+    conn: Connection,
+}
+
+impl ScoreStore {
+    /// Opens or creates the database at `path`.
+    pub fn open(path: &Path) -> Result<ScoreStore> {
+        let conn =
+            Connection::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+        ScoreStore::with_connection(conn)
+    }
+
+    /// A database that lives as long as the store, for tests and demos.
+    pub fn open_in_memory() -> Result<ScoreStore> {
+        ScoreStore::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<ScoreStore> {
+        conn.execute_batch(SCHEMA)
+            .context("failed to create the score tables")?;
+        Ok(ScoreStore { conn })
+    }
+
+    /// Stores `score` and returns `true`, or returns `false` if a
+    /// submission with the same replay is already stored.
+    pub fn insert(&self, score: &StoredScore) -> Result<bool> {
+        let inserted = self.conn.execute(
+            &format!(
+                "INSERT OR IGNORE INTO scores ({COLUMNS}) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)"
+            ),
+            params![
+                score.replay_hash,
+                score.player,
+                to_sql(score.score)?,
+                score.snake_length,
+                score.ticks,
+                // Seeds use all 64 bits, so they are stored bit for bit
+                score.seed as i64,
+                score.level_hash,
+                score.config_hash,
+                score.replay,
+                score.proof,
+                score.submitted_at,
+            ],
+        )?;
+        Ok(inserted == 1)
+    }
+
+    /// Records where the proof of submission `replay_hash` is kept.
+    /// Returns `false` if there is no such submission.
+    pub fn set_proof(&self, replay_hash: &[u8; 32], proof: &str) -> Result<bool> {
+        let updated = self.conn.execute(
+            "UPDATE scores SET proof = ?2 WHERE replay_hash = ?1",
+            params![replay_hash, proof],
+        )?;
+        Ok(updated == 1)
+    }
+
+    pub fn get(&self, replay_hash: &[u8; 32]) -> Result<Option<StoredScore>> {
+        let query = format!("SELECT {COLUMNS} FROM scores WHERE replay_hash = ?1");
+        let score = (self.conn)
+            .query_row(&query, params![replay_hash], from_row)
+            .optional()?;
+        Ok(score)
+    }
+
+    /// The `limit` best submissions, highest score first and earliest
+    /// first among equal scores.
+    pub fn top(&self, limit: usize) -> Result<Vec<StoredScore>> {
+        self.query(
+            &format!(
+                "SELECT {COLUMNS} FROM scores \
+                 ORDER BY score DESC, submitted_at, replay_hash LIMIT ?1"
+            ),
+            params![to_sql(limit)?],
+        )
+    }
+
+    /// Each player's best submission, for the `limit` best players.
+    pub fn player_bests(&self, limit: usize) -> Result<Vec<StoredScore>> {
+        // SQLite takes the other columns from the row holding the MAX
+        self.query(
+            &format!(
+                "SELECT {COLUMNS} FROM ( \
+                     SELECT *, MAX(score) FROM scores GROUP BY player \
+                 ) ORDER BY score DESC, submitted_at, replay_hash LIMIT ?1"
+            ),
+            params![to_sql(limit)?],
+        )
+    }
+
+    /// Every submission of `player`, best first.
+    pub fn for_player(&self, player: &[u8; 20], limit: usize) -> Result<Vec<StoredScore>> {
+        self.query(
+            &format!(
+                "SELECT {COLUMNS} FROM scores WHERE player = ?1 \
+                 ORDER BY score DESC, submitted_at, replay_hash LIMIT ?2"
+            ),
+            params![player, to_sql(limit)?],
+        )
+    }
+
+    /// The `limit` best submissions made on `day`, a UTC date as
+    /// `YYYY-MM-DD`, e.g. for a daily-challenge leaderboard.
+    pub fn day(&self, day: &str, limit: usize) -> Result<Vec<StoredScore>> {
+        self.query(
+            &format!(
+                "SELECT {COLUMNS} FROM scores WHERE date(submitted_at, 'unixepoch') = ?1 \
+                 ORDER BY score DESC, submitted_at, replay_hash LIMIT ?2"
+            ),
+            params![day, to_sql(limit)?],
+        )
+    }
+
+    fn query(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<StoredScore>> {
+        let mut statement = self.conn.prepare_cached(sql)?;
+        let scores = statement
+            .query_map(params, from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(scores)
+    }
+}
+
+/// SQLite integers are signed; scores and limits past `i64::MAX` are
+/// refused rather than wrapped.
+fn to_sql<T: TryInto<i64>>(value: T) -> Result<i64> {
+    value
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("value does not fit an SQLite integer"))
+}
+
+fn from_row(row: &Row) -> rusqlite::Result<StoredScore> {
+    Ok(StoredScore {
+        replay_hash: row.get(0)?,
+        player: row.get(1)?,
+        score: row.get::<_, i64>(2)? as u64,
+        snake_length: row.get(3)?,
+        ticks: row.get(4)?,
+        seed: row.get::<_, i64>(5)? as u64,
+        level_hash: row.get(6)?,
+        config_hash: row.get(7)?,
+        replay: row.get(8)?,
+        proof: row.get(9)?,
+        submitted_at: row.get(10)?,
+    })
+}
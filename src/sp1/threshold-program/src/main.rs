@@ -0,0 +1,45 @@
+//! Snake Threshold Verifier
+//! This is an SP1 program that proves a Snake game reached a score without
+//! revealing it. It reads a recorded game and a threshold from stdin,
+//! replays the moves with the shared `snake-core` engine and commits only
+//! the threshold, config hash and player as ABI-encoded public values; the
+//! score, moves and final board stay private.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use snake_core::inputs::{ThresholdPrivateInputs, ThresholdPublicInputs};
+use snake_core::{abi, hash, moves, verify, SnakeError};
+
+// Replays the game and returns the values to commit, or an error if the
+// recording is not a legal game or falls short of the threshold
+pub fn snake_threshold_verifier(
+    private_inputs: &ThresholdPrivateInputs,
+) -> Result<ThresholdPublicInputs, SnakeError> {
+    let game = &private_inputs.game;
+    let moves = moves::decompress_moves(&game.game_moves)?;
+    let final_state = verify::replay_moves(&game.level, game.seed, &game.food_positions, &moves)?;
+
+    // Failing here rather than committing a flag means a proof exists only
+    // for games that pass, so it reveals a single bit
+    if final_state.score() < private_inputs.threshold {
+        return Err(SnakeError::BelowThreshold);
+    }
+
+    Ok(ThresholdPublicInputs {
+        threshold: private_inputs.threshold,
+        config_hash: hash::config_hash(&game.level),
+        player: game.player,
+    })
+}
+
+pub fn main() {
+    let private_inputs = sp1_zkvm::io::read::<ThresholdPrivateInputs>();
+
+    // An illegal or short game aborts execution, so no proof can be
+    // produced for it
+    let public_inputs = snake_threshold_verifier(&private_inputs)
+        .unwrap_or_else(|err| panic!("invalid threshold claim: {err}"));
+
+    sp1_zkvm::io::commit_slice(&abi::encode_threshold_inputs(&public_inputs));
+}
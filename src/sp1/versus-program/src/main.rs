@@ -0,0 +1,56 @@
+//! Snake Versus Verifier
+//! This is an SP1 program that settles a two-player Snake match. It reads a
+//! recorded match from stdin, replays both players' moves with the shared
+//! `snake-core` engine and commits both scores, the winner, the final match
+//! hash, level hash, seed, config hash and both players as ABI-encoded
+//! public values.
+
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use snake_core::inputs::{VersusPrivateInputs, VersusPublicInputs};
+use snake_core::versus::PLAYERS;
+use snake_core::{abi, hash, moves, verify, SnakeError};
+
+// Replays the match and returns the values to commit, or an error if the
+// recording is not a legal match
+pub fn snake_versus_verifier(
+    private_inputs: &VersusPrivateInputs,
+) -> Result<VersusPublicInputs, SnakeError> {
+    let level = &private_inputs.level;
+    let [first, second] = &private_inputs.game_moves;
+    let moves = [
+        moves::decompress_moves(first)?,
+        moves::decompress_moves(second)?,
+    ];
+    let food_positions = verify::to_positions(&private_inputs.food_positions);
+
+    // Both snakes move through the shared engine tick by tick, so every
+    // collision between them is judged exactly as in the browser
+    let final_state = verify::replay_versus(
+        level,
+        private_inputs.seed,
+        &food_positions,
+        [&moves[0], &moves[1]],
+    )?;
+
+    Ok(VersusPublicInputs {
+        match_state_hash: final_state.state_hash(),
+        scores: [final_state.score(0), final_state.score(1)],
+        winner: final_state.winner().unwrap_or(PLAYERS) as u8,
+        level_hash: level.hash(),
+        seed: private_inputs.seed,
+        config_hash: hash::config_hash(level),
+        players: private_inputs.players,
+    })
+}
+
+pub fn main() {
+    let private_inputs = sp1_zkvm::io::read::<VersusPrivateInputs>();
+
+    // An illegal match aborts execution, so no proof can be produced for it
+    let public_inputs = snake_versus_verifier(&private_inputs)
+        .unwrap_or_else(|err| panic!("invalid versus replay: {err}"));
+
+    sp1_zkvm::io::commit_slice(&abi::encode_versus_inputs(&public_inputs));
+}
@@ -18,15 +18,10 @@
     ;; Check if score is valid (multiple of 10 or 0)
     (if (i32.eq (i32.rem_s (local.get $score) (i32.const 10)) (i32.const 0))
       (then
-        ;; Check if snake length matches expected length (with ±1 tolerance)
-        (if (i32.le_s 
-              (i32.abs 
-                (i32.sub 
-                  (local.get $expected_length) 
-                  (local.get $snake_length)
-                )
-              )
-              (i32.const 1)
+        ;; Check if snake length matches expected length exactly
+        (if (i32.eq 
+              (local.get $expected_length) 
+              (local.get $snake_length)
             )
           (then (return (i32.const 1)))  ;; Valid score
           (else (return (i32.const 0)))  ;; Invalid length
@@ -0,0 +1,218 @@
+//! A `requestAnimationFrame` loop that plays a game without any JS loop
+//! code: each frame runs the steps a `TickScheduler` says are due and
+//! hands the result to a draw callback.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{event, Direction, GameState};
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &'static str = r#"
+/** Passed to the `on_frame` callback of `GameState.run_loop`. */
+export interface FrameEvent {
+    steps: number;
+    progress: number;
+    snake: Int32Array;
+    foods: Int32Array;
+    score: bigint;
+}
+"#;
+
+/// Everything a loop owns. It is borrowed while Rust runs a frame, so the
+/// game's event callbacks, which fire inside a frame, must not call back
+/// into the loop; the draw callback runs after the borrow ends and may.
+struct Runner {
+    game: GameState,
+    scheduler: snake_core::scheduler::TickScheduler,
+    on_frame: Option<js_sys::Function>,
+    /// The rAF callback, kept alive as long as the loop.
+    frame: Option<Closure<dyn FnMut(f64)>>,
+    /// Id of the pending frame request, `None` while stopped.
+    request: Option<i32>,
+    /// Resolve and reject functions of `finished`, until it settles.
+    settle: Option<(js_sys::Function, js_sys::Function)>,
+    finished: js_sys::Promise,
+}
+
+impl Runner {
+    fn request_frame(&mut self) -> Result<(), JsError> {
+        if self.request.is_some() {
+            return Ok(());
+        }
+        let (Some(window), Some(frame)) = (web_sys::window(), &self.frame) else {
+            return Err(JsError::new("no window to animate in"));
+        };
+        let request = window
+            .request_animation_frame(frame.as_ref().unchecked_ref())
+            .map_err(crate::js_error)?;
+        self.request = Some(request);
+        Ok(())
+    }
+
+    fn cancel_frame(&mut self) {
+        if let (Some(request), Some(window)) = (self.request.take(), web_sys::window()) {
+            let _ = window.cancel_animation_frame(request);
+        }
+    }
+
+    /// Stops the loop and settles `finished`: resolved with the score, or
+    /// rejected with `error`.
+    fn finish(&mut self, error: Option<JsValue>) {
+        self.cancel_frame();
+        if let Some((resolve, reject)) = self.settle.take() {
+            let _ = match error {
+                Some(error) => reject.call1(&JsValue::NULL, &error),
+                None => resolve.call1(&JsValue::NULL, &self.game.score().into()),
+            };
+        }
+    }
+}
+
+/// A game played by the browser's animation frames, started with
+/// `GameState::run_loop`. Freeing it stops the loop.
+#[wasm_bindgen]
+pub struct GameLoop {
+    runner: Rc<RefCell<Runner>>,
+}
+
+impl GameLoop {
+    pub(crate) fn start(
+        game: GameState,
+        on_frame: Option<js_sys::Function>,
+    ) -> Result<GameLoop, JsError> {
+        let mut settle = None;
+        let finished =
+            js_sys::Promise::new(&mut |resolve, reject| settle = Some((resolve, reject)));
+        let runner = Rc::new(RefCell::new(Runner {
+            game,
+            scheduler: snake_core::scheduler::TickScheduler::new(),
+            on_frame,
+            frame: None,
+            request: None,
+            settle,
+            finished,
+        }));
+        // A weak handle, so the closure does not keep its own loop alive
+        let handle = Rc::downgrade(&runner);
+        let frame = Closure::<dyn FnMut(f64)>::new(move |now_ms| {
+            if let Some(runner) = handle.upgrade() {
+                run_frame(&runner, now_ms);
+            }
+        });
+        runner.borrow_mut().frame = Some(frame);
+        if !runner.borrow().game.is_paused() {
+            runner.borrow_mut().request_frame()?;
+        }
+        Ok(GameLoop { runner })
+    }
+
+    fn runner(&self) -> Result<std::cell::RefMut<'_, Runner>, JsError> {
+        self.runner
+            .try_borrow_mut()
+            .map_err(|_| JsError::new("the game loop cannot be used from a game event callback"))
+    }
+}
+
+#[wasm_bindgen]
+impl GameLoop {
+    /// Pauses the game and stops requesting frames.
+    pub fn pause(&self) -> Result<(), JsError> {
+        let mut runner = self.runner()?;
+        runner.game.pause();
+        runner.cancel_frame();
+        Ok(())
+    }
+
+    /// Resumes a paused game. Time spent paused is not played out.
+    pub fn resume(&self) -> Result<(), JsError> {
+        let mut runner = self.runner()?;
+        if runner.settle.is_none() {
+            return Err(JsError::new("the game loop has finished"));
+        }
+        runner.game.resume();
+        runner.scheduler.reset();
+        runner.request_frame()
+    }
+
+    /// Stops the loop for good; `finished` resolves with the score so far.
+    pub fn cancel(&self) -> Result<(), JsError> {
+        self.runner()?.finish(None);
+        Ok(())
+    }
+
+    /// Whether a frame is pending, i.e. the loop is neither paused nor
+    /// finished.
+    pub fn is_running(&self) -> Result<bool, JsError> {
+        Ok(self.runner()?.request.is_some())
+    }
+
+    /// Resolves with the score once the game ends or the loop is
+    /// cancelled; rejects if a step throws.
+    #[wasm_bindgen(unchecked_return_type = "Promise<bigint>")]
+    pub fn finished(&self) -> Result<js_sys::Promise, JsError> {
+        Ok(self.runner()?.finished.clone())
+    }
+
+    /// Queues a turn for the running game, as `GameState::queue_direction`.
+    pub fn queue_direction(&self, direction: Direction) -> Result<(), JsError> {
+        self.runner()?.game.queue_direction(direction)
+    }
+
+    /// A copy of the game as it stands.
+    pub fn state(&self) -> Result<GameState, JsError> {
+        Ok(self.runner()?.game.inner.clone().into())
+    }
+}
+
+impl Drop for GameLoop {
+    fn drop(&mut self) {
+        if let Ok(mut runner) = self.runner.try_borrow_mut() {
+            runner.cancel_frame();
+        }
+    }
+}
+
+/// One animation frame: runs the steps due, settles `finished` if the
+/// game is over, and otherwise asks for the next frame. The draw callback
+/// gets `{ steps, progress, snake, foods, score }`, coordinates as in
+/// `GameState::snake_coords`; if it throws, the loop stops and `finished`
+/// rejects.
+fn run_frame(runner: &Rc<RefCell<Runner>>, now_ms: f64) {
+    let (on_frame, payload) = {
+        let mut runner = runner.borrow_mut();
+        runner.request = None;
+        let interval = runner.game.current_tick_interval_ms();
+        let due = runner.scheduler.advance(now_ms, interval);
+        let steps = match runner.game.step_many(due) {
+            Ok(steps) => steps,
+            Err(error) => {
+                runner.finish(Some(error.into()));
+                return;
+            }
+        };
+        if runner.game.is_game_over() {
+            runner.finish(None);
+        } else if !runner.game.is_paused() {
+            if let Err(error) = runner.request_frame() {
+                runner.finish(Some(error.into()));
+            }
+        }
+        let interval = runner.game.current_tick_interval_ms();
+        let payload = event(&[
+            ("steps", steps.into()),
+            ("progress", runner.scheduler.progress(interval).into()),
+            ("snake", runner.game.snake_coords().into()),
+            ("foods", runner.game.food_coords().into()),
+            ("score", runner.game.score().into()),
+        ]);
+        (runner.on_frame.clone(), payload)
+    };
+    if let Some(on_frame) = on_frame {
+        if let Err(error) = on_frame.call1(&JsValue::NULL, &payload) {
+            runner.borrow_mut().finish(Some(error));
+        }
+    }
+}
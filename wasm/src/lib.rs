@@ -1,6 +1,149 @@
+use snake_core::StepOutcome;
 use wasm_bindgen::prelude::*;
 
+#[cfg(feature = "browser")]
+mod game_loop;
+
+#[cfg(feature = "net")]
+mod net;
+
+#[cfg(feature = "browser")]
+pub use game_loop::GameLoop;
+#[cfg(feature = "net")]
+pub use net::LiveStream;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &'static str = r#"
+/** A grid cell. */
+export interface Cell {
+    x: number;
+    y: number;
+}
+
+export type DirectionName = "Up" | "Down" | "Left" | "Right";
+
+export type FoodKindName = "Normal" | "Golden" | "Poison";
+
+export interface FoodRules {
+    first?: Cell | null;
+    count?: number;
+    golden_percent?: number;
+    poison_percent?: number;
+    lifetime_ticks?: number;
+}
+
+export interface ScoringRules {
+    mode?: "Food" | "Survival";
+    points_per_food?: number;
+    points_per_golden_food?: number;
+    growth_per_food?: number;
+    survival_points_per_tick?: number;
+    combo_window_ticks?: number;
+    max_combo_multiplier?: number;
+}
+
+export interface SpeedStep {
+    min_score: number;
+    interval_ms: number;
+}
+
+export interface Difficulty {
+    base_interval_ms?: number;
+    steps?: SpeedStep[];
+}
+
+/** A level, as taken by `GameState.from_level_json` after `JSON.stringify`. */
+export interface GameConfig {
+    grid_width: number;
+    grid_height: number;
+    wrap_walls?: boolean;
+    obstacles?: Cell[];
+    start: Cell;
+    start_direction: DirectionName;
+    food?: FoodRules;
+    scoring?: ScoringRules;
+    illegal_moves?: "Ignore" | "EndGame";
+    difficulty?: Difficulty;
+    lives?: number;
+    max_ticks?: number;
+    shrink_interval_ticks?: number;
+}
+
+/** A direction change taking effect on the step taken at `tick`. */
+export interface Move {
+    tick: number;
+    direction: DirectionName;
+}
+
+/** A decoded `.skr` replay, from `decode_replay`. */
+export interface Replay {
+    engine_version: number;
+    level: Required<GameConfig>;
+    seed: number;
+    score: number;
+    moves: Move[];
+}
+
+/** A ChaCha8 stream inside `GameStateJson`. */
+export interface RngJson {
+    /** 8 32-bit words. */
+    key: number[];
+    /** Outputs drawn so far; a u64, exact in a `number` up to 2^53. */
+    position: number;
+}
+
+/** The whole game, from `GameState.to_js`. */
+export interface GameStateJson {
+    snake: Cell[];
+    foods: Cell[];
+    food_kinds: FoodKindName[];
+    food_ticks: number[];
+    direction: DirectionName;
+    level: Required<GameConfig>;
+    seed: number;
+    rng: RngJson;
+    kind_rng: RngJson;
+    score: number;
+    ticks: number;
+    pending_growth: number;
+    lives: number;
+    combo: number;
+    last_food_tick: number;
+    game_over: boolean;
+    moves: Move[];
+    queued: DirectionName[];
+    paused: boolean;
+    countdown: number;
+    /** Head of the tick hash chain, 32 bytes; see `tick_chain`. */
+    tick_chain: number[];
+}
+
+/** Passed to the `GameState.on_food_eaten` callback. */
+export interface FoodEatenEvent {
+    x: number;
+    y: number;
+    /** 0 normal, 1 golden, 2 poison. */
+    kind: number;
+    score: bigint;
+    combo: number;
+}
+
+/** Passed to the `GameState.on_game_over` callback. */
+export interface GameOverEvent {
+    score: bigint;
+    ticks: number;
+    length: number;
+}
+
+/** Passed to the `GameState.on_level_up` callback. */
+export interface LevelUpEvent {
+    level: number;
+    interval_ms: number;
+}
+"#;
+
 #[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Position {
     x: i32,
     y: i32,
@@ -12,64 +155,899 @@ impl Position {
     pub fn new(x: i32, y: i32) -> Position {
         Position { x, y }
     }
+    
+    #[wasm_bindgen(getter)]
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn y(&self) -> i32 {
+        self.y
+    }
+    
+    /// Compares coordinates; JS `===` only compares object identity.
+    pub fn equals(&self, other: &Position) -> bool {
+        self == other
+    }
+}
+
+impl From<snake_core::Position> for Position {
+    fn from(pos: snake_core::Position) -> Self {
+        Position::new(pos.x, pos.y)
+    }
+}
+
+impl From<Position> for snake_core::Position {
+    fn from(pos: Position) -> Self {
+        snake_core::Position::new(pos.x, pos.y)
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl From<Direction> for snake_core::Direction {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::Up => snake_core::Direction::Up,
+            Direction::Down => snake_core::Direction::Down,
+            Direction::Left => snake_core::Direction::Left,
+            Direction::Right => snake_core::Direction::Right,
+        }
+    }
+}
+
+impl From<snake_core::Direction> for Direction {
+    fn from(direction: snake_core::Direction) -> Self {
+        match direction {
+            snake_core::Direction::Up => Direction::Up,
+            snake_core::Direction::Down => Direction::Down,
+            snake_core::Direction::Left => Direction::Left,
+            snake_core::Direction::Right => Direction::Right,
+        }
+    }
+}
+
+/// Computer player strategies, see `snake_core::bot::Strategy`.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BotStrategy {
+    Greedy,
+    AStar,
+    Hamiltonian,
+}
+
+impl From<BotStrategy> for snake_core::bot::Strategy {
+    fn from(strategy: BotStrategy) -> Self {
+        match strategy {
+            BotStrategy::Greedy => snake_core::bot::Strategy::Greedy,
+            BotStrategy::AStar => snake_core::bot::Strategy::AStar,
+            BotStrategy::Hamiltonian => snake_core::bot::Strategy::Hamiltonian,
+        }
+    }
 }
 
 #[wasm_bindgen]
 pub struct GameState {
-    snake: Vec<Position>,
-    food: Position,
-    grid_width: i32,
-    grid_height: i32,
+    inner: snake_core::GameState,
+    /// The move log in its byte encoding, appended to as moves are made
+    /// and lent to JS by `moves_view`.
+    move_bytes: Vec<u8>,
+    on_food_eaten: Option<js_sys::Function>,
+    on_game_over: Option<js_sys::Function>,
+    on_level_up: Option<js_sys::Function>,
+    /// A recorded run played alongside, see `attach_ghost`.
+    ghost: Option<snake_core::ReplayPlayer>,
+    /// Where each step is streamed, see `attach_stream`.
+    #[cfg(feature = "net")]
+    stream: Option<LiveStream>,
+}
+
+impl From<snake_core::GameState> for GameState {
+    fn from(inner: snake_core::GameState) -> GameState {
+        GameState {
+            inner,
+            move_bytes: Vec::new(),
+            on_food_eaten: None,
+            on_game_over: None,
+            on_level_up: None,
+            ghost: None,
+            #[cfg(feature = "net")]
+            stream: None,
+        }
+    }
 }
 
 #[wasm_bindgen]
 impl GameState {
+    /// Starts a new game. Food placement is derived from `seed`; with
+    /// `wrap_walls` the snake passes through the edges.
     #[wasm_bindgen(constructor)]
-    pub fn new(grid_width: i32, grid_height: i32) -> GameState {
-        let initial_x = grid_width / 2;
-        let initial_y = grid_height / 2;
-        
-        let snake = vec![
-            Position::new(initial_x, initial_y),
-            Position::new(initial_x - 1, initial_y),
-            Position::new(initial_x - 2, initial_y),
-        ];
-        
-        GameState {
-            snake,
-            food: Position::new(0, 0),
+    pub fn new(grid_width: i32, grid_height: i32, seed: u64, wrap_walls: bool) -> GameState {
+        snake_core::GameState::new(grid_width, grid_height, seed, wrap_walls).into()
+    }
+    
+    /// Starts a game with obstacle tiles, given as flat `[x0, y0, x1, y1, ...]`
+    /// coordinates. Throws if an obstacle is off the grid or under the snake.
+    pub fn with_obstacles(
+        grid_width: i32,
+        grid_height: i32,
+        seed: u64,
+        wrap_walls: bool,
+        obstacles: &[i32],
+    ) -> Result<GameState, JsError> {
+        Ok(snake_core::GameState::with_obstacles(
             grid_width,
             grid_height,
+            seed,
+            wrap_walls,
+            cells(obstacles)?,
+        )?
+        .into())
+    }
+    
+    /// Starts the daily challenge of `date`, a `YYYY-MM-DD` day, on a
+    /// classic board: every player of the same `domain_salt` gets the same
+    /// seed that day (see `snake_core::challenge`). Throws if the date is
+    /// not a real day.
+    pub fn daily_challenge(
+        grid_width: i32,
+        grid_height: i32,
+        wrap_walls: bool,
+        date: &str,
+        domain_salt: &[u8],
+    ) -> Result<GameState, JsError> {
+        let seed = snake_core::challenge::seed_for_date(date, domain_salt)?;
+        Ok(GameState::new(grid_width, grid_height, seed, wrap_walls))
+    }
+    
+    /// Starts a game on a level given as JSON (see `snake_core::Level`).
+    /// Throws if the JSON is malformed or the level is not playable.
+    pub fn from_level_json(json: &str, seed: u64) -> Result<GameState, JsError> {
+        let level = snake_core::Level::from_json(json)?;
+        Ok(snake_core::GameState::from_level(level, seed)?.into())
+    }
+    
+    /// Identity of the level, as committed by the prover. Always SHA-256,
+    /// whichever commitment hash (`hash::commitment`) the crate is built
+    /// with.
+    pub fn level_hash(&self) -> Vec<u8> {
+        self.inner.level().hash().to_vec()
+    }
+    
+    /// Hash of the level and engine version, as committed by the prover.
+    pub fn config_hash(&self) -> Vec<u8> {
+        snake_core::hash::config_hash(self.inner.level()).to_vec()
+    }
+    
+    /// Restores a game saved with `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<GameState, JsError> {
+        Ok(snake_core::GameState::from_bytes(bytes)?.into())
+    }
+    
+    /// Saves the whole game, RNG included, so it can be resumed later.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.inner.to_bytes()
+    }
+    
+    /// The whole game as a plain JS object (`snake`, `foods`, `score`,
+    /// `level`, `ticks`, ...) for debugging overlays and state stores.
+    /// Scores past 2^53 lose precision; use `to_bytes` to save games.
+    #[wasm_bindgen(unchecked_return_type = "GameStateJson")]
+    pub fn to_js(&self) -> Result<JsValue, JsError> {
+        js_sys::JSON::parse(&self.inner.to_json()).map_err(js_error)
+    }
+    
+    pub fn score(&self) -> u64 {
+        self.inner.score()
+    }
+    
+    /// Number of segments in the snake, including the head.
+    pub fn length(&self) -> u32 {
+        self.inner.snake().len() as u32
+    }
+    
+    /// Column of the first food item, or -1 once the board is full.
+    pub fn food_x(&self) -> i32 {
+        self.inner.foods().first().map_or(-1, |food| food.x)
+    }
+    
+    /// Row of the first food item, or -1 once the board is full.
+    pub fn food_y(&self) -> i32 {
+        self.inner.foods().first().map_or(-1, |food| food.y)
+    }
+    
+    /// All food items as flat `[x0, y0, x1, y1, ...]` coordinates.
+    pub fn foods(&self) -> Vec<i32> {
+        self.inner.foods().iter().flat_map(|cell| [cell.x, cell.y]).collect()
+    }
+    
+    /// The snake, head first, as flat `[x0, y0, x1, y1, ...]` coordinates
+    /// in one typed array, so a renderer can draw it with a single call.
+    pub fn snake_coords(&self) -> js_sys::Int32Array {
+        let coords: Vec<i32> = self.inner.snake().iter().flat_map(|cell| [cell.x, cell.y]).collect();
+        js_sys::Int32Array::from(&coords[..])
+    }
+    
+    /// Food items as a typed array of the same layout as `snake_coords`,
+    /// in the same order as `foods`.
+    pub fn food_coords(&self) -> js_sys::Int32Array {
+        js_sys::Int32Array::from(&self.foods()[..])
+    }
+    
+    /// Kind of each food item (0 normal, 1 golden, 2 poison), in the same
+    /// order as `foods`.
+    pub fn food_kinds(&self) -> Vec<u8> {
+        self.inner.food_kinds().iter().map(|kind| kind.to_byte()).collect()
+    }
+    
+    /// Ticks until each food item despawns, in the same order as `foods`;
+    /// empty when food on this level never expires.
+    pub fn food_ticks_left(&self) -> Vec<u32> {
+        let lifetime = self.inner.level().food.lifetime_ticks;
+        if lifetime == 0 {
+            return Vec::new();
         }
+        let ticks = self.inner.ticks();
+        self.inner
+            .food_ticks()
+            .iter()
+            .map(|&spawned| lifetime.saturating_sub(ticks.saturating_sub(spawned)))
+            .collect()
+    }
+    
+    pub fn head(&self) -> Position {
+        self.inner.head().into()
+    }
+    
+    /// Cells that changed since the last call, as flat `[x0, y0, x1, y1,
+    /// ...]` coordinates, for renderers that redraw only what moved. The
+    /// first call returns every snake and food cell.
+    pub fn take_dirty_cells(&mut self) -> Vec<i32> {
+        self.inner.take_dirty_cells().iter().flat_map(|cell| [cell.x, cell.y]).collect()
     }
     
-    pub fn check_collision(&self, head_x: i32, head_y: i32) -> bool {
-        // Check wall collision
-        if head_x < 0 || head_x >= self.grid_width || head_y < 0 || head_y >= self.grid_height {
-            return true;
+    pub fn is_game_over(&self) -> bool {
+        self.inner.is_game_over()
+    }
+    
+    /// Current combo multiplier, 1 when no combo is running.
+    pub fn combo_multiplier(&self) -> u32 {
+        self.inner.combo_multiplier()
+    }
+    
+    /// Whether score comes from ticks survived rather than food.
+    pub fn is_survival_mode(&self) -> bool {
+        self.inner.scoring_mode() == snake_core::rules::ScoringMode::Survival
+    }
+    
+    /// Lives left, including the current one.
+    pub fn lives(&self) -> u8 {
+        self.inner.lives()
+    }
+    
+    /// Ticks left on a timed level, or `undefined` without a limit.
+    pub fn ticks_left(&self) -> Option<u32> {
+        self.inner.ticks_left()
+    }
+    
+    /// Playable area as `[min_x, min_y, max_x, max_y]`, corners inclusive.
+    /// Cells outside it are lethal; on shrinking levels it loses a ring
+    /// every few ticks.
+    pub fn arena(&self) -> Vec<i32> {
+        let arena = self.inner.arena();
+        vec![arena.min.x, arena.min.y, arena.max.x, arena.max.y]
+    }
+    
+    pub fn wrap_walls(&self) -> bool {
+        self.inner.wrap_walls()
+    }
+    
+    /// Obstacle tiles as flat `[x0, y0, x1, y1, ...]` coordinates.
+    pub fn obstacles(&self) -> Vec<i32> {
+        self.inner.obstacles().iter().flat_map(|cell| [cell.x, cell.y]).collect()
+    }
+    
+    /// Throws with every broken invariant listed if the game state is not
+    /// structurally sound, e.g. after restoring it from storage.
+    pub fn validate_invariants(&self) -> Result<(), JsError> {
+        let report = self.inner.validate_invariants();
+        if report.is_ok() {
+            Ok(())
+        } else {
+            Err(JsError::new(&report.to_string()))
         }
-        
-        // Check self collision (skip the head)
-        for i in 1..self.snake.len() {
-            if head_x == self.snake[i].x && head_y == self.snake[i].y {
-                return true;
+    }
+    
+    /// Throws if the head could not occupy the given cell.
+    pub fn check_collision(&self, head_x: i32, head_y: i32) -> Result<(), JsError> {
+        Ok(self.inner.check_collision(snake_core::Position::new(head_x, head_y))?)
+    }
+    
+    /// Throws if `score` is inconsistent with the snake length.
+    pub fn verify_score(&self, score: u64) -> Result<(), JsError> {
+        Ok(self.inner.verify_score(score)?)
+    }
+    
+    /// Turns the snake for the next tick. Throws (leaving the direction
+    /// unchanged) when reversing into the neck or after game over. On levels
+    /// with the `EndGame` illegal-move policy, reversing or repeating the
+    /// current direction also ends the game.
+    pub fn change_direction(&mut self, direction: Direction) -> Result<(), JsError> {
+        Ok(self.inner.change_direction(direction.into())?)
+    }
+    
+    /// Buffers a turn for the next free tick, so two quick key presses
+    /// within one tick both count. Throws when the queue is full or after
+    /// game over.
+    pub fn queue_direction(&mut self, direction: Direction) -> Result<(), JsError> {
+        Ok(self.inner.queue_direction(direction.into())?)
+    }
+    
+    /// Holds the game; `step` does nothing until `resume`.
+    pub fn pause(&mut self) {
+        self.inner.pause();
+    }
+    
+    pub fn resume(&mut self) {
+        self.inner.resume();
+    }
+    
+    pub fn is_paused(&self) -> bool {
+        self.inner.is_paused()
+    }
+    
+    /// Makes the next `steps` calls to `step` count down before the snake
+    /// starts moving.
+    pub fn set_countdown(&mut self, steps: u32) {
+        self.inner.set_countdown(steps);
+    }
+    
+    /// Milliseconds until the next `step`, following the level's speed
+    /// schedule at the current score.
+    pub fn current_tick_interval_ms(&self) -> u32 {
+        self.inner.current_tick_interval_ms()
+    }
+    
+    /// Countdown steps left, 0 once the snake is moving.
+    pub fn countdown(&self) -> u32 {
+        self.inner.countdown()
+    }
+    
+    /// The recorded direction changes, encoded for the SP1 verifier.
+    pub fn export_moves(&self) -> Vec<u8> {
+        self.inner.export_moves()
+    }
+    
+    /// The 32 bytes the player signs with their Ed25519 key (e.g. through
+    /// `crypto.subtle.sign`) once the game is over, binding the proof to
+    /// them: the SHA-256 of the compressed move log and the seed, whichever
+    /// commitment hash (`hash::commitment`) the crate is built with.
+    pub fn move_digest(&self) -> Vec<u8> {
+        let moves = snake_core::moves::compress_moves(self.inner.moves());
+        snake_core::hash::move_digest(&moves, self.inner.seed()).to_vec()
+    }
+    
+    /// The same bytes as `export_moves`, as a view into wasm memory rather
+    /// than a copy. The view is only valid until the next call into this
+    /// module: any call may grow the memory, which detaches it, and moves
+    /// made later are not in it. Read or copy it straight away, and call
+    /// again for a fresh view.
+    pub fn moves_view(&mut self) -> js_sys::Uint8Array {
+        let moves = self.inner.moves();
+        let encoded = self.move_bytes.len() / snake_core::moves::MOVE_SIZE;
+        self.move_bytes
+            .extend_from_slice(&snake_core::moves::encode_moves(&moves[encoded..]));
+        // SAFETY: the view borrows `move_bytes`, which is neither moved
+        // nor reallocated before control returns to JS, and the contract
+        // above forbids using it once any other call has run
+        unsafe { js_sys::Uint8Array::view(&self.move_bytes) }
+    }
+    
+    /// The game as a `.skr` replay file, for download or upload to the
+    /// prover.
+    pub fn export_replay(&self) -> Vec<u8> {
+        snake_core::Replay::from_game(&self.inner).encode()
+    }
+    
+    /// The replay as short `skr.`-prefixed text for sharing in a chat
+    /// message or URL fragment.
+    pub fn export_share_string(&self) -> String {
+        snake_core::Replay::from_game(&self.inner).to_share_string()
+    }
+    
+    /// The turn the given bot would make now, for the "watch the AI" mode.
+    /// Pass it to `change_direction` to play it.
+    pub fn bot_direction(&self, strategy: BotStrategy) -> Direction {
+        snake_core::bot::Strategy::from(strategy).next_direction(&self.inner).into()
+    }
+    
+    /// Advances the game by one tick. Returns `false` if the snake crashed;
+    /// throws if the game was already over. Registered event callbacks run
+    /// before it returns; one that throws makes `step` throw, after the
+    /// tick has been taken.
+    pub fn step(&mut self) -> Result<bool, JsError> {
+        let foods = self.inner.foods().to_vec();
+        let kinds = self.inner.food_kinds().to_vec();
+        let speed_level = self.speed_level();
+        let outcome = self.advance()?;
+        self.sync_ghost();
+
+        let mut result = Ok(());
+        if let (StepOutcome::AteFood, Some(callback)) = (outcome, &self.on_food_eaten) {
+            let head = self.inner.head();
+            let kind = (foods.iter().zip(&kinds))
+                .find(|&(&food, _)| food == head)
+                .map_or(0, |(_, kind)| kind.to_byte());
+            let payload = event(&[
+                ("x", head.x.into()),
+                ("y", head.y.into()),
+                ("kind", kind.into()),
+                ("score", self.inner.score().into()),
+                ("combo", self.inner.combo_multiplier().into()),
+            ]);
+            result = result.and(callback.call1(&JsValue::NULL, &payload).map(drop));
+        }
+        if let (true, Some(callback)) = (self.speed_level() > speed_level, &self.on_level_up) {
+            let payload = event(&[
+                ("level", (self.speed_level() as u32).into()),
+                ("interval_ms", self.inner.current_tick_interval_ms().into()),
+            ]);
+            result = result.and(callback.call1(&JsValue::NULL, &payload).map(drop));
+        }
+        if let (true, Some(callback)) = (self.inner.is_game_over(), &self.on_game_over) {
+            let payload = event(&[
+                ("score", self.inner.score().into()),
+                ("ticks", self.inner.ticks().into()),
+                ("length", self.length().into()),
+            ]);
+            result = result.and(callback.call1(&JsValue::NULL, &payload).map(drop));
+        }
+        result.map_err(js_error)?;
+        Ok(outcome != StepOutcome::GameOver)
+    }
+    
+    /// Advances up to `steps` ticks in one call, for fast-forwarding, and
+    /// returns how many ran before the game ended or was paused. Event
+    /// callbacks run for every tick, as with `step`.
+    pub fn step_many(&mut self, steps: u32) -> Result<u32, JsError> {
+        let no_callbacks =
+            self.on_food_eaten.is_none() && self.on_game_over.is_none() && self.on_level_up.is_none();
+        if no_callbacks && !self.is_streaming() {
+            let ticks = self.inner.step_many(steps)?;
+            self.sync_ghost();
+            return Ok(ticks);
+        }
+        let start = self.inner.ticks();
+        for _ in 0..steps {
+            if self.inner.is_game_over() || self.inner.is_paused() {
+                break;
+            }
+            self.step()?;
+        }
+        Ok(self.inner.ticks() - start)
+    }
+    
+    /// Calls `callback` with `{ x, y, kind, score, combo }` whenever the
+    /// snake eats, `kind` as in `food_kinds`. Pass `undefined` to remove it.
+    pub fn on_food_eaten(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "((event: FoodEatenEvent) => void) | undefined")]
+        callback: Option<js_sys::Function>,
+    ) {
+        self.on_food_eaten = callback;
+    }
+    
+    /// Calls `callback` with `{ score, ticks, length }` once the game ends.
+    /// Pass `undefined` to remove it.
+    pub fn on_game_over(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "((event: GameOverEvent) => void) | undefined")]
+        callback: Option<js_sys::Function>,
+    ) {
+        self.on_game_over = callback;
+    }
+    
+    /// Calls `callback` with `{ level, interval_ms }` whenever the score
+    /// reaches the next step of the level's speed schedule. Pass
+    /// `undefined` to remove it.
+    pub fn on_level_up(
+        &mut self,
+        #[wasm_bindgen(unchecked_param_type = "((event: LevelUpEvent) => void) | undefined")]
+        callback: Option<js_sys::Function>,
+    ) {
+        self.on_level_up = callback;
+    }
+    
+    /// Plays the game on `requestAnimationFrame`, pacing steps with a
+    /// `TickScheduler`, and calls `on_frame` after every frame to draw it
+    /// (see `GameLoop`). Takes the game over: control it through the
+    /// returned loop, and await `finished()` for the end.
+    #[cfg(feature = "browser")]
+    pub fn run_loop(
+        self,
+        #[wasm_bindgen(unchecked_param_type = "((frame: FrameEvent) => void) | undefined")]
+        on_frame: Option<js_sys::Function>,
+    ) -> Result<GameLoop, JsError> {
+        GameLoop::start(self, on_frame)
+    }
+    
+    /// Plays `ghost`, a replay of an earlier run, in lockstep with this
+    /// game: after every step it is at the same tick, or at its end if it
+    /// ended sooner. It never affects this game. A ghost whose move log
+    /// turns out not to replay is dropped. Takes the player over.
+    pub fn attach_ghost(&mut self, ghost: ReplayPlayer) {
+        self.ghost = Some(ghost.inner);
+        self.sync_ghost();
+    }
+    
+    pub fn detach_ghost(&mut self) {
+        self.ghost = None;
+    }
+    
+    pub fn has_ghost(&self) -> bool {
+        self.ghost.is_some()
+    }
+    
+    /// The ghost's snake, laid out as `snake_coords`, or `undefined`
+    /// without a ghost.
+    pub fn ghost_snake_coords(&self) -> Option<js_sys::Int32Array> {
+        let ghost = self.ghost.as_ref()?.state();
+        let coords: Vec<i32> = ghost.snake().iter().flat_map(|cell| [cell.x, cell.y]).collect();
+        Some(js_sys::Int32Array::from(&coords[..]))
+    }
+    
+    /// The ghost's score at its current tick, or `undefined` without a
+    /// ghost.
+    pub fn ghost_score(&self) -> Option<u64> {
+        self.ghost.as_ref().map(|ghost| ghost.state().score())
+    }
+    
+    /// Streams every step from now on to `stream`, and the replay once the
+    /// game ends. Takes the stream over.
+    #[cfg(feature = "net")]
+    pub fn attach_stream(&mut self, stream: LiveStream) {
+        self.stream = Some(stream);
+    }
+    
+    /// Stops streaming and closes the connection.
+    #[cfg(feature = "net")]
+    pub fn detach_stream(&mut self) {
+        self.stream = None;
+    }
+    
+    /// One step of the game, streamed if a stream is attached. Stepping
+    /// a finished game fails as usual and sends nothing.
+    fn advance(&mut self) -> Result<StepOutcome, snake_core::SnakeError> {
+        #[cfg(feature = "net")]
+        if let (Some(stream), false) = (&self.stream, self.inner.is_game_over()) {
+            let (outcome, delta) = snake_core::spectate::step_with_outcome(&mut self.inner)?;
+            if let Some(delta) = delta {
+                stream.send_step(delta, &self.inner);
             }
+            return Ok(outcome);
         }
-        
+        self.inner.step()
+    }
+    
+    #[cfg(feature = "net")]
+    fn is_streaming(&self) -> bool {
+        self.stream.is_some()
+    }
+    
+    #[cfg(not(feature = "net"))]
+    fn is_streaming(&self) -> bool {
         false
     }
     
-    pub fn verify_score(&self, score: i32) -> bool {
-        // Each food gives 10 points
-        let expected_length = 3 + (score / 10);
-        let actual_length = self.snake.len() as i32;
-        
-        // Allow some flexibility in length verification
-        (expected_length - actual_length).abs() <= 1 && (score % 10 == 0 || score == 0)
+    fn sync_ghost(&mut self) {
+        let ticks = self.inner.ticks();
+        if let Some(ghost) = &mut self.ghost {
+            if ghost.seek(ticks).is_err() {
+                self.ghost = None;
+            }
+        }
+    }
+    
+    fn speed_level(&self) -> usize {
+        self.inner.level().difficulty.speed_level(self.inner.score())
+    }
+    
+    /// Commitment to the current state under the commitment hash
+    /// (`hash::commitment`), byte-for-byte identical to the
+    /// `game_state_hash` committed by the SP1 program.
+    pub fn state_hash(&self) -> Vec<u8> {
+        self.inner.state_hash().to_vec()
+    }
+    
+    /// The state hash after game over, or `undefined` while still playing.
+    pub fn final_state_hash(&self) -> Option<Vec<u8>> {
+        self.inner.final_state_hash().map(|hash| hash.to_vec())
+    }
+    
+    /// Head of the hash chain over every tick so far; at game over it is
+    /// the `tickChain` the SP1 program commits.
+    pub fn tick_chain(&self) -> Vec<u8> {
+        self.inner.tick_chain().to_vec()
     }
 }
 
+/// Paces a game loop driven by `requestAnimationFrame`: pass each frame's
+/// timestamp and the game's `current_tick_interval_ms` to `advance`, then
+/// run that many steps with `step_many`.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct TickScheduler {
+    inner: snake_core::scheduler::TickScheduler,
+}
+
+#[wasm_bindgen]
+impl TickScheduler {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> TickScheduler {
+        TickScheduler::default()
+    }
+    
+    /// Steps due at `now_ms`, e.g. `performance.now()`. The first call
+    /// only starts the clock, and a long gap is capped at a few steps.
+    pub fn advance(&mut self, now_ms: f64, interval_ms: u32) -> u32 {
+        self.inner.advance(now_ms, interval_ms)
+    }
+    
+    /// Fraction of the way to the next tick, for interpolated drawing.
+    pub fn progress(&self, interval_ms: u32) -> f64 {
+        self.inner.progress(interval_ms)
+    }
+    
+    /// Call on resume so time spent paused is not played out.
+    pub fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+/// Steps through a recorded game using the same engine that produced it.
+#[wasm_bindgen]
+pub struct ReplayPlayer {
+    inner: snake_core::ReplayPlayer,
+}
+
+#[wasm_bindgen]
+impl ReplayPlayer {
+    /// `moves` is a log produced by `GameState::export_moves` for a game
+    /// started with the same grid size, seed, wall mode and obstacles (flat
+    /// `[x0, y0, ...]` coordinates).
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        grid_width: i32,
+        grid_height: i32,
+        seed: u64,
+        wrap_walls: bool,
+        obstacles: &[i32],
+        moves: &[u8],
+    ) -> Result<ReplayPlayer, JsError> {
+        let moves = snake_core::moves::decode_moves(moves)?;
+        let initial = snake_core::GameState::with_obstacles(
+            grid_width,
+            grid_height,
+            seed,
+            wrap_walls,
+            cells(obstacles)?,
+        )?;
+        Ok(ReplayPlayer {
+            inner: snake_core::ReplayPlayer::new(initial, moves),
+        })
+    }
+    
+    /// Loads a `.skr` replay produced by `GameState::export_replay`.
+    pub fn from_replay(replay: &[u8]) -> Result<ReplayPlayer, JsError> {
+        let replay = snake_core::Replay::decode(replay)?;
+        Ok(ReplayPlayer {
+            inner: snake_core::ReplayPlayer::new(replay.initial_state()?, replay.moves),
+        })
+    }
+    
+    /// Loads a replay shared with `GameState::export_share_string`. Throws
+    /// if the text was cut short or altered.
+    pub fn from_share_string(text: &str) -> Result<ReplayPlayer, JsError> {
+        let replay = snake_core::Replay::from_share_string(text)?;
+        Ok(ReplayPlayer {
+            inner: snake_core::ReplayPlayer::new(replay.initial_state()?, replay.moves),
+        })
+    }
+    
+    pub fn tick(&self) -> u32 {
+        self.inner.tick()
+    }
+    
+    pub fn is_finished(&self) -> bool {
+        self.inner.is_finished()
+    }
+    
+    /// Advances playback by one tick. Returns `false` if the snake crashed.
+    pub fn step(&mut self) -> Result<bool, JsError> {
+        Ok(self.inner.step()? != StepOutcome::GameOver)
+    }
+    
+    /// Jumps to `tick`, rewinding if necessary.
+    pub fn seek(&mut self, tick: u32) -> Result<(), JsError> {
+        Ok(self.inner.seek(tick)?)
+    }
+    
+    /// A copy of the game state at the current tick.
+    pub fn state(&self) -> GameState {
+        self.inner.state().clone().into()
+    }
+}
+
+/// A `.skr` replay as a plain JS object, for tools that inspect or edit
+/// recordings. Throws if the file is corrupt.
+#[wasm_bindgen(unchecked_return_type = "Replay")]
+pub fn decode_replay(replay: &[u8]) -> Result<JsValue, JsError> {
+    let replay = snake_core::Replay::decode(replay)?;
+    js_sys::JSON::parse(&replay.to_json()).map_err(js_error)
+}
+
+/// State hashes of a `.skr` replay before the first tick and after each
+/// one, 32 bytes per tick, as computed by this wasm build.
+#[wasm_bindgen]
+pub fn replay_hash_trace(replay: &[u8]) -> Result<Vec<u8>, JsError> {
+    let replay = snake_core::Replay::decode(replay)?;
+    let trace = snake_core::conformance::hash_trace(&replay)?;
+    Ok(snake_core::conformance::encode_trace(&trace))
+}
+
+/// Replays a `.skr` file in this wasm build and compares it with `trace`,
+/// a hash trace from the native engine. Returns the first tick where the
+/// two disagree, or `undefined` if every tick matches.
+#[wasm_bindgen]
+pub fn first_divergent_tick(replay: &[u8], trace: &[u8]) -> Result<Option<u32>, JsError> {
+    let replay = snake_core::Replay::decode(replay)?;
+    let expected = snake_core::conformance::decode_trace(trace)?;
+    Ok(snake_core::conformance::check_trace(&replay, &expected)?)
+}
+
+/// Checks that `player`, a 20-byte address, holds `score` on the
+/// leaderboard whose Merkle root is `root`, given the player's encoded
+/// inclusion proof. Throws if an argument is malformed.
+#[wasm_bindgen]
+pub fn verify_leaderboard_inclusion(
+    root: &[u8],
+    player: &[u8],
+    score: u64,
+    proof: &[u8],
+) -> Result<bool, JsError> {
+    let root = root.try_into().map_err(|_| JsError::new("root must be 32 bytes"))?;
+    let player = player.try_into().map_err(|_| JsError::new("player must be 20 bytes"))?;
+    Ok(snake_core::leaderboard::verify_inclusion(root, player, score, proof)?)
+}
+
+/// Seed of the daily challenge on `date`, a `YYYY-MM-DD` day, for games
+/// started some other way, e.g. with `GameState.from_level_json`. Throws if
+/// the date is not a real day.
+#[wasm_bindgen]
+pub fn challenge_seed(date: &str, domain_salt: &[u8]) -> Result<u64, JsError> {
+    Ok(snake_core::challenge::seed_for_date(date, domain_salt)?)
+}
+
+/// A plain JS object with the given fields, passed to event callbacks.
+fn event(fields: &[(&str, JsValue)]) -> JsValue {
+    let object = js_sys::Object::new();
+    for (name, value) in fields {
+        // Setting a property on a fresh plain object cannot fail
+        let _ = js_sys::Reflect::set(&object, &JsValue::from_str(name), value);
+    }
+    object.into()
+}
+
+/// Turns a value thrown by JS, e.g. from an event callback, into a
+/// `JsError`.
+fn js_error(error: JsValue) -> JsError {
+    let message = match error.dyn_ref::<js_sys::Error>() {
+        Some(error) => String::from(error.message()),
+        None => error.as_string().unwrap_or_else(|| "event callback threw".to_owned()),
+    };
+    JsError::new(&message)
+}
+
+/// Parses flat `[x0, y0, x1, y1, ...]` coordinates from JS.
+fn cells(coords: &[i32]) -> Result<Vec<snake_core::Position>, JsError> {
+    if !coords.len().is_multiple_of(2) {
+        return Err(JsError::new("coordinates must come in x, y pairs"));
+    }
+    Ok(coords
+        .chunks_exact(2)
+        .map(|pair| snake_core::Position::new(pair[0], pair[1]))
+        .collect())
+}
+
+/// Verifies an SP1 Groth16 proof of a game in the browser.
+///
+/// `proof` is the on-chain proof encoding (`SP1ProofWithPublicValues::bytes`),
+/// `public_values` the raw committed public values and `vkey_hash` the
+/// program's `bytes32` verification key hash. Throws if the proof is invalid.
+#[wasm_bindgen]
+pub fn verify_groth16(proof: &[u8], public_values: &[u8], vkey_hash: &str) -> Result<(), JsError> {
+    sp1_verifier::Groth16Verifier::verify(
+        proof,
+        public_values,
+        vkey_hash,
+        &sp1_verifier::GROTH16_VK_BYTES,
+    )
+    .map_err(|err| JsError::new(&err.to_string()))
+}
+
+/// Verifies a `.skp` proof bundle of a game in the browser and returns the
+/// public values it commits to.
+///
+/// `vkey_hash` is the `bytes32` verification key hash of the program the
+/// caller trusts; a bundle made by any other program is rejected. Only
+/// Groth16 and PLONK bundles verify outside the SP1 SDK. Throws if the
+/// bundle is malformed or its proof is invalid.
+#[wasm_bindgen]
+pub fn verify_bundle(bundle: &[u8], vkey_hash: &str) -> Result<Vec<u8>, JsError> {
+    let bundle = snake_core::bundle::ProofBundle::decode(bundle)?;
+    if !vkey_hash.trim_start_matches("0x").eq_ignore_ascii_case(&hex(&bundle.vkey_hash)) {
+        return Err(JsError::new("the bundle is for another program"));
+    }
+    verify_onchain(&bundle)?;
+    Ok(bundle.public_values)
+}
+
+/// Like `verify_bundle`, accepting any program registered in `manifest`, a
+/// vkey registry manifest mapping program versions to vkey hashes (see
+/// `snake_core::registry`). Returns the public values. Throws if the
+/// manifest is malformed, the bundle's program is not registered or its
+/// proof is invalid.
+#[wasm_bindgen]
+pub fn verify_registered_bundle(bundle: &[u8], manifest: &str) -> Result<Vec<u8>, JsError> {
+    let registry = snake_core::registry::VkeyRegistry::from_json(manifest)?;
+    let bundle = snake_core::bundle::ProofBundle::decode(bundle)?;
+    registry.check(&bundle)?;
+    verify_onchain(&bundle)?;
+    Ok(bundle.public_values)
+}
+
+/// Checks a Groth16 or PLONK bundle's proof against its own vkey hash,
+/// which the caller has pinned.
+fn verify_onchain(bundle: &snake_core::bundle::ProofBundle) -> Result<(), JsError> {
+    use snake_core::bundle::ProofKind;
+
+    if !bundle.kind.is_onchain() {
+        return Err(JsError::new("only Groth16 and PLONK bundles verify in the browser"));
+    }
+    let vkey_hash = format!("0x{}", hex(&bundle.vkey_hash));
+    let result = match bundle.kind {
+        ProofKind::Groth16 => sp1_verifier::Groth16Verifier::verify(
+            &bundle.proof,
+            &bundle.public_values,
+            &vkey_hash,
+            &sp1_verifier::GROTH16_VK_BYTES,
+        )
+        .map_err(|err| err.to_string()),
+        _ => sp1_verifier::PlonkVerifier::verify(
+            &bundle.proof,
+            &bundle.public_values,
+            &vkey_hash,
+            &sp1_verifier::PLONK_VK_BYTES,
+        )
+        .map_err(|err| err.to_string()),
+    };
+    result.map_err(|err| JsError::new(&err))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(feature = "panic-hook")]
 #[wasm_bindgen]
 pub fn init_panic_hook() {
     console_error_panic_hook::set_once();
-}
\ No newline at end of file
+}
@@ -0,0 +1,92 @@
+//! Streams a game to a server as it is played: a signed delta per tick,
+//! then the signed replay at game over, so the server can follow the game
+//! live and verify it once it ends. Frames are `snake_core::spectate::LiveFrame`
+//! encodings, one per binary WebSocket message.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use snake_core::spectate::{LiveFrame, StateDelta};
+use wasm_bindgen::prelude::*;
+use web_sys::WebSocket;
+
+/// A WebSocket connection a game streams to, attached with
+/// `GameState::attach_stream`.
+#[wasm_bindgen]
+pub struct LiveStream {
+    socket: WebSocket,
+    /// Session key the server issued, which signs every frame.
+    key: Vec<u8>,
+    /// Frames made before the socket opened, sent by `_on_open`.
+    queued: Rc<RefCell<Vec<Vec<u8>>>>,
+    /// The open handler, kept alive as long as the stream.
+    _on_open: Closure<dyn FnMut()>,
+}
+
+#[wasm_bindgen]
+impl LiveStream {
+    /// Opens a connection to `url`, a `ws:` or `wss:` address. Frames sent
+    /// while it connects are held until it opens.
+    #[wasm_bindgen(constructor)]
+    pub fn new(url: &str, key: &[u8]) -> Result<LiveStream, JsError> {
+        let socket = WebSocket::new(url).map_err(crate::js_error)?;
+        socket.set_binary_type(web_sys::BinaryType::Arraybuffer);
+        let queued = Rc::new(RefCell::new(Vec::<Vec<u8>>::new()));
+        let on_open = Closure::<dyn FnMut()>::new({
+            let (socket, queued) = (socket.clone(), Rc::clone(&queued));
+            move || {
+                for bytes in queued.borrow_mut().drain(..) {
+                    let _ = socket.send_with_u8_array(&bytes);
+                }
+            }
+        });
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        Ok(LiveStream {
+            socket,
+            key: key.to_vec(),
+            queued,
+            _on_open: on_open,
+        })
+    }
+
+    /// Whether the connection is open. A stream that closed drops its
+    /// frames; the game plays on regardless.
+    pub fn is_open(&self) -> bool {
+        self.socket.ready_state() == WebSocket::OPEN
+    }
+
+    /// Closes the connection; freeing the stream does too.
+    pub fn close(&self) -> Result<(), JsError> {
+        self.socket.close().map_err(crate::js_error)
+    }
+}
+
+impl LiveStream {
+    /// Sends the delta of one step, followed by the replay if it ended the
+    /// game.
+    pub(crate) fn send_step(&self, delta: StateDelta, game: &snake_core::GameState) {
+        let game_over = delta.game_over;
+        self.send(LiveFrame::Delta(delta));
+        if game_over {
+            self.send(LiveFrame::Finished(snake_core::Replay::from_game(game)));
+        }
+    }
+
+    fn send(&self, frame: LiveFrame) {
+        let bytes = frame.encode_signed(&self.key);
+        match self.socket.ready_state() {
+            WebSocket::CONNECTING => self.queued.borrow_mut().push(bytes),
+            WebSocket::OPEN => {
+                let _ = self.socket.send_with_u8_array(&bytes);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Drop for LiveStream {
+    fn drop(&mut self) {
+        self.socket.set_onopen(None);
+        let _ = self.socket.close();
+    }
+}